@@ -0,0 +1,75 @@
+//! Maps the OpenAI `model` field to router strategies.
+//!
+//! Strategies are exposed under kebab-case names, e.g. `best-local` or `vision`, so clients
+//! get the router's fallback between models and providers. Embedding strategies also answer
+//! to the name of their model, e.g. `bge-m3`.
+
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::providers::ollama_models_api::normalize_model_name;
+use strum::VariantArray;
+
+/// `BestLocal` becomes `best-local`.
+pub fn strategy_id(strategy: &impl std::fmt::Debug) -> String {
+    let mut id = String::new();
+    for (i, c) in format!("{:?}", strategy).chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            id.push('-');
+        }
+        id.push(c.to_ascii_lowercase());
+    }
+    id
+}
+
+/// Embedding strategies the router can serve; remote embedding has no providers yet.
+pub fn embedding_strategies() -> impl Iterator<Item = WellKnownEmbeddingStrategy> {
+    WellKnownEmbeddingStrategy::VARIANTS
+        .iter()
+        .copied()
+        .filter(|strategy| *strategy != WellKnownEmbeddingStrategy::BestRemote)
+}
+
+pub fn generative_text_strategies() -> impl Iterator<Item = WellKnownGenerativeTextStrategy> {
+    WellKnownGenerativeTextStrategy::VARIANTS.iter().copied()
+}
+
+pub fn embedding_strategy(model: &str) -> Option<WellKnownEmbeddingStrategy> {
+    embedding_strategies()
+        .find(|strategy| strategy_id(strategy) == model)
+        .or_else(|| {
+            embedding_strategies().find(|strategy| {
+                normalize_model_name(strategy.get_model().name()) == normalize_model_name(model)
+            })
+        })
+}
+
+pub fn generative_text_strategy(model: &str) -> Option<WellKnownGenerativeTextStrategy> {
+    generative_text_strategies().find(|strategy| strategy_id(strategy) == model)
+}
+
+#[cfg(test)]
+mod test {
+    use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+    use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+
+    use super::embedding_strategy;
+    use super::generative_text_strategy;
+
+    #[test]
+    fn resolves_strategy_and_model_names() {
+        assert_eq!(
+            embedding_strategy("best-local"),
+            Some(WellKnownEmbeddingStrategy::BestLocal)
+        );
+        assert_eq!(
+            embedding_strategy("bge-m3"),
+            Some(WellKnownEmbeddingStrategy::BestLocal)
+        );
+        assert_eq!(embedding_strategy("best-remote"), None);
+        assert_eq!(
+            generative_text_strategy("vision"),
+            Some(WellKnownGenerativeTextStrategy::Vision)
+        );
+        assert_eq!(generative_text_strategy("gpt-4o"), None);
+    }
+}
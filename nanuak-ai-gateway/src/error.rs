@@ -0,0 +1,53 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use serde_json::json;
+use tracing::warn;
+
+/// An error in OpenAI's `{"error": {...}}` shape, so client libraries surface the message.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub kind: &'static str,
+    pub message: String,
+}
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError {
+            status: StatusCode::BAD_REQUEST,
+            kind: "invalid_request_error",
+            message: message.into(),
+        }
+    }
+    pub fn unknown_model(model: &str) -> Self {
+        ApiError {
+            status: StatusCode::NOT_FOUND,
+            kind: "invalid_request_error",
+            message: format!("The model `{}` does not exist, see /v1/models", model),
+        }
+    }
+}
+/// Router errors mean every provider failed, so they are reported as upstream failures.
+impl From<eyre::Report> for ApiError {
+    fn from(error: eyre::Report) -> Self {
+        warn!("Request failed: {:?}", error);
+        ApiError {
+            status: StatusCode::BAD_GATEWAY,
+            kind: "upstream_error",
+            message: format!("{:#}", error),
+        }
+    }
+}
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "message": self.message,
+                "type": self.kind,
+                "code": null,
+            }
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,16 @@
+pub mod catalog;
+pub mod error;
+pub mod openai;
+pub mod routes;
+
+use axum::Router;
+use axum::routing::get;
+use axum::routing::post;
+
+/// The OpenAI-compatible routes, ready to serve.
+pub fn router() -> Router {
+    Router::new()
+        .route("/v1/models", get(routes::list_models))
+        .route("/v1/embeddings", post(routes::create_embeddings))
+        .route("/v1/chat/completions", post(routes::create_chat_completion))
+}
@@ -0,0 +1,51 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use nanuak_ai_gateway::router;
+use nanuak_ai_router::telemetry::install_postgres;
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "OpenAI-compatible API over the Nanuak AI router")]
+struct Cli {
+    /// If set, enable debug logging
+    #[arg(long)]
+    debug: bool,
+
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    #[arg(long, default_value_t = 11435)]
+    port: u16,
+
+    /// Don't record calls to the ai.calls table
+    #[arg(long)]
+    no_telemetry: bool,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+
+    let log_level = if cli.debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
+    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+
+    let _telemetry = (!cli.no_telemetry).then(|| install_postgres("nanuak-ai-gateway"));
+
+    let addr: SocketAddr = format!("{}:{}", cli.host, cli.port).parse()?;
+    info!("Listening on http://{}/v1", addr);
+    axum::Server::bind(&addr)
+        .serve(router().into_make_service())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use axum::Json;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use nanuak_ai_router::answer::Answer;
+use nanuak_ai_router::conversation::Conversation;
+use nanuak_ai_router::conversation::Message;
+use nanuak_ai_router::embedding::Embedding;
+use nanuak_ai_router::embedding_request::EmbeddingPayload;
+use tracing::debug;
+
+use crate::catalog::embedding_strategies;
+use crate::catalog::embedding_strategy;
+use crate::catalog::generative_text_strategies;
+use crate::catalog::generative_text_strategy;
+use crate::catalog::strategy_id;
+use crate::error::ApiError;
+use crate::openai::ChatChoice;
+use crate::openai::ChatCompletionRequest;
+use crate::openai::ChatCompletionResponse;
+use crate::openai::ContentPart;
+use crate::openai::EmbeddingData;
+use crate::openai::EmbeddingVector;
+use crate::openai::EmbeddingsRequest;
+use crate::openai::EmbeddingsResponse;
+use crate::openai::MessageContent;
+use crate::openai::ModelList;
+use crate::openai::ModelObject;
+use crate::openai::ResponseMessage;
+use crate::openai::Usage;
+
+pub async fn list_models() -> Json<ModelList> {
+    let model = |id: String| ModelObject {
+        id,
+        object: "model".to_string(),
+        created: 0,
+        owned_by: "nanuak".to_string(),
+    };
+    let mut ids = Vec::new();
+    for strategy in embedding_strategies() {
+        ids.push(strategy.get_model().name().to_string());
+        ids.push(strategy_id(&strategy));
+    }
+    ids.extend(generative_text_strategies().map(|strategy| strategy_id(&strategy)));
+    let mut data: Vec<ModelObject> = Vec::new();
+    for id in ids {
+        if !data.iter().any(|existing| existing.id == id) {
+            data.push(model(id));
+        }
+    }
+    Json(ModelList {
+        object: "list".to_string(),
+        data,
+    })
+}
+
+pub async fn create_embeddings(
+    Json(request): Json<EmbeddingsRequest>,
+) -> Result<Json<EmbeddingsResponse>, ApiError> {
+    let strategy = embedding_strategy(&request.model)
+        .ok_or_else(|| ApiError::unknown_model(&request.model))?;
+    let base64 = match request.encoding_format.as_deref() {
+        None | Some("float") => false,
+        Some("base64") => true,
+        Some(other) => {
+            return Err(ApiError::bad_request(format!(
+                "Unsupported encoding_format: {}",
+                other
+            )));
+        }
+    };
+    let texts = request.input.into_vec();
+    if texts.is_empty() {
+        return Err(ApiError::bad_request("input must not be empty"));
+    }
+    debug!("Embedding {} inputs with {:?}", texts.len(), strategy);
+    let payloads = texts.into_iter().map(EmbeddingPayload::Text).collect();
+    let embeddings = Embedding::try_generate(strategy, payloads).await?;
+    let mut data = Vec::with_capacity(embeddings.len());
+    for (index, embedding) in embeddings.into_iter().enumerate() {
+        let embedding = match request.dimensions {
+            Some(dimensions) => embedding
+                .truncated(dimensions)
+                .map_err(|error| ApiError::bad_request(format!("{:#}", error)))?,
+            None => embedding,
+        };
+        let embedding = if base64 {
+            let bytes: Vec<u8> = embedding.0.iter().flat_map(|x| x.to_le_bytes()).collect();
+            EmbeddingVector::Base64(STANDARD.encode(bytes))
+        } else {
+            EmbeddingVector::Float(embedding.0)
+        };
+        data.push(EmbeddingData {
+            object: "embedding".to_string(),
+            index,
+            embedding,
+        });
+    }
+    Ok(Json(EmbeddingsResponse {
+        object: "list".to_string(),
+        data,
+        model: request.model,
+        // The router doesn't surface token counts for embeddings.
+        usage: Usage::default(),
+    }))
+}
+
+pub async fn create_chat_completion(
+    Json(request): Json<ChatCompletionRequest>,
+) -> Result<Json<ChatCompletionResponse>, ApiError> {
+    let strategy = generative_text_strategy(&request.model)
+        .ok_or_else(|| ApiError::unknown_model(&request.model))?;
+    if request.stream {
+        return Err(ApiError::bad_request("Streaming is not supported"));
+    }
+    if let Some(format) = &request.response_format
+        && format.kind != "text"
+    {
+        return Err(ApiError::bad_request(format!(
+            "Unsupported response_format: {}",
+            format.kind
+        )));
+    }
+    let mut images = Vec::new();
+    let mut conversation = Conversation::new();
+    for message in request.messages {
+        let mut text = String::new();
+        let mut paths = Vec::new();
+        match message.content {
+            None => {}
+            Some(MessageContent::Text(content)) => text = content,
+            Some(MessageContent::Parts(parts)) => {
+                for part in parts {
+                    match part {
+                        ContentPart::Text { text: part } => {
+                            if !text.is_empty() {
+                                text.push('\n');
+                            }
+                            text.push_str(&part);
+                        }
+                        ContentPart::ImageUrl { image_url } => {
+                            let image = TempImage::from_data_url(&image_url.url).await?;
+                            paths.push(image.path.clone());
+                            images.push(image);
+                        }
+                    }
+                }
+            }
+        }
+        let message = match message.role.as_str() {
+            "system" | "developer" => Message::system(text),
+            "user" => Message::user(text),
+            "assistant" => Message::assistant(text),
+            other => {
+                return Err(ApiError::bad_request(format!(
+                    "Unsupported message role: {}",
+                    other
+                )));
+            }
+        };
+        conversation.push(message.with_images(paths));
+    }
+    let answer = Answer::try_generate(strategy, &conversation).await?;
+    drop(images);
+    let created = chrono::Utc::now().timestamp();
+    Ok(Json(ChatCompletionResponse {
+        id: format!("chatcmpl-{}", chrono::Utc::now().timestamp_micros()),
+        object: "chat.completion".to_string(),
+        created,
+        model: request.model,
+        choices: vec![ChatChoice {
+            index: 0,
+            message: ResponseMessage {
+                role: "assistant".to_string(),
+                content: answer.body,
+            },
+            finish_reason: "stop".to_string(),
+        }],
+    }))
+}
+
+/// An inline image written to disk for the providers, which take image paths.
+struct TempImage {
+    path: PathBuf,
+}
+impl TempImage {
+    async fn from_data_url(url: &str) -> Result<Self, ApiError> {
+        let Some((header, data)) = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(";base64,"))
+        else {
+            return Err(ApiError::bad_request(
+                "Only base64 data: image URLs are supported",
+            ));
+        };
+        // The extension goes into the file name, so only known types are accepted.
+        let extension = match header.to_ascii_lowercase().as_str() {
+            "image/png" => "png",
+            "image/jpeg" | "image/jpg" => "jpg",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            _ => {
+                return Err(ApiError::bad_request(format!(
+                    "Unsupported image type: {}",
+                    header
+                )));
+            }
+        };
+        let bytes = STANDARD
+            .decode(data)
+            .map_err(|error| ApiError::bad_request(format!("Invalid image data: {}", error)))?;
+        let path = std::env::temp_dir().join(format!(
+            "nanuak-ai-gateway-{}-{}.{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            extension
+        ));
+        tokio::fs::write(&path, bytes)
+            .await
+            .map_err(eyre::Report::from)?;
+        Ok(TempImage { path })
+    }
+}
+impl Drop for TempImage {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,136 @@
+use std::net::SocketAddr;
+use std::net::TcpListener;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use nanuak_ai_gateway::openai::ChatCompletionResponse;
+use nanuak_ai_gateway::openai::EmbeddingVector;
+use nanuak_ai_gateway::openai::EmbeddingsResponse;
+use nanuak_ai_gateway::router;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use serde_json::Value;
+use serde_json::json;
+
+/// Serves the gateway on the test's runtime.
+fn serve() -> eyre::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = axum::Server::from_tcp(listener)?.serve(router().into_make_service());
+    tokio::spawn(server);
+    Ok(addr)
+}
+
+#[tokio::test]
+async fn embeddings_match_the_openai_shape() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::new(8).install();
+    let addr = serve()?;
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/v1/embeddings", addr);
+
+    let response: EmbeddingsResponse = client
+        .post(&url)
+        .json(&json!({"model": "mock", "input": ["a cat", "a dog"]}))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(response.data.len(), 2);
+    let EmbeddingVector::Float(first) = &response.data[0].embedding else {
+        eyre::bail!("Expected float embedding");
+    };
+    assert_eq!(first.len(), 8);
+
+    let response: EmbeddingsResponse = client
+        .post(&url)
+        .json(&json!({
+            "model": "mock",
+            "input": "a cat",
+            "encoding_format": "base64",
+            "dimensions": 4,
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let EmbeddingVector::Base64(encoded) = &response.data[0].embedding else {
+        eyre::bail!("Expected base64 embedding");
+    };
+    let decoded: Vec<f32> = STANDARD
+        .decode(encoded)?
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    assert_eq!(decoded.len(), 4);
+    let norm = decoded.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 1.0).abs() < 1e-4, "{}", norm);
+    Ok(())
+}
+
+#[tokio::test]
+async fn chat_completions_answer_and_reject_unknown_models() -> eyre::Result<()> {
+    let mock = MockGenerativeTextProvider::new().with_reply("Hello from the mock");
+    let _mock = mock.clone().install();
+    let addr = serve()?;
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/v1/chat/completions", addr);
+
+    let response: ChatCompletionResponse = client
+        .post(&url)
+        .json(&json!({
+            "model": "mock",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [{"type": "text", "text": "Hi"}]},
+            ],
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(response.choices[0].message.content, "Hello from the mock");
+    let conversation = &mock.conversations()[0];
+    assert_eq!(conversation.messages.len(), 2);
+    assert_eq!(conversation.messages[1].content, "Hi");
+
+    let response = client
+        .post(&url)
+        .json(&json!({"model": "gpt-4o", "messages": []}))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    let error: Value = response.json().await?;
+    assert!(
+        error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("gpt-4o")
+    );
+
+    // The image type becomes the temp file's extension, so only known types get through.
+    for data_url in [
+        "data:image/../../escaped;base64,AAAA",
+        "data:image/svg+xml;base64,AAAA",
+    ] {
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "model": "mock",
+                "messages": [{"role": "user", "content": [
+                    {"type": "image_url", "image_url": {"url": data_url}},
+                ]}],
+            }))
+            .send()
+            .await?;
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::BAD_REQUEST,
+            "{}",
+            data_url
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use eyre::bail;
+use tracing::debug;
+use tracing::warn;
+
+use crate::answer::Answer;
+use crate::conversation::Conversation;
+use crate::conversation::Message;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::tool::Tool;
+use crate::tool::ToolCall;
+use crate::tool::ToolDefinition;
+
+pub const DEFAULT_MAX_STEPS: usize = 8;
+
+/// Drives a model through tool calls until it produces a final answer.
+#[derive(Debug, Clone)]
+pub struct Agent {
+    pub tools: Vec<Tool>,
+    pub max_steps: usize,
+}
+
+impl Agent {
+    pub fn new(tools: Vec<Tool>) -> Self {
+        Agent {
+            tools,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Runs the loop, appending every assistant turn and tool result to `conversation`.
+    pub async fn run(
+        &self,
+        provider: &dyn GenerativeTextProvider,
+        model: &dyn GenerativeTextModel,
+        conversation: &mut Conversation,
+    ) -> eyre::Result<Answer> {
+        let definitions: Vec<ToolDefinition> = self
+            .tools
+            .iter()
+            .map(|tool| tool.definition.clone())
+            .collect();
+        for step in 1..=self.max_steps {
+            let answer = provider
+                .chat_with_tools(model, conversation, &definitions)
+                .await?;
+            conversation.push(
+                Message::assistant(answer.body.clone()).with_tool_calls(answer.tool_calls.clone()),
+            );
+            if answer.tool_calls.is_empty() {
+                debug!("Agent finished after {} steps", step);
+                return Ok(answer);
+            }
+            for call in &answer.tool_calls {
+                let result = self.execute(call).await;
+                conversation.push(Message::tool(call.name.clone(), result));
+            }
+        }
+        bail!(
+            "Agent did not reach a final answer within {} steps",
+            self.max_steps
+        );
+    }
+
+    /// Errors are reported back to the model instead of aborting, so it can correct itself.
+    async fn execute(&self, call: &ToolCall) -> String {
+        let Some(tool) = self.tools.iter().find(|tool| tool.name() == call.name) else {
+            warn!("Model requested unknown tool {}", call.name);
+            return format!("Error: no tool named {:?}", call.name);
+        };
+        debug!("Calling tool {} with {}", call.name, call.arguments);
+        match tool.call(call.arguments.clone()).await {
+            Ok(value) => value.to_string(),
+            Err(error) => {
+                warn!("Tool {} failed: {:?}", call.name, error);
+                format!("Error: {}", error)
+            }
+        }
+    }
+}
@@ -1,10 +1,194 @@
+use std::path::PathBuf;
+
+use eyre::bail;
+use eyre::eyre;
+use futures::future::BoxFuture;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use tracing::warn;
+
+use crate::conversation::Conversation;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use crate::prompt_template::PromptTemplate;
+use crate::prompt_template::PromptTemplateRef;
+use crate::prompt_template::PromptVariables;
+use crate::resilience::ProviderGuard;
+use crate::resilience::ResiliencePolicy;
+use crate::structured_answer::StructuredAnswerExt;
+use crate::tool::ToolCall;
+
 pub struct Answer {
     pub body: String,
+    pub tool_calls: Vec<ToolCall>,
+    /// The template that produced the prompt, if one was used.
+    pub prompt: Option<PromptTemplateRef>,
 }
 impl Answer {
     pub fn new(body: String) -> Self {
         Answer {
             body,
+            tool_calls: Vec::new(),
+            prompt: None,
         }
     }
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
+    pub fn with_prompt(mut self, prompt: Option<PromptTemplateRef>) -> Self {
+        self.prompt = prompt;
+        self
+    }
+    pub async fn try_generate(
+        strategy: WellKnownGenerativeTextStrategy,
+        conversation: &Conversation,
+    ) -> eyre::Result<Self> {
+        Self::try_generate_with_policy(strategy, &strategy.get_policy().await?, conversation).await
+    }
+    /// Like [`Answer::try_generate`], rendering `template` for each candidate model so its
+    /// per-model variant is used.
+    pub async fn try_generate_from_template(
+        strategy: WellKnownGenerativeTextStrategy,
+        template: &PromptTemplate,
+        variables: &PromptVariables,
+        images: Vec<PathBuf>,
+    ) -> eyre::Result<Self> {
+        Self::try_generate_each(strategy, &strategy.get_policy().await?, |model| {
+            Ok(template
+                .render(model.name(), variables)?
+                .to_conversation(images.clone()))
+        })
+        .await
+    }
+    /// Tries each of the strategy's models on each of its providers until one answers.
+    pub async fn try_generate_with_policy(
+        strategy: WellKnownGenerativeTextStrategy,
+        policy: &ResiliencePolicy,
+        conversation: &Conversation,
+    ) -> eyre::Result<Self> {
+        Self::try_generate_each(strategy, policy, |_| Ok(conversation.clone())).await
+    }
+    /// Asks the strategy's models for a reply shaped like `T`, moving on to the next model or
+    /// provider when one fails or can't produce valid output within its retries.
+    pub async fn try_generate_structured<T>(
+        strategy: WellKnownGenerativeTextStrategy,
+        conversation: &Conversation,
+    ) -> eyre::Result<T>
+    where
+        T: DeserializeOwned + JsonSchema + Send + 'static,
+    {
+        try_each_candidate(
+            strategy,
+            &strategy.get_policy().await?,
+            |_| Ok(conversation.clone()),
+            |provider, model, conversation| provider.answer_structured::<T>(model, conversation),
+        )
+        .await
+    }
+    async fn try_generate_each(
+        strategy: WellKnownGenerativeTextStrategy,
+        policy: &ResiliencePolicy,
+        conversation_for: impl Fn(&dyn GenerativeTextModel) -> eyre::Result<Conversation>,
+    ) -> eyre::Result<Self> {
+        try_each_candidate(
+            strategy,
+            policy,
+            conversation_for,
+            |provider, model, conversation| provider.chat(model, conversation),
+        )
+        .await
+    }
+}
+
+/// Runs `call` for each of the strategy's models on each of its providers, under the provider's
+/// [`ProviderGuard`], until one succeeds.
+///
+/// Pairings the provider reports as unavailable are skipped without counting as failures.
+pub(crate) async fn try_each_candidate<T>(
+    strategy: WellKnownGenerativeTextStrategy,
+    policy: &ResiliencePolicy,
+    conversation_for: impl Fn(&dyn GenerativeTextModel) -> eyre::Result<Conversation>,
+    call: impl for<'a> Fn(
+        &'a dyn GenerativeTextProvider,
+        &'a dyn GenerativeTextModel,
+        &'a Conversation,
+    ) -> BoxFuture<'a, eyre::Result<T>>,
+) -> eyre::Result<T> {
+    let providers = strategy.get_providers().await?;
+    if providers.is_empty() {
+        bail!(
+            "Failed to find suitable provider for strategy: {:?}",
+            strategy
+        );
+    }
+    let models = strategy.get_candidate_models().await?;
+    let mut errors = Vec::new();
+    for model in &models {
+        let conversation = &conversation_for(model.as_ref())?;
+        if conversation.has_images() && !model.accepts_images() {
+            debug!("Model {} does not accept images, skipping", model.name());
+            continue;
+        }
+        for provider in &providers {
+            match provider.is_supported(model.as_ref()).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!(
+                        "Model {} not available on {}, skipping",
+                        model.name(),
+                        provider.name()
+                    );
+                    continue;
+                }
+                Err(error) => {
+                    errors.push(format!(
+                        "{} on {}: {}",
+                        model.name(),
+                        provider.name(),
+                        error
+                    ));
+                    continue;
+                }
+            }
+            let guard =
+                ProviderGuard::acquire(&format!("{:?}/{}", strategy, provider.name()), policy);
+            match guard
+                .call(|| call(provider.as_ref(), model.as_ref(), conversation))
+                .await
+            {
+                Ok(answer) => return Ok(answer),
+                Err(error) => {
+                    warn!(
+                        "Model {} on {} failed for strategy {:?}, trying next: {}",
+                        model.name(),
+                        provider.name(),
+                        strategy,
+                        error
+                    );
+                    errors.push(format!(
+                        "{} on {}: {}",
+                        model.name(),
+                        provider.name(),
+                        error
+                    ));
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        bail!(
+            "None of the models for strategy {:?} are available: {}",
+            strategy,
+            models.iter().map(|model| model.name()).join(", ")
+        );
+    }
+    Err(eyre!(
+        "All providers failed for strategy {:?}:\n{}",
+        strategy,
+        errors.iter().join("\n")
+    ))
 }
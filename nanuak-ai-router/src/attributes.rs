@@ -3,15 +3,20 @@ use uom::si::f32::InformationRate;
 use uom::si::f32::Ratio;
 use uom::si::f32::Time;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Residency {
     Local,
     RemoteSameCountry,
     RemoteAnywhere,
 }
+#[derive(Debug, Clone, Copy)]
 pub struct VramRequirement(pub Information);
 
+#[derive(Debug, Clone, Copy)]
 pub struct Latency(pub Time);
+#[derive(Debug, Clone, Copy)]
 pub struct Accuracy(pub Ratio);
+#[derive(Debug, Clone, Copy)]
 pub struct Throughput(pub InformationRate);
+#[derive(Debug, Clone, Copy)]
 pub struct ContextSize(pub u32);
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use eyre::Context;
+use eyre::bail;
+use nanuak_config::dirs::get_project_dirs;
+use serde::Deserialize;
+use serde::Serialize;
+use strum::VariantArray;
+use tracing::info;
+use tracing::warn;
+use uom::si::f32::Information;
+use uom::si::f32::InformationRate;
+use uom::si::f32::Ratio;
+use uom::si::f32::Time;
+use uom::si::information::byte;
+use uom::si::information_rate::byte_per_second;
+use uom::si::ratio::ratio;
+use uom::si::time::second;
+
+use crate::attributes::Accuracy;
+use crate::attributes::Latency;
+use crate::attributes::Throughput;
+use crate::attributes::VramRequirement;
+use crate::conversation::Conversation;
+use crate::conversation::Message;
+use crate::embedding_model::EmbeddingModel;
+use crate::embedding_provider::EmbeddingProvider;
+use crate::embedding_request::EmbeddingPayload;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::model_attributes::ModelAttributes;
+use crate::provider_registry::get_embedding_providers;
+use crate::provider_registry::get_generative_text_providers;
+use crate::retrieval_eval::EvalHistory;
+use crate::well_known_embedding_models::WellKnownEmbeddingModels;
+use crate::well_known_generative_text_models::WellKnownGenerativeTextModels;
+
+const SAMPLE_TEXTS: &[&str] = &[
+    "How to cook the perfect steak in a cast iron pan",
+    "A beginner's guide to the Rust borrow checker",
+    "Top 10 moments from the 2024 Olympics",
+    "Why is the sky blue? Rayleigh scattering explained",
+    "Building a Minecraft redstone computer from scratch",
+    "Lofi hip hop radio - beats to relax/study to",
+    "The history of the Roman Empire in 20 minutes",
+    "Unboxing the new mechanical keyboard I ordered",
+];
+
+const SAMPLE_PROMPT: &str = "In two sentences, explain what an embedding vector is.";
+
+/// Questions with one short right answer, for a rough accuracy score on generative models.
+const QUIZ: &[(&str, &str)] = &[
+    (
+        "What is 12 multiplied by 12? Reply with only the number.",
+        "144",
+    ),
+    (
+        "What is the capital of France? Reply with one word.",
+        "paris",
+    ),
+    (
+        "Which planet is closest to the sun? Reply with one word.",
+        "mercury",
+    ),
+    (
+        "What is the chemical symbol for gold? Reply with only the symbol.",
+        "au",
+    ),
+    (
+        "How many sides does a hexagon have? Reply with only the number.",
+        "6",
+    ),
+    ("What is 17 plus 26? Reply with only the number.", "43"),
+    (
+        "Which gas do plants absorb from the air for photosynthesis? Reply with its chemical formula.",
+        "co2",
+    ),
+    (
+        "If it is Monday today, what day is it in three days? Reply with one word.",
+        "thursday",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkKind {
+    Embedding,
+    GenerativeText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkRecord {
+    pub provider: String,
+    pub model: String,
+    pub kind: BenchmarkKind,
+    pub iterations: usize,
+    pub mean_latency_seconds: f32,
+    /// Embedding input bytes, or generated output bytes, per second.
+    pub throughput_bytes_per_second: f32,
+    pub vram_bytes: Option<u64>,
+    /// Share of the quiz answered correctly, for generative models.
+    #[serde(default)]
+    pub accuracy: Option<f32>,
+    pub measured_at_unix_seconds: u64,
+}
+
+impl BenchmarkRecord {
+    fn new(
+        provider: &str,
+        model: &str,
+        kind: BenchmarkKind,
+        iterations: usize,
+        total: Duration,
+        total_bytes: usize,
+    ) -> Self {
+        let seconds = total.as_secs_f32().max(f32::EPSILON);
+        BenchmarkRecord {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            kind,
+            iterations,
+            mean_latency_seconds: seconds / iterations as f32,
+            throughput_bytes_per_second: total_bytes as f32 / seconds,
+            vram_bytes: None,
+            accuracy: None,
+            measured_at_unix_seconds: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Fills the measured fields of `attributes`, keeping anything already known.
+    pub fn apply_to(&self, attributes: &mut ModelAttributes) {
+        attributes.latency = Some(Latency(Time::new::<second>(self.mean_latency_seconds)));
+        attributes.throughput = Some(Throughput(InformationRate::new::<byte_per_second>(
+            self.throughput_bytes_per_second,
+        )));
+        if let Some(accuracy) = self.accuracy {
+            attributes.accuracy = Some(Accuracy(Ratio::new::<ratio>(accuracy)));
+        }
+        if attributes.vram_requirement.is_none() {
+            attributes.vram_requirement = self
+                .vram_bytes
+                .map(|bytes| VramRequirement(Information::new::<byte>(bytes as f32)));
+        }
+    }
+}
+
+/// Benchmark results persisted in the project data dir, keyed by provider and model.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BenchmarkStore {
+    pub records: BTreeMap<String, BenchmarkRecord>,
+}
+
+impl BenchmarkStore {
+    fn key(provider: &str, model: &str) -> String {
+        format!("{}/{}", provider, model)
+    }
+    pub fn default_path() -> eyre::Result<PathBuf> {
+        Ok(get_project_dirs()?.data_dir().join("model_benchmarks.json"))
+    }
+    pub async fn load() -> eyre::Result<Self> {
+        Self::load_from(&Self::default_path()?).await
+    }
+    pub async fn load_from(path: &Path) -> eyre::Result<Self> {
+        if !tokio::fs::try_exists(path).await? {
+            return Ok(BenchmarkStore::default());
+        }
+        let json = tokio::fs::read_to_string(path).await?;
+        serde_json::from_str(&json)
+            .wrap_err_with(|| format!("Failed to parse benchmarks: {}", path.display()))
+    }
+    pub async fn save(&self) -> eyre::Result<()> {
+        self.save_to(&Self::default_path()?).await
+    }
+    pub async fn save_to(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string_pretty(self)?)
+            .await
+            .wrap_err_with(|| format!("Failed to write benchmarks: {}", path.display()))
+    }
+    pub fn get(&self, provider: &str, model: &str) -> Option<&BenchmarkRecord> {
+        self.records.get(&Self::key(provider, model))
+    }
+    pub fn insert(&mut self, record: BenchmarkRecord) {
+        self.records
+            .insert(Self::key(&record.provider, &record.model), record);
+    }
+}
+
+/// Overlays the stored benchmark for `provider`/`model`, if any, onto `attributes`, along with
+/// the accuracy from its retrieval evaluations.
+pub async fn measured_attributes(
+    provider: &str,
+    model: &str,
+    attributes: ModelAttributes,
+) -> ModelAttributes {
+    let mut attributes = attributes;
+    match BenchmarkStore::load().await {
+        Ok(store) => {
+            if let Some(record) = store.get(provider, model) {
+                record.apply_to(&mut attributes);
+            }
+        }
+        Err(error) => warn!("Failed to load benchmark store: {:?}", error),
+    }
+    let accuracy = match EvalHistory::open() {
+        Ok(history) => history.accuracy(provider, model).await,
+        Err(error) => Err(error),
+    };
+    match accuracy {
+        Ok(Some(accuracy)) => {
+            attributes.accuracy = Some(Accuracy(Ratio::new::<ratio>(accuracy as f32)));
+        }
+        Ok(None) => {}
+        Err(error) => warn!("Failed to load retrieval evaluations: {:?}", error),
+    }
+    attributes
+}
+
+pub async fn benchmark_embedding(
+    provider: &dyn EmbeddingProvider,
+    model: &dyn EmbeddingModel,
+    iterations: usize,
+) -> eyre::Result<BenchmarkRecord> {
+    if iterations == 0 {
+        bail!("Benchmark needs at least one iteration");
+    }
+    let payloads: Vec<EmbeddingPayload> = SAMPLE_TEXTS
+        .iter()
+        .map(|text| EmbeddingPayload::Text(text.to_string()))
+        .collect();
+    let bytes_per_iteration: usize = SAMPLE_TEXTS.iter().map(|text| text.len()).sum();
+    // Warm up so model loading isn't counted as latency.
+    provider.get_embeddings(model, payloads.clone()).await?;
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        provider.get_embeddings(model, payloads.clone()).await?;
+        total += start.elapsed();
+    }
+    let mut record = BenchmarkRecord::new(
+        provider.name(),
+        model.name(),
+        BenchmarkKind::Embedding,
+        iterations,
+        total,
+        bytes_per_iteration * iterations,
+    );
+    record.vram_bytes = vram_bytes(provider.get_attributes(model).await?);
+    Ok(record)
+}
+
+pub async fn benchmark_generative_text(
+    provider: &dyn GenerativeTextProvider,
+    model: &dyn GenerativeTextModel,
+    iterations: usize,
+) -> eyre::Result<BenchmarkRecord> {
+    if iterations == 0 {
+        bail!("Benchmark needs at least one iteration");
+    }
+    let conversation = Conversation::new().with_message(Message::user(SAMPLE_PROMPT.to_string()));
+    provider.chat(model, &conversation).await?;
+    let mut total = Duration::ZERO;
+    let mut total_bytes = 0;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let answer = provider.chat(model, &conversation).await?;
+        total += start.elapsed();
+        total_bytes += answer.body.len();
+    }
+    let mut correct = 0;
+    for (question, expected) in QUIZ {
+        let conversation = Conversation::new().with_message(Message::user(question.to_string()));
+        let answer = provider.chat(model, &conversation).await?;
+        if is_correct(&answer.body, expected) {
+            correct += 1;
+        }
+    }
+    let mut record = BenchmarkRecord::new(
+        provider.name(),
+        model.name(),
+        BenchmarkKind::GenerativeText,
+        iterations,
+        total,
+        total_bytes,
+    );
+    record.vram_bytes = vram_bytes(provider.get_attributes(model).await?);
+    record.accuracy = Some(correct as f32 / QUIZ.len() as f32);
+    Ok(record)
+}
+
+/// Whether `expected` appears in `answer` as a whole word, ignoring case and punctuation.
+fn is_correct(answer: &str, expected: &str) -> bool {
+    answer
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word == expected)
+}
+
+fn vram_bytes(attributes: ModelAttributes) -> Option<u64> {
+    attributes
+        .vram_requirement
+        .map(|VramRequirement(vram)| vram.get::<byte>() as u64)
+}
+
+/// Benchmarks every supported pairing in the registries and persists the results.
+pub async fn run_benchmarks(iterations: usize) -> eyre::Result<Vec<BenchmarkRecord>> {
+    let mut store = BenchmarkStore::load().await?;
+    let mut records = Vec::new();
+    for provider in get_embedding_providers().await? {
+        for model_kind in WellKnownEmbeddingModels::VARIANTS {
+            let model = model_kind.get();
+            if !provider.is_supported(model.as_ref()).await.unwrap_or(false) {
+                info!(
+                    "Skipping {} on {}: not available",
+                    model.name(),
+                    provider.name()
+                );
+                continue;
+            }
+            info!("Benchmarking {} on {}", model.name(), provider.name());
+            match benchmark_embedding(provider.as_ref(), model.as_ref(), iterations).await {
+                Ok(record) => records.push(record),
+                Err(error) => warn!("Benchmark of {} failed: {:?}", model.name(), error),
+            }
+        }
+    }
+    for provider in get_generative_text_providers().await? {
+        for model_kind in WellKnownGenerativeTextModels::VARIANTS {
+            let model = model_kind.get();
+            if !provider.is_supported(model.as_ref()).await.unwrap_or(false) {
+                info!(
+                    "Skipping {} on {}: not available",
+                    model.name(),
+                    provider.name()
+                );
+                continue;
+            }
+            info!("Benchmarking {} on {}", model.name(), provider.name());
+            match benchmark_generative_text(provider.as_ref(), model.as_ref(), iterations).await {
+                Ok(record) => records.push(record),
+                Err(error) => warn!("Benchmark of {} failed: {:?}", model.name(), error),
+            }
+        }
+    }
+    for record in &records {
+        store.insert(record.clone());
+    }
+    store.save().await?;
+    Ok(records)
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_correct;
+
+    #[test]
+    fn quiz_answers_match_whole_words() {
+        assert!(is_correct("Paris.", "paris"));
+        assert!(is_correct("The answer is 144", "144"));
+        assert!(is_correct("CO2", "co2"));
+        assert!(!is_correct("1440", "144"));
+        assert!(!is_correct("Comparison", "paris"));
+    }
+}
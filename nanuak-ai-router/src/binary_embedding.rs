@@ -0,0 +1,40 @@
+use eyre::OptionExt;
+use eyre::bail;
+use pgvector::Bit;
+
+/// An [`crate::embedding::Embedding`] reduced to its sign bits, packed most significant
+/// bit first. Good for a fast first pass before rescoring with full vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryEmbedding {
+    pub bytes: Vec<u8>,
+    pub dimensions: usize,
+}
+impl BinaryEmbedding {
+    pub fn hamming_distance(&self, other: &Self) -> eyre::Result<u32> {
+        use simsimd::BinarySimilarity;
+        if self.dimensions != other.dimensions {
+            bail!(
+                "Cannot compare {} and {} dimensional embeddings",
+                self.dimensions,
+                other.dimensions
+            );
+        }
+        let distance = u8::hamming(&self.bytes, &other.bytes)
+            .ok_or_eyre("Vectors must be of the same length")?;
+        Ok(distance as u32)
+    }
+    pub fn to_bit(&self) -> Bit {
+        if self.dimensions.is_multiple_of(8) {
+            return Bit::from_bytes(&self.bytes);
+        }
+        let bits: Vec<bool> = (0..self.dimensions)
+            .map(|i| self.bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect();
+        Bit::new(&bits)
+    }
+}
+impl From<&BinaryEmbedding> for Bit {
+    fn from(embedding: &BinaryEmbedding) -> Self {
+        embedding.to_bit()
+    }
+}
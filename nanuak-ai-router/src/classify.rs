@@ -0,0 +1,419 @@
+//! Zero-shot classification of text into a caller's own taxonomy.
+//!
+//! Embedding mode compares each item to each label by cosine similarity, LLM mode asks a chat
+//! model to rate every label, and hybrid mode lets the LLM rate only the labels embeddings
+//! shortlist. Scores are calibrated to `0..=1` so one threshold works across modes: with a
+//! single label per item they sum to 1, with multi-label each is an independent probability.
+
+use std::collections::HashMap;
+
+use eyre::bail;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::answer::try_each_candidate;
+use crate::embedding::Embedding;
+use crate::embedding_request::EmbeddingPayload;
+use crate::embedding_strategy::WellKnownEmbeddingStrategy;
+use crate::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use crate::prompt_registry::PromptRegistry;
+use crate::prompt_template::PromptVariables;
+use crate::structured_answer::StructuredAnswerExt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassificationMode {
+    Embedding,
+    Llm,
+    Hybrid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassLabel {
+    pub name: String,
+    /// Shown to the models alongside the name; helps when names are terse.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Overrides [`ClassifyOptions::threshold`] for this label.
+    #[serde(default)]
+    pub threshold: Option<f64>,
+}
+impl ClassLabel {
+    pub fn new(name: impl Into<String>) -> Self {
+        ClassLabel {
+            name: name.into(),
+            description: None,
+            threshold: None,
+        }
+    }
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+    fn text(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{}: {}", self.name, description),
+            None => self.name.clone(),
+        }
+    }
+}
+impl From<&str> for ClassLabel {
+    fn from(name: &str) -> Self {
+        ClassLabel::new(name)
+    }
+}
+
+/// Maps cosine similarity to a probability with a logistic curve, `1 / (1 + e^-(a·s + b))`.
+///
+/// Similarities cluster differently per model, so fit this to a few labelled examples with
+/// [`EmbeddingCalibration::fit`] rather than relying on the default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingCalibration {
+    pub slope: f64,
+    pub intercept: f64,
+}
+impl Default for EmbeddingCalibration {
+    /// Centred on 0.5 similarity, about where bge-m3 separates related from unrelated text.
+    fn default() -> Self {
+        EmbeddingCalibration {
+            slope: 20.0,
+            intercept: -10.0,
+        }
+    }
+}
+impl EmbeddingCalibration {
+    pub fn probability(&self, similarity: f64) -> f64 {
+        sigmoid(self.slope * similarity + self.intercept)
+    }
+    /// Platt scaling: logistic regression of `(similarity, applies)` examples by gradient
+    /// descent. Needs at least one positive and one negative example.
+    pub fn fit(examples: &[(f64, bool)]) -> eyre::Result<Self> {
+        let positives = examples.iter().filter(|(_, applies)| *applies).count();
+        if positives == 0 || positives == examples.len() {
+            bail!("Calibration needs both matching and non-matching examples");
+        }
+        // Platt's smoothed targets keep the fit from running off to infinite slope.
+        let high = (positives as f64 + 1.0) / (positives as f64 + 2.0);
+        let low = 1.0 / ((examples.len() - positives) as f64 + 2.0);
+        let (mut slope, mut intercept) = (1.0, 0.0);
+        let rate = 1.0;
+        for _ in 0..20_000 {
+            let (mut d_slope, mut d_intercept) = (0.0, 0.0);
+            for (similarity, applies) in examples {
+                let target = if *applies { high } else { low };
+                let error = sigmoid(slope * similarity + intercept) - target;
+                d_slope += error * similarity;
+                d_intercept += error;
+            }
+            slope -= rate * d_slope / examples.len() as f64;
+            intercept -= rate * d_intercept / examples.len() as f64;
+        }
+        Ok(EmbeddingCalibration { slope, intercept })
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassifyOptions {
+    pub mode: ClassificationMode,
+    /// Assign every label over its threshold instead of only the best one.
+    pub multi_label: bool,
+    /// Minimum calibrated score for a label to be assigned.
+    pub threshold: f64,
+    pub embedding_strategy: WellKnownEmbeddingStrategy,
+    pub generative_text_strategy: WellKnownGenerativeTextStrategy,
+    pub calibration: EmbeddingCalibration,
+    /// Softmax temperature over similarities for single-label embedding scores.
+    pub temperature: f64,
+    /// How many embedding-ranked labels the LLM rates in hybrid mode.
+    pub shortlist: usize,
+    /// Weight of the LLM's rating against the embedding score in hybrid mode.
+    pub llm_weight: f64,
+}
+impl Default for ClassifyOptions {
+    fn default() -> Self {
+        ClassifyOptions {
+            mode: ClassificationMode::Embedding,
+            multi_label: false,
+            threshold: 0.5,
+            embedding_strategy: WellKnownEmbeddingStrategy::BestLocal,
+            generative_text_strategy: WellKnownGenerativeTextStrategy::Fast,
+            calibration: EmbeddingCalibration::default(),
+            temperature: 0.05,
+            shortlist: 5,
+            llm_weight: 0.7,
+        }
+    }
+}
+impl ClassifyOptions {
+    pub fn new(mode: ClassificationMode) -> Self {
+        ClassifyOptions {
+            mode,
+            ..Default::default()
+        }
+    }
+    pub fn multi_label(mut self) -> Self {
+        self.multi_label = true;
+        self
+    }
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabelScore {
+    pub label: String,
+    /// Calibrated, in `0..=1`.
+    pub score: f64,
+    /// Raw cosine similarity, when embeddings were used.
+    pub similarity: Option<f64>,
+    /// The LLM's rating scaled to `0..=1`, when it rated this label.
+    pub llm_rating: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Classification {
+    pub item: String,
+    /// Every label, best first.
+    pub scores: Vec<LabelScore>,
+    /// Labels over their threshold; at most one unless multi-label.
+    pub assigned: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct LabelRatings {
+    ratings: Vec<LabelRating>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct LabelRating {
+    label: String,
+    /// 0 to 10
+    score: u8,
+}
+
+pub async fn classify(
+    items: &[String],
+    labels: &[ClassLabel],
+    options: &ClassifyOptions,
+) -> eyre::Result<Vec<Classification>> {
+    if labels.is_empty() {
+        bail!("Classification needs at least one label");
+    }
+    let similarities = match options.mode {
+        ClassificationMode::Llm => None,
+        _ => Some(label_similarities(items, labels, options.embedding_strategy).await?),
+    };
+    let mut classifications = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let similarities = similarities.as_ref().map(|all| &all[i]);
+        let mut scores: Vec<LabelScore> = labels
+            .iter()
+            .enumerate()
+            .map(|(j, label)| LabelScore {
+                label: label.name.clone(),
+                score: 0.0,
+                similarity: similarities.map(|row| row[j]),
+                llm_rating: None,
+            })
+            .collect();
+        let embedding_scores = similarities.map(|row| embedding_scores(row, options));
+        let judged: Vec<usize> = match (options.mode, &embedding_scores) {
+            (ClassificationMode::Embedding, _) => Vec::new(),
+            (ClassificationMode::Llm, _) => (0..labels.len()).collect(),
+            (ClassificationMode::Hybrid, Some(embedding)) => {
+                let mut ranked: Vec<usize> = (0..labels.len()).collect();
+                ranked.sort_by(|a, b| embedding[*b].total_cmp(&embedding[*a]));
+                ranked.truncate(options.shortlist.max(1));
+                ranked
+            }
+            (ClassificationMode::Hybrid, None) => {
+                bail!("Hybrid classification needs embedding scores to shortlist labels")
+            }
+        };
+        if !judged.is_empty() {
+            let judged_labels: Vec<&ClassLabel> = judged.iter().map(|j| &labels[*j]).collect();
+            let ratings = rate_labels(item, &judged_labels, options).await?;
+            for (j, rating) in judged.iter().zip(ratings) {
+                scores[*j].llm_rating = Some(rating);
+            }
+        }
+        let llm_scores = calibrate_ratings(&scores, options.multi_label);
+        for (j, score) in scores.iter_mut().enumerate() {
+            score.score = match (&embedding_scores, llm_scores[j]) {
+                (Some(embedding), Some(llm)) => {
+                    options.llm_weight * llm + (1.0 - options.llm_weight) * embedding[j]
+                }
+                // Labels the hybrid shortlist left out can't outrank the ones it kept.
+                (Some(embedding), None) if options.mode == ClassificationMode::Hybrid => {
+                    (1.0 - options.llm_weight) * embedding[j]
+                }
+                (Some(embedding), None) => embedding[j],
+                (None, llm) => llm.unwrap_or_default(),
+            };
+        }
+        classifications.push(assign(item, labels, scores, options));
+    }
+    Ok(classifications)
+}
+
+/// Cosine similarity of every item to every label, one row per item.
+async fn label_similarities(
+    items: &[String],
+    labels: &[ClassLabel],
+    strategy: WellKnownEmbeddingStrategy,
+) -> eyre::Result<Vec<Vec<f64>>> {
+    let payloads = labels
+        .iter()
+        .map(ClassLabel::text)
+        .chain(items.iter().cloned())
+        .map(EmbeddingPayload::Text)
+        .collect();
+    let embeddings = Embedding::try_generate(strategy, payloads).await?;
+    let (label_embeddings, item_embeddings) = embeddings.split_at(labels.len());
+    item_embeddings
+        .iter()
+        .map(|item| {
+            label_embeddings
+                .iter()
+                .map(|label| item.cosine_similarity(label))
+                .collect()
+        })
+        .collect()
+}
+
+fn embedding_scores(similarities: &[f64], options: &ClassifyOptions) -> Vec<f64> {
+    if options.multi_label {
+        return similarities
+            .iter()
+            .map(|similarity| options.calibration.probability(*similarity))
+            .collect();
+    }
+    let max = similarities.iter().copied().fold(f64::MIN, f64::max);
+    let weights: Vec<f64> = similarities
+        .iter()
+        .map(|similarity| ((similarity - max) / options.temperature.max(1e-6)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+/// LLM ratings as probabilities; normalized over the rated labels for single-label.
+fn calibrate_ratings(scores: &[LabelScore], multi_label: bool) -> Vec<Option<f64>> {
+    let total: f64 = scores.iter().filter_map(|score| score.llm_rating).sum();
+    scores
+        .iter()
+        .map(|score| {
+            let rating = score.llm_rating?;
+            Some(match multi_label {
+                true => rating,
+                false if total > 0.0 => rating / total,
+                false => 0.0,
+            })
+        })
+        .collect()
+}
+
+/// Ratings in `0..=1` for each label, in order; labels the model skipped get 0.
+async fn rate_labels(
+    item: &str,
+    labels: &[&ClassLabel],
+    options: &ClassifyOptions,
+) -> eyre::Result<Vec<f64>> {
+    let template = PromptRegistry::global().await?.get("classify")?;
+    let variables = PromptVariables::new().with("item", item).with(
+        "labels",
+        labels.iter().map(|label| label.text()).collect::<Vec<_>>(),
+    );
+    let strategy = options.generative_text_strategy;
+    let answer: LabelRatings = try_each_candidate(
+        strategy,
+        &strategy.get_policy().await?,
+        |model| {
+            Ok(template
+                .render(model.name(), &variables)?
+                .to_conversation(Vec::new()))
+        },
+        |provider, model, conversation| provider.answer_structured(model, conversation),
+    )
+    .await?;
+    let ratings: HashMap<String, u8> = answer
+        .ratings
+        .into_iter()
+        .map(|rating| (rating.label.trim().to_lowercase(), rating.score.min(10)))
+        .collect();
+    Ok(labels
+        .iter()
+        .map(|label| {
+            ratings
+                .get(&label.name.to_lowercase())
+                .map(|score| *score as f64 / 10.0)
+                .unwrap_or_default()
+        })
+        .collect())
+}
+
+fn assign(
+    item: &str,
+    labels: &[ClassLabel],
+    scores: Vec<LabelScore>,
+    options: &ClassifyOptions,
+) -> Classification {
+    let threshold = |name: &str| {
+        labels
+            .iter()
+            .find(|label| label.name == name)
+            .and_then(|label| label.threshold)
+            .unwrap_or(options.threshold)
+    };
+    let mut scores = scores;
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let passing = scores
+        .iter()
+        .filter(|score| score.score >= threshold(&score.label))
+        .map(|score| score.label.clone());
+    let assigned = match options.multi_label {
+        true => passing.collect(),
+        false => passing.take(1).collect(),
+    };
+    Classification {
+        item: item.to_string(),
+        scores,
+        assigned,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EmbeddingCalibration;
+
+    #[test]
+    fn fits_calibration_to_examples() -> eyre::Result<()> {
+        let examples = [
+            (0.72, true),
+            (0.65, true),
+            (0.61, true),
+            (0.48, false),
+            (0.41, false),
+            (0.35, false),
+            (0.58, false),
+            (0.60, true),
+        ];
+        let calibration = EmbeddingCalibration::fit(&examples)?;
+        assert!(calibration.probability(0.7) > 0.5);
+        assert!(calibration.probability(0.4) < 0.5);
+        assert!(calibration.probability(0.65) > calibration.probability(0.55));
+        assert!(EmbeddingCalibration::fit(&[(0.5, true)]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use eyre::Context;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::answer::Answer;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::prompt_template::PromptTemplateRef;
+use crate::tool::ToolCall;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For [`Role::Tool`] messages, the tool that produced the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl Message {
+    pub fn new(role: Role, content: String) -> Self {
+        Message {
+            role,
+            content,
+            images: Vec::new(),
+            tool_calls: Vec::new(),
+            tool_name: None,
+        }
+    }
+    pub fn system(content: String) -> Self {
+        Message::new(Role::System, content)
+    }
+    pub fn user(content: String) -> Self {
+        Message::new(Role::User, content)
+    }
+    pub fn assistant(content: String) -> Self {
+        Message::new(Role::Assistant, content)
+    }
+    pub fn tool(tool_name: String, content: String) -> Self {
+        Message {
+            tool_name: Some(tool_name),
+            ..Message::new(Role::Tool, content)
+        }
+    }
+    pub fn with_images(mut self, images: Vec<PathBuf>) -> Self {
+        self.images = images;
+        self
+    }
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
+}
+
+/// An ordered chat history that can be sent to a [`GenerativeTextProvider`] and resumed later.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Conversation {
+    pub messages: Vec<Message>,
+    /// The template the conversation was started from, recorded with each answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<PromptTemplateRef>,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Conversation::default()
+    }
+    pub fn with_system_prompt(mut self, prompt: String) -> Self {
+        self.messages.retain(|message| message.role != Role::System);
+        self.messages.insert(0, Message::system(prompt));
+        self
+    }
+    pub fn with_message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+    pub fn with_prompt(mut self, prompt: PromptTemplateRef) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+    pub fn has_images(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|message| !message.images.is_empty())
+    }
+    pub fn last_assistant_message(&self) -> Option<&Message> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::Assistant)
+    }
+
+    /// Appends `message`, asks the provider for a reply, and records the reply in the history.
+    /// If the provider fails, the history is left as it was.
+    pub async fn continue_with(
+        &mut self,
+        provider: &dyn GenerativeTextProvider,
+        model: &dyn GenerativeTextModel,
+        message: Message,
+    ) -> eyre::Result<Answer> {
+        self.messages.push(message);
+        let answer = match provider.chat(model, self).await {
+            Ok(answer) => answer,
+            Err(error) => {
+                self.messages.pop();
+                return Err(error);
+            }
+        };
+        self.messages.push(
+            Message::assistant(answer.body.clone()).with_tool_calls(answer.tool_calls.clone()),
+        );
+        Ok(answer)
+    }
+
+    pub async fn load(path: &Path) -> eyre::Result<Self> {
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .wrap_err_with(|| format!("Failed to read conversation: {}", path.display()))?;
+        serde_json::from_str(&json)
+            .wrap_err_with(|| format!("Failed to parse conversation: {}", path.display()))
+    }
+    pub async fn save(&self, path: &Path) -> eyre::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, json)
+            .await
+            .wrap_err_with(|| format!("Failed to write conversation: {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Conversation;
+    use super::Message;
+    use super::Role;
+
+    #[test]
+    fn round_trips_through_json() -> eyre::Result<()> {
+        let conversation = Conversation::new()
+            .with_message(Message::user("Hi".to_string()))
+            .with_message(Message::assistant("Hello!".to_string()))
+            .with_system_prompt("Be brief.".to_string());
+        let json = serde_json::to_string(&conversation)?;
+        let restored: Conversation = serde_json::from_str(&json)?;
+        let roles = restored.messages.iter().map(|m| m.role).collect::<Vec<_>>();
+        assert_eq!(roles, vec![Role::System, Role::User, Role::Assistant]);
+        assert_eq!(
+            restored
+                .last_assistant_message()
+                .map(|m| m.content.as_str()),
+            Some("Hello!")
+        );
+        Ok(())
+    }
+}
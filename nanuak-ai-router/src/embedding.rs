@@ -1,13 +1,19 @@
 use eyre::OptionExt;
 use eyre::bail;
-use strum::VariantArray;
+use eyre::eyre;
+use itertools::Itertools;
+use pgvector::HalfVector;
+use pgvector::Vector;
+use tracing::warn;
 
-use crate::attributes::Residency;
-use crate::embedding_provider::EmbeddingProvider;
+use crate::binary_embedding::BinaryEmbedding;
 use crate::embedding_request::EmbeddingPayload;
 use crate::embedding_strategy::WellKnownEmbeddingStrategy;
-use crate::well_known_embedding_providers::WellKnownEmbeddingProviders;
+use crate::int8_embedding::Int8Embedding;
+use crate::resilience::ProviderGuard;
+use crate::resilience::ResiliencePolicy;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Embedding(pub Vec<f32>);
 
 impl Embedding {
@@ -15,30 +21,129 @@ impl Embedding {
         strategy: WellKnownEmbeddingStrategy,
         payloads: Vec<EmbeddingPayload>,
     ) -> eyre::Result<Vec<Self>> {
-        let WellKnownEmbeddingStrategy::BestLocal = strategy else {
+        Self::try_generate_with_policy(strategy, &strategy.get_policy().await?, payloads).await
+    }
+    /// Tries each of the strategy's providers in turn until one returns a full batch.
+    pub async fn try_generate_with_policy(
+        strategy: WellKnownEmbeddingStrategy,
+        policy: &ResiliencePolicy,
+        payloads: Vec<EmbeddingPayload>,
+    ) -> eyre::Result<Vec<Self>> {
+        if let WellKnownEmbeddingStrategy::BestRemote = strategy {
             bail!("Strategy not supported: {:?}", strategy);
-        };
-        let mut providers = WellKnownEmbeddingProviders::VARIANTS
-            .iter()
-            .map(|provider| provider.get())
-            .filter(|provider| provider.get_residency() == Residency::Local);
-        let Some(chosen_provider) = providers.next() else {
+        }
+        let providers = strategy.get_providers().await?;
+        if providers.is_empty() {
             bail!(
                 "Failed to find suitable provider for strategy: {:?}",
                 strategy
             );
-        };
+        }
         let model = strategy.get_model();
         let expected = payloads.len();
-        let embeddings = chosen_provider
-            .get_embeddings(model.as_ref(), payloads)
-            .await?;
-        if embeddings.len() != expected {
-            bail!("Expected {} embeddings, got {}", expected, embeddings.len());
-        };
-        Ok(embeddings)
+        let mut errors = Vec::new();
+        for provider in providers {
+            let guard =
+                ProviderGuard::acquire(&format!("{:?}/{}", strategy, provider.name()), policy);
+            let result = guard
+                .call(|| provider.get_embeddings(model.as_ref(), payloads.clone()))
+                .await
+                .and_then(|embeddings| {
+                    if embeddings.len() != expected {
+                        bail!("Expected {} embeddings, got {}", expected, embeddings.len());
+                    }
+                    Ok(embeddings)
+                });
+            match result {
+                Ok(embeddings) => return Ok(embeddings),
+                Err(error) => {
+                    warn!(
+                        "Provider {} failed for strategy {:?}, trying next: {}",
+                        provider.name(),
+                        strategy,
+                        error
+                    );
+                    errors.push(format!("{}: {}", provider.name(), error));
+                }
+            }
+        }
+        Err(eyre!(
+            "All providers failed for strategy {:?}:\n{}",
+            strategy,
+            errors.iter().join("\n")
+        ))
+    }
+    pub fn dimensions(&self) -> usize {
+        self.0.len()
+    }
+    pub fn norm(&self) -> f32 {
+        self.0.iter().map(|x| x * x).sum::<f32>().sqrt()
+    }
+    /// Scales to unit length, after which dot product and cosine similarity agree.
+    pub fn normalize(&mut self) {
+        let norm = self.norm();
+        if norm > 0.0 {
+            self.0.iter_mut().for_each(|x| *x /= norm);
+        }
+    }
+    pub fn normalized(&self) -> Self {
+        let mut embedding = Embedding(self.0.clone());
+        embedding.normalize();
+        embedding
+    }
+    /// Keeps the leading `dimensions` and renormalizes.
+    ///
+    /// Only meaningful for Matryoshka-trained models, whose leading dimensions carry the most
+    /// information; check the model's [`crate::embedding_space::EmbeddingSpace`] first.
+    pub fn truncated(&self, dimensions: usize) -> eyre::Result<Self> {
+        if dimensions == 0 || dimensions > self.dimensions() {
+            bail!(
+                "Cannot truncate a {}-dimensional embedding to {} dimensions",
+                self.dimensions(),
+                dimensions
+            );
+        }
+        Ok(Embedding(self.0[..dimensions].to_vec()).normalized())
+    }
+    /// Symmetric int8 quantization, scaled so the largest component maps to ±127.
+    pub fn quantize_int8(&self) -> Int8Embedding {
+        let max = self.0.iter().fold(0.0_f32, |max, x| max.max(x.abs()));
+        let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+        Int8Embedding {
+            values: self
+                .0
+                .iter()
+                .map(|x| (x / scale).round().clamp(-127.0, 127.0) as i8)
+                .collect(),
+            scale,
+        }
+    }
+    /// One bit per dimension, set when the component is positive.
+    pub fn quantize_binary(&self) -> BinaryEmbedding {
+        let mut bytes = vec![0_u8; self.dimensions().div_ceil(8)];
+        for (i, x) in self.0.iter().enumerate() {
+            if *x > 0.0 {
+                // Most significant bit first, matching pgvector's bit type.
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        BinaryEmbedding {
+            bytes,
+            dimensions: self.dimensions(),
+        }
+    }
+    pub fn to_vector(&self) -> Vector {
+        Vector::from(self.0.clone())
+    }
+    pub fn to_half_vector(&self) -> HalfVector {
+        HalfVector::from_f32_slice(&self.0)
     }
+    /// 1 for the same direction, 0 for orthogonal, -1 for opposite.
     pub fn cosine_similarity(&self, other: &Self) -> eyre::Result<f64> {
+        Ok(1.0 - self.cosine_distance(other)?)
+    }
+    /// simsimd's cosine metric, `1 - cosine_similarity`, like pgvector's `<=>` operator.
+    pub fn cosine_distance(&self, other: &Self) -> eyre::Result<f64> {
         use simsimd::SpatialSimilarity;
         f32::cosine(&self.0, &other.0).ok_or_eyre("Vectors must be of the same length")
     }
@@ -46,4 +151,48 @@ impl Embedding {
         use simsimd::SpatialSimilarity;
         f32::sqeuclidean(&self.0, &other.0).ok_or_eyre("Vectors must be of the same length")
     }
+    pub fn dot_product(&self, other: &Self) -> eyre::Result<f64> {
+        use simsimd::SpatialSimilarity;
+        f32::dot(&self.0, &other.0).ok_or_eyre("Vectors must be of the same length")
+    }
+    /// Negated dot product, so smaller is closer like pgvector's `<#>` operator.
+    pub fn negative_inner_product(&self, other: &Self) -> eyre::Result<f64> {
+        Ok(-self.dot_product(other)?)
+    }
+}
+impl From<&Embedding> for Vector {
+    fn from(embedding: &Embedding) -> Self {
+        embedding.to_vector()
+    }
+}
+impl From<&Embedding> for HalfVector {
+    fn from(embedding: &Embedding) -> Self {
+        embedding.to_half_vector()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Embedding;
+
+    #[test]
+    fn truncates_and_quantizes() -> eyre::Result<()> {
+        let embedding = Embedding(vec![3.0, -4.0, 0.0, 12.0, 0.5, -0.5, 1.0, 2.0, -1.0]);
+        let truncated = embedding.truncated(2)?;
+        assert_eq!(truncated.0, vec![0.6, -0.8]);
+        assert!(embedding.truncated(10).is_err());
+
+        let int8 = embedding.quantize_int8();
+        assert_eq!(int8.values[3], 127);
+        assert_eq!(int8.values[1], -42);
+        assert!((int8.dequantize().0[0] - 3.0).abs() < int8.scale);
+
+        let binary = embedding.quantize_binary();
+        assert_eq!(binary.bytes, vec![0b1001_1011, 0b0000_0000]);
+        assert_eq!(binary.to_bit().len(), 9);
+        let flipped = Embedding(embedding.0.iter().map(|x| -x).collect()).quantize_binary();
+        // The zero component is unset in both.
+        assert_eq!(binary.hamming_distance(&flipped)?, 8);
+        Ok(())
+    }
 }
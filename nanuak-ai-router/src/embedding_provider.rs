@@ -7,7 +7,8 @@ use crate::embedding_request::EmbeddingPayload;
 use crate::model_attributes::ModelAttributes;
 
 #[async_trait]
-pub trait EmbeddingProvider {
+pub trait EmbeddingProvider: Send + Sync {
+    fn name(&self) -> &str;
     async fn is_supported(&self, model: &dyn EmbeddingModel) -> eyre::Result<bool>;
     async fn get_embeddings(
         &self,
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use crate::modality::Modality;
 
+#[derive(Debug, Clone)]
 pub enum EmbeddingPayload {
     Text(String),
     ImagePath(PathBuf),
@@ -0,0 +1,78 @@
+//! Where embeddings live once computed, behind one interface so tools and tests can swap
+//! Postgres for memory.
+
+use async_trait::async_trait;
+use eyre::bail;
+use serde_json::Value;
+
+use crate::embedding::Embedding;
+use crate::metadata_filter::MetadataFilter;
+
+/// Arbitrary JSON attributes stored alongside an embedding and matched by [`MetadataFilter`].
+pub type EmbeddingMetadata = serde_json::Map<String, Value>;
+
+#[derive(Debug, Clone)]
+pub struct StoredEmbedding {
+    pub id: String,
+    pub embedding: Embedding,
+    pub metadata: EmbeddingMetadata,
+}
+impl StoredEmbedding {
+    pub fn new(id: impl Into<String>, embedding: Embedding) -> Self {
+        StoredEmbedding {
+            id: id.into(),
+            embedding,
+            metadata: EmbeddingMetadata::new(),
+        }
+    }
+    pub fn with_metadata(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingMatch {
+    pub id: String,
+    /// Cosine similarity to the query; 1 is the same direction.
+    pub score: f64,
+    pub metadata: EmbeddingMetadata,
+}
+
+/// Fails unless `embedding` has the `expected` dimensions; anything goes when nothing is stored.
+pub(crate) fn ensure_dimensions(
+    expected: Option<usize>,
+    what: &str,
+    embedding: &Embedding,
+) -> eyre::Result<()> {
+    if let Some(expected) = expected
+        && embedding.dimensions() != expected
+    {
+        bail!(
+            "{} has {} dimensions, the store holds {}",
+            what,
+            embedding.dimensions(),
+            expected
+        );
+    }
+    Ok(())
+}
+
+/// Every record in a store shares one dimension count. Upserts and queries that don't match it
+/// are errors rather than silently matching nothing.
+#[async_trait]
+pub trait EmbeddingStore: Send + Sync {
+    fn name(&self) -> &str;
+    /// Inserts the records, replacing the embedding and metadata of any existing IDs.
+    async fn upsert(&self, records: Vec<StoredEmbedding>) -> eyre::Result<()>;
+    /// Returns how many of the IDs were present.
+    async fn delete(&self, ids: &[String]) -> eyre::Result<usize>;
+    /// The `k` records most similar to `query` that match `filter`, most similar first.
+    async fn top_k(
+        &self,
+        query: &Embedding,
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> eyre::Result<Vec<EmbeddingMatch>>;
+    async fn count(&self) -> eyre::Result<usize>;
+}
@@ -1,16 +1,87 @@
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::resilience_policies::ResiliencePolicies;
+use strum::VariantArray;
+
+use crate::attributes::Residency;
 use crate::embedding_model::EmbeddingModel;
+use crate::embedding_provider::EmbeddingProvider;
 use crate::models::bge_m3_embedding_model::BgeM3EmbeddingModel;
+use crate::provider_registry::get_embedding_providers;
+#[cfg(feature = "mock")]
+use crate::providers::mock_embedding_provider::MockEmbeddingProvider;
+use crate::resilience::ResiliencePolicy;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, VariantArray)]
 pub enum WellKnownEmbeddingStrategy {
     BestLocal,
     BestRemote,
+    /// The process-wide installed [`MockEmbeddingProvider`], standing in for `BestLocal`.
+    #[cfg(feature = "mock")]
+    Mock,
 }
 impl WellKnownEmbeddingStrategy {
     pub fn get_model(&self) -> Box<dyn EmbeddingModel> {
         match self {
             WellKnownEmbeddingStrategy::BestLocal => Box::new(BgeM3EmbeddingModel),
             WellKnownEmbeddingStrategy::BestRemote => todo!(),
+            #[cfg(feature = "mock")]
+            WellKnownEmbeddingStrategy::Mock => Box::new(BgeM3EmbeddingModel),
+        }
+    }
+    pub fn accepts(&self, residency: Residency) -> bool {
+        match self {
+            WellKnownEmbeddingStrategy::BestLocal => residency == Residency::Local,
+            WellKnownEmbeddingStrategy::BestRemote => residency != Residency::Local,
+            #[cfg(feature = "mock")]
+            WellKnownEmbeddingStrategy::Mock => true,
+        }
+    }
+    /// Candidate provider instances in the order they should be tried.
+    pub async fn get_providers(&self) -> eyre::Result<Vec<Box<dyn EmbeddingProvider>>> {
+        #[cfg(feature = "mock")]
+        if let WellKnownEmbeddingStrategy::Mock = self {
+            let Some(mock) = MockEmbeddingProvider::installed() else {
+                eyre::bail!("No mock embedding provider installed");
+            };
+            return Ok(vec![Box::new(mock)]);
+        }
+        Ok(get_embedding_providers()
+            .await?
+            .into_iter()
+            .filter(|provider| self.accepts(provider.get_residency()))
+            .collect())
+    }
+    /// The built-in policy, before any overrides from the Nanuak config.
+    pub fn default_policy(&self) -> ResiliencePolicy {
+        match self {
+            WellKnownEmbeddingStrategy::BestLocal => ResiliencePolicy::default(),
+            WellKnownEmbeddingStrategy::BestRemote => ResiliencePolicy {
+                timeout: std::time::Duration::from_secs(30),
+                max_attempts: 5,
+                min_interval: Some(std::time::Duration::from_millis(100)),
+                ..Default::default()
+            },
+            #[cfg(feature = "mock")]
+            WellKnownEmbeddingStrategy::Mock => ResiliencePolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+        }
+    }
+    /// The built-in policy with any `RESILIENCE_POLICIES` overrides for this strategy applied.
+    pub async fn get_policy(&self) -> eyre::Result<ResiliencePolicy> {
+        let policy = self.default_policy();
+        #[cfg(feature = "mock")]
+        if let WellKnownEmbeddingStrategy::Mock = self {
+            return Ok(policy);
         }
+        let config = NanuakConfig::acquire().await?;
+        let overrides = config
+            .get_optional::<ResiliencePolicies>()?
+            .unwrap_or_default();
+        Ok(match overrides.embedding.get(&format!("{:?}", self)) {
+            Some(policy_override) => policy.with_override(policy_override),
+            None => policy,
+        })
     }
 }
@@ -1,6 +1,14 @@
 use crate::attributes::ContextSize;
+use crate::modality::Modality;
 
 pub trait GenerativeTextModel: Send + Sync {
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
     fn get_context_size(&self) -> ContextSize;
+    /// Input modalities the model understands; output is always text.
+    fn get_modalities(&self) -> Vec<Modality> {
+        vec![Modality::Text]
+    }
+    fn accepts_images(&self) -> bool {
+        self.get_modalities().contains(&Modality::Image)
+    }
 }
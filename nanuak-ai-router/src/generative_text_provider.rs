@@ -1,12 +1,16 @@
 use crate::answer::Answer;
 use crate::attributes::Residency;
+use crate::conversation::Conversation;
 use crate::generative_text_model::GenerativeTextModel;
 use crate::model_attributes::ModelAttributes;
 use crate::question::Question;
+use crate::tool::ToolDefinition;
 use async_trait::async_trait;
+use eyre::bail;
 
 #[async_trait]
-pub trait GenerativeTextProvider {
+pub trait GenerativeTextProvider: Send + Sync {
+    fn name(&self) -> &str;
     async fn is_supported(&self, model: &dyn GenerativeTextModel) -> eyre::Result<bool>;
     async fn get_attributes(
         &self,
@@ -14,12 +18,31 @@ pub trait GenerativeTextProvider {
     ) -> eyre::Result<ModelAttributes>;
     fn get_residency(&self) -> Residency;
     async fn answer_question(
-        &self, 
+        &self,
         model: &dyn GenerativeTextModel,
-        question: Question
+        question: Question,
     ) -> eyre::Result<Answer>;
-    async fn format_question(
+    async fn chat(
         &self,
-        question: Question,
-    ) -> eyre::Result<String>;
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+    ) -> eyre::Result<Answer>;
+    /// Like [`GenerativeTextProvider::chat`], but constrains the reply to JSON matching `schema`.
+    async fn chat_with_schema(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        schema: &serde_json::Value,
+    ) -> eyre::Result<Answer>;
+    /// Offers `tools` to the model; requested calls are returned in [`Answer::tool_calls`].
+    async fn chat_with_tools(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        tools: &[ToolDefinition],
+    ) -> eyre::Result<Answer> {
+        let _ = (conversation, tools);
+        bail!("Tool calling is not supported for model {}", model.name());
+    }
+    async fn format_question(&self, question: Question) -> eyre::Result<String>;
 }
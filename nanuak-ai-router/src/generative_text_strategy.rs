@@ -0,0 +1,256 @@
+use std::time::Duration;
+
+use eyre::bail;
+
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::generative_text_model_pins::GenerativeTextModelPins;
+use nanuak_config::resilience_policies::ResiliencePolicies;
+use strum::VariantArray;
+use uom::si::f32::Information;
+use uom::si::information::gigabyte;
+
+use crate::attributes::Residency;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::modality::Modality;
+use crate::model_selection::ModelRequirements;
+use crate::model_selection::SelectionObjective;
+use crate::model_selection::select_generative_text_model;
+use crate::models::pinned_generative_text_model::PinnedGenerativeTextModel;
+use crate::provider_registry::get_generative_text_providers;
+#[cfg(feature = "mock")]
+use crate::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use crate::resilience::ResiliencePolicy;
+use crate::well_known_generative_text_models::WellKnownGenerativeTextModels;
+
+const DEFAULT_PINNED_CONTEXT_SIZE: u32 = 8192;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, VariantArray)]
+pub enum WellKnownGenerativeTextStrategy {
+    BestLocal,
+    BestRemote,
+    Fast,
+    Vision,
+    /// The local model with the lowest measured latency and at least an 8k context.
+    FastestLongContext,
+    /// The local model with the highest measured accuracy that fits in 4GB of VRAM.
+    MostAccurateLowVram,
+    /// The process-wide installed [`MockGenerativeTextProvider`], ignoring config pins.
+    #[cfg(feature = "mock")]
+    Mock,
+}
+impl WellKnownGenerativeTextStrategy {
+    /// Candidate models in order of preference, ignoring any config pin.
+    pub fn get_models(&self) -> Vec<WellKnownGenerativeTextModels> {
+        match self {
+            // Remote candidates are served by provider instances marked `remote` in the config.
+            WellKnownGenerativeTextStrategy::BestLocal
+            | WellKnownGenerativeTextStrategy::BestRemote => vec![
+                WellKnownGenerativeTextModels::Gemma2_9B,
+                WellKnownGenerativeTextModels::Gemma2_2B,
+            ],
+            WellKnownGenerativeTextStrategy::Fast => vec![WellKnownGenerativeTextModels::Gemma2_2B],
+            WellKnownGenerativeTextStrategy::Vision => vec![
+                WellKnownGenerativeTextModels::Llama3_2Vision,
+                WellKnownGenerativeTextModels::Qwen2_5VL,
+            ],
+            // Every model is a candidate; `get_requirements` picks among them.
+            WellKnownGenerativeTextStrategy::FastestLongContext
+            | WellKnownGenerativeTextStrategy::MostAccurateLowVram => {
+                WellKnownGenerativeTextModels::VARIANTS.to_vec()
+            }
+            #[cfg(feature = "mock")]
+            WellKnownGenerativeTextStrategy::Mock => vec![WellKnownGenerativeTextModels::Gemma2_2B],
+        }
+    }
+    /// Constraints for strategies that choose by measured attributes rather than a fixed list.
+    pub fn get_requirements(&self) -> Option<ModelRequirements> {
+        match self {
+            WellKnownGenerativeTextStrategy::FastestLongContext => Some(
+                ModelRequirements::new(SelectionObjective::Fastest)
+                    .local()
+                    .with_min_context_size(8192),
+            ),
+            WellKnownGenerativeTextStrategy::MostAccurateLowVram => Some(
+                ModelRequirements::new(SelectionObjective::MostAccurate)
+                    .local()
+                    .with_max_vram(Information::new::<gigabyte>(4.0)),
+            ),
+            _ => None,
+        }
+    }
+    /// The provider and model chosen by [`Self::get_requirements`], unless a pin overrides it.
+    async fn select(
+        &self,
+    ) -> eyre::Result<
+        Option<(
+            Box<dyn GenerativeTextProvider>,
+            Box<dyn GenerativeTextModel>,
+        )>,
+    > {
+        let Some(requirements) = self.get_requirements() else {
+            return Ok(None);
+        };
+        if self.get_pinned_model().await?.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(select_generative_text_model(&requirements).await?))
+    }
+    pub fn accepts(&self, residency: Residency) -> bool {
+        let local = residency == Residency::Local;
+        match self {
+            WellKnownGenerativeTextStrategy::BestRemote => !local,
+            _ => local,
+        }
+    }
+    /// Candidate provider instances in the order they should be tried.
+    pub async fn get_providers(&self) -> eyre::Result<Vec<Box<dyn GenerativeTextProvider>>> {
+        #[cfg(feature = "mock")]
+        if let WellKnownGenerativeTextStrategy::Mock = self {
+            let Some(mock) = MockGenerativeTextProvider::installed() else {
+                bail!("No mock generative text provider installed");
+            };
+            return Ok(vec![Box::new(mock)]);
+        }
+        if let Some((provider, _)) = self.select().await? {
+            return Ok(vec![provider]);
+        }
+        Ok(get_generative_text_providers()
+            .await?
+            .into_iter()
+            .filter(|provider| self.accepts(provider.get_residency()))
+            .collect())
+    }
+    /// The built-in policy, before any overrides from the Nanuak config.
+    pub fn default_policy(&self) -> ResiliencePolicy {
+        match self {
+            WellKnownGenerativeTextStrategy::BestLocal
+            | WellKnownGenerativeTextStrategy::Vision
+            | WellKnownGenerativeTextStrategy::MostAccurateLowVram => ResiliencePolicy {
+                timeout: Duration::from_secs(300),
+                max_concurrency: 1,
+                ..Default::default()
+            },
+            WellKnownGenerativeTextStrategy::BestRemote => ResiliencePolicy {
+                timeout: Duration::from_secs(60),
+                max_attempts: 5,
+                min_interval: Some(Duration::from_millis(250)),
+                ..Default::default()
+            },
+            WellKnownGenerativeTextStrategy::Fast
+            | WellKnownGenerativeTextStrategy::FastestLongContext => ResiliencePolicy {
+                timeout: Duration::from_secs(60),
+                ..Default::default()
+            },
+            #[cfg(feature = "mock")]
+            WellKnownGenerativeTextStrategy::Mock => ResiliencePolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+        }
+    }
+    /// The built-in policy with any `RESILIENCE_POLICIES` overrides for this strategy applied.
+    pub async fn get_policy(&self) -> eyre::Result<ResiliencePolicy> {
+        let policy = self.default_policy();
+        #[cfg(feature = "mock")]
+        if let WellKnownGenerativeTextStrategy::Mock = self {
+            return Ok(policy);
+        }
+        let config = NanuakConfig::acquire().await?;
+        let overrides = config
+            .get_optional::<ResiliencePolicies>()?
+            .unwrap_or_default();
+        Ok(
+            match overrides.generative_text.get(&format!("{:?}", self)) {
+                Some(policy_override) => policy.with_override(policy_override),
+                None => policy,
+            },
+        )
+    }
+    /// The model pinned for this strategy in the Nanuak config, if any.
+    pub async fn get_pinned_model(&self) -> eyre::Result<Option<PinnedGenerativeTextModel>> {
+        #[cfg(feature = "mock")]
+        if let WellKnownGenerativeTextStrategy::Mock = self {
+            return Ok(None);
+        }
+        let config = NanuakConfig::acquire().await?;
+        let pins = config
+            .get_optional::<GenerativeTextModelPins>()?
+            .unwrap_or_default();
+        Ok(pins
+            .get(&format!("{:?}", self))
+            .map(|pin| PinnedGenerativeTextModel {
+                name: pin.model.clone(),
+                context_size: pin.context_size.unwrap_or(DEFAULT_PINNED_CONTEXT_SIZE),
+                modalities: match self {
+                    WellKnownGenerativeTextStrategy::Vision => {
+                        vec![Modality::Text, Modality::Image]
+                    }
+                    _ => vec![Modality::Text],
+                },
+            }))
+    }
+    /// The pinned model if configured, otherwise the selected model for strategies with
+    /// requirements, otherwise the strategy's own candidates.
+    pub async fn get_candidate_models(&self) -> eyre::Result<Vec<Box<dyn GenerativeTextModel>>> {
+        if let Some(pinned) = self.get_pinned_model().await? {
+            return Ok(vec![Box::new(pinned)]);
+        }
+        if let Some((_, model)) = self.select().await? {
+            return Ok(vec![model]);
+        }
+        Ok(self
+            .get_models()
+            .into_iter()
+            .map(|model| model.get())
+            .collect())
+    }
+    /// The first candidate model that one of the strategy's providers can serve right now.
+    pub async fn resolve(
+        &self,
+    ) -> eyre::Result<(
+        Box<dyn GenerativeTextProvider>,
+        Box<dyn GenerativeTextModel>,
+    )> {
+        let mut providers = self.get_providers().await?;
+        for model in self.get_candidate_models().await? {
+            for i in 0..providers.len() {
+                if providers[i]
+                    .is_supported(model.as_ref())
+                    .await
+                    .unwrap_or(false)
+                {
+                    return Ok((providers.swap_remove(i), model));
+                }
+            }
+        }
+        bail!("No available model for strategy {:?}", self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strum::VariantArray;
+
+    use super::WellKnownGenerativeTextStrategy;
+    use crate::attributes::Residency;
+    use crate::well_known_generative_text_providers::WellKnownGenerativeTextProviders;
+
+    #[test]
+    fn strategies_have_candidates() {
+        for strategy in WellKnownGenerativeTextStrategy::VARIANTS {
+            assert!(!strategy.get_models().is_empty(), "{:?}", strategy);
+            if *strategy == WellKnownGenerativeTextStrategy::BestRemote {
+                assert!(strategy.accepts(Residency::RemoteAnywhere));
+                continue;
+            }
+            assert!(
+                WellKnownGenerativeTextProviders::VARIANTS
+                    .iter()
+                    .any(|provider| strategy.accepts(provider.get().get_residency())),
+                "{:?}",
+                strategy
+            );
+        }
+    }
+}
@@ -0,0 +1,449 @@
+//! An in-process approximate nearest neighbour index (HNSW, Malkov & Yashunin 2016).
+//!
+//! Vectors are normalized on insert and compared by cosine distance. Removing or replacing an
+//! ID leaves a tombstone that still routes searches but is never returned; call
+//! [`HnswIndex::compact`] once tombstones pile up.
+
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use eyre::Context;
+use eyre::bail;
+use nanuak_config::dirs::get_project_dirs;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::embedding::Embedding;
+use crate::splitmix::splitmix64;
+
+/// Bumped when the saved layout changes, so stale files are rebuilt instead of misread.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HnswParameters {
+    /// Links per node above layer 0; layer 0 allows twice as many.
+    pub m: usize,
+    /// Candidate list size while inserting; higher builds slower with better recall.
+    pub ef_construction: usize,
+    /// Candidate list size while searching, raised to `k` when smaller.
+    pub ef_search: usize,
+}
+impl Default for HnswParameters {
+    fn default() -> Self {
+        HnswParameters {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HnswMatch {
+    pub id: String,
+    /// Cosine similarity to the query.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HnswNode {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbours on each layer from 0 up to the node's level.
+    links: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+impl Eq for Candidate {}
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    pub parameters: HnswParameters,
+    /// For indexes built from a table, the newest row timestamp already included.
+    pub synced_through: Option<NaiveDateTime>,
+    dimensions: Option<usize>,
+    nodes: Vec<HnswNode>,
+    ids: HashMap<String, u32>,
+    entry_point: Option<u32>,
+    rng_state: u64,
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new(HnswParameters::default())
+    }
+}
+
+impl HnswIndex {
+    pub fn new(parameters: HnswParameters) -> Self {
+        HnswIndex {
+            parameters,
+            synced_through: None,
+            dimensions: None,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry_point: None,
+            rng_state: 0x5EED,
+        }
+    }
+    /// `<cache dir>/hnsw/<name>.bin`
+    pub fn default_path(name: &str) -> eyre::Result<PathBuf> {
+        Ok(get_project_dirs()?
+            .cache_dir()
+            .join("hnsw")
+            .join(format!("{}.bin", name)))
+    }
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+    /// IDs of the live entries, in no particular order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.ids.keys().map(String::as_str)
+    }
+    pub fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+    /// Removed or replaced entries still held for routing.
+    pub fn tombstones(&self) -> usize {
+        self.nodes.len() - self.ids.len()
+    }
+
+    /// Adds `id`, replacing its previous embedding if present.
+    pub fn insert(&mut self, id: &str, embedding: &Embedding) -> eyre::Result<()> {
+        match self.dimensions {
+            Some(dimensions) if dimensions != embedding.dimensions() => bail!(
+                "Embedding for {} has {} dimensions, the index holds {}",
+                id,
+                embedding.dimensions(),
+                dimensions
+            ),
+            _ => self.dimensions = Some(embedding.dimensions()),
+        }
+        if embedding.norm() == 0.0 {
+            bail!("Embedding for {} is all zeros", id);
+        }
+        self.remove(id);
+        let node = u32::try_from(self.nodes.len())?;
+        let level = self.random_level();
+        self.nodes.push(HnswNode {
+            id: id.to_string(),
+            vector: embedding.normalized().0,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id.to_string(), node);
+        self.link(node);
+        Ok(())
+    }
+
+    /// Returns whether `id` was present.
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.ids.remove(id) {
+            Some(node) => {
+                self.nodes[node as usize].deleted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn search(&self, query: &Embedding, k: usize) -> eyre::Result<Vec<HnswMatch>> {
+        let Some(entry_point) = self.entry_point else {
+            return Ok(Vec::new());
+        };
+        if Some(query.dimensions()) != self.dimensions {
+            bail!(
+                "Query has {} dimensions, the index holds {:?}",
+                query.dimensions(),
+                self.dimensions
+            );
+        }
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let query = query.normalized().0;
+        let mut entry = self.candidate(&query, entry_point);
+        for layer in (1..=self.level_of(entry_point)).rev() {
+            entry = self.search_layer(&query, vec![entry], 1, layer)[0];
+        }
+        // Tombstones take up candidate slots, so widen the search to make up for them.
+        let ef = self.parameters.ef_search.max(k) + self.tombstones().min(k);
+        Ok(self
+            .search_layer(&query, vec![entry], ef, 0)
+            .into_iter()
+            .filter(|candidate| !self.nodes[candidate.node as usize].deleted)
+            .take(k)
+            .map(|candidate| HnswMatch {
+                id: self.nodes[candidate.node as usize].id.clone(),
+                score: 1.0 - candidate.distance as f64,
+            })
+            .collect())
+    }
+
+    /// Rebuilds the graph from live entries, dropping tombstones.
+    pub fn compact(&mut self) -> eyre::Result<()> {
+        let mut rebuilt = HnswIndex::new(self.parameters);
+        rebuilt.synced_through = self.synced_through;
+        for node in self.nodes.iter().filter(|node| !node.deleted) {
+            rebuilt.insert(&node.id, &Embedding(node.vector.clone()))?;
+        }
+        *self = rebuilt;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let file = std::fs::File::open(path)
+            .wrap_err_with(|| format!("Failed to open index: {}", path.display()))?;
+        let (version, index): (u32, HnswIndex) = bincode::deserialize_from(BufReader::new(file))
+            .wrap_err_with(|| format!("Failed to read index, rebuild it: {}", path.display()))?;
+        if version != FORMAT_VERSION {
+            bail!(
+                "Index {} has format {}, expected {}; rebuild it",
+                path.display(),
+                version,
+                FORMAT_VERSION
+            );
+        }
+        Ok(index)
+    }
+    /// Writes to a temporary file first so a crash never leaves a truncated index.
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("tmp");
+        let file = std::fs::File::create(&temp)
+            .wrap_err_with(|| format!("Failed to create index: {}", temp.display()))?;
+        bincode::serialize_into(BufWriter::new(file), &(FORMAT_VERSION, self))?;
+        std::fs::rename(&temp, path)
+            .wrap_err_with(|| format!("Failed to write index: {}", path.display()))?;
+        Ok(())
+    }
+
+    fn level_of(&self, node: u32) -> usize {
+        self.nodes[node as usize].links.len() - 1
+    }
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.parameters.m * 2
+        } else {
+            self.parameters.m
+        }
+    }
+    /// Draws from the exponentially decaying level distribution using splitmix64.
+    fn random_level(&mut self) -> usize {
+        let z = splitmix64(&mut self.rng_state);
+        let uniform = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let multiplier = 1.0 / (self.parameters.m.max(2) as f64).ln();
+        (-uniform.ln() * multiplier) as usize
+    }
+    fn distance(&self, query: &[f32], node: u32) -> f32 {
+        use simsimd::SpatialSimilarity;
+        let dot = f32::dot(query, &self.nodes[node as usize].vector).unwrap_or_default();
+        1.0 - dot as f32
+    }
+    fn candidate(&self, query: &[f32], node: u32) -> Candidate {
+        Candidate {
+            distance: self.distance(query, node),
+            node,
+        }
+    }
+
+    /// The `ef` nodes closest to `query` reachable on `layer`, closest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: Vec<Candidate>,
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = entry_points.iter().map(|c| c.node).collect();
+        let mut frontier: BinaryHeap<Reverse<Candidate>> =
+            entry_points.iter().copied().map(Reverse).collect();
+        let mut nearest: BinaryHeap<Candidate> = entry_points.into_iter().collect();
+        while let Some(Reverse(closest)) = frontier.pop() {
+            let furthest = nearest.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+            if closest.distance > furthest && nearest.len() >= ef {
+                break;
+            }
+            for &neighbour in &self.nodes[closest.node as usize].links[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let candidate = self.candidate(query, neighbour);
+                let furthest = nearest.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+                if nearest.len() < ef || candidate.distance < furthest {
+                    frontier.push(Reverse(candidate));
+                    nearest.push(candidate);
+                    if nearest.len() > ef {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+        nearest.into_sorted_vec()
+    }
+
+    /// The paper's neighbour selection heuristic: keep a candidate only if it is closer to the
+    /// base than to any neighbour already kept, then top up with the closest of the rest.
+    fn select_neighbours(&self, candidates: &[Candidate], count: usize) -> Vec<u32> {
+        let mut selected: Vec<Candidate> = Vec::with_capacity(count);
+        let mut pruned = Vec::new();
+        for &candidate in candidates {
+            if selected.len() >= count {
+                break;
+            }
+            let vector = &self.nodes[candidate.node as usize].vector;
+            if selected
+                .iter()
+                .all(|kept| self.distance(vector, kept.node) > candidate.distance)
+            {
+                selected.push(candidate);
+            } else {
+                pruned.push(candidate);
+            }
+        }
+        let missing = count.saturating_sub(selected.len());
+        selected.extend(pruned.into_iter().take(missing));
+        selected.into_iter().map(|c| c.node).collect()
+    }
+
+    fn link(&mut self, node: u32) {
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+        let level = self.level_of(node);
+        let top = self.level_of(entry_point);
+        let query = self.nodes[node as usize].vector.clone();
+        let mut entry = vec![self.candidate(&query, entry_point)];
+        for layer in (level + 1..=top).rev() {
+            entry = vec![self.search_layer(&query, entry, 1, layer)[0]];
+        }
+        for layer in (0..=level.min(top)).rev() {
+            let candidates =
+                self.search_layer(&query, entry, self.parameters.ef_construction, layer);
+            let neighbours = self.select_neighbours(&candidates, self.parameters.m);
+            self.nodes[node as usize].links[layer] = neighbours.clone();
+            for neighbour in neighbours {
+                self.nodes[neighbour as usize].links[layer].push(node);
+                if self.nodes[neighbour as usize].links[layer].len() > self.max_links(layer) {
+                    self.shrink_links(neighbour, layer);
+                }
+            }
+            entry = candidates;
+        }
+        if level > top {
+            self.entry_point = Some(node);
+        }
+    }
+
+    fn shrink_links(&mut self, node: u32, layer: usize) {
+        let vector = self.nodes[node as usize].vector.clone();
+        let mut candidates: Vec<Candidate> = self.nodes[node as usize].links[layer]
+            .iter()
+            .map(|&neighbour| self.candidate(&vector, neighbour))
+            .collect();
+        candidates.sort();
+        self.nodes[node as usize].links[layer] =
+            self.select_neighbours(&candidates, self.max_links(layer));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HnswIndex;
+    use super::HnswParameters;
+    use crate::embedding::Embedding;
+
+    fn vectors(count: usize, dimensions: usize) -> Vec<Embedding> {
+        let mut state = 42u64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+        };
+        (0..count)
+            .map(|_| Embedding((0..dimensions).map(|_| next()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_nearest_neighbours_and_round_trips() -> eyre::Result<()> {
+        let data = vectors(600, 16);
+        let mut index = HnswIndex::new(HnswParameters::default());
+        for (i, embedding) in data.iter().enumerate() {
+            index.insert(&i.to_string(), embedding)?;
+        }
+        let queries = vectors(20, 16);
+        let mut hits = 0;
+        for query in &queries {
+            let mut exact: Vec<(f64, usize)> = data
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (query.cosine_similarity(e).unwrap(), i))
+                .collect();
+            exact.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let found = index.search(query, 10)?;
+            hits += exact[..10]
+                .iter()
+                .filter(|(_, i)| found.iter().any(|m| m.id == i.to_string()))
+                .count();
+        }
+        assert!(hits >= 190, "recall@10 was {}/200", hits);
+
+        // Replacing and removing leave tombstones that are never returned.
+        index.insert("0", &queries[0])?;
+        assert_eq!(index.search(&queries[0], 1)?[0].id, "0");
+        assert!(index.remove("0"));
+        assert!(index.search(&queries[0], 5)?.iter().all(|m| m.id != "0"));
+        assert_eq!((index.len(), index.tombstones()), (599, 2));
+
+        let dir = std::env::temp_dir().join(format!("nanuak-hnsw-{}", std::process::id()));
+        let path = dir.join("test.bin");
+        index.save(&path)?;
+        let mut loaded = HnswIndex::load(&path)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(
+            loaded.search(&queries[1], 10)?,
+            index.search(&queries[1], 10)?
+        );
+
+        loaded.compact()?;
+        assert_eq!((loaded.len(), loaded.tombstones()), (599, 0));
+        assert!(loaded.insert("bad", &Embedding(vec![1.0])).is_err());
+        Ok(())
+    }
+}
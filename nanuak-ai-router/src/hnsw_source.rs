@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use diesel::PgConnection;
+use diesel::prelude::*;
+use pgvector::Vector;
+use strum::VariantArray;
+use tracing::info;
+use tracing::warn;
+
+use crate::embedding::Embedding;
+use crate::embedding_model::EmbeddingModel;
+use crate::hnsw_index::HnswIndex;
+use crate::providers::ollama_models_api::normalize_model_name;
+use crate::well_known_embedding_models::WellKnownEmbeddingModels;
+
+const PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HnswSync {
+    pub added: usize,
+    /// Entries whose rows were deleted from the table, now tombstoned.
+    pub removed: usize,
+}
+
+/// Existing embedding tables an [`HnswIndex`] can be built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HnswSource {
+    /// `youtube.video_embeddings_bge_m3`, keyed by video etag.
+    YoutubeVideoEmbeddingsBgeM3,
+    /// `files.embeddings_512` rows for one model, keyed by file path.
+    FileEmbeddings512 { model: String },
+}
+impl HnswSource {
+    /// The index file name, unique per source.
+    pub fn name(&self) -> String {
+        match self {
+            HnswSource::YoutubeVideoEmbeddingsBgeM3 => {
+                "youtube-video-embeddings-bge-m3".to_string()
+            }
+            HnswSource::FileEmbeddings512 { model } => format!(
+                "file-embeddings-512-{}",
+                model.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_")
+            ),
+        }
+    }
+    pub fn default_index_path(&self) -> eyre::Result<PathBuf> {
+        HnswIndex::default_path(&self.name())
+    }
+    /// The model that embedded the table, so queries land in the same space, if this crate
+    /// knows it.
+    pub fn embedding_model(&self) -> Option<Box<dyn EmbeddingModel>> {
+        let name = match self {
+            HnswSource::YoutubeVideoEmbeddingsBgeM3 => "bge-m3",
+            HnswSource::FileEmbeddings512 { model } => model,
+        };
+        WellKnownEmbeddingModels::VARIANTS
+            .iter()
+            .map(|known| known.get())
+            .find(|known| normalize_model_name(known.name()) == normalize_model_name(name))
+    }
+
+    /// Adds rows written since the index was last synced and tombstones entries whose rows
+    /// have since been deleted.
+    ///
+    /// Timestamps default to the transaction start, so a row committed after a sync can be
+    /// stamped at or before its watermark; any live row the index still lacks is added too.
+    pub fn sync(&self, conn: &mut PgConnection, index: &mut HnswIndex) -> eyre::Result<HnswSync> {
+        let live = self.fetch_ids(conn)?;
+        let gone: Vec<String> = index
+            .ids()
+            .filter(|id| !live.contains(*id))
+            .map(str::to_string)
+            .collect();
+        for id in &gone {
+            index.remove(id);
+        }
+        if !gone.is_empty() {
+            info!("Removed {} deleted rows from {}", gone.len(), self.name());
+        }
+
+        let since = index.synced_through;
+        let mut added = 0;
+        let mut after: Option<(NaiveDateTime, String)> = None;
+        loop {
+            let rows = self.fetch_page(conn, since, after.as_ref())?;
+            let Some((_, at, key, _)) = rows.last() else {
+                break;
+            };
+            after = Some((*at, key.clone()));
+            for (id, at, _, vector) in rows {
+                match index.insert(&id, &Embedding(vector.to_vec())) {
+                    Ok(()) => added += 1,
+                    Err(error) => warn!("Skipping {}: {}", id, error),
+                }
+                index.synced_through = index.synced_through.max(Some(at));
+            }
+            info!("Indexed {} rows from {}", added, self.name());
+        }
+
+        let missing = missing_ids(&live, index);
+        let added_incrementally = added;
+        for ids in missing.chunks(PAGE_SIZE as usize) {
+            for (id, vector) in self.fetch_by_ids(conn, ids)? {
+                match index.insert(&id, &Embedding(vector.to_vec())) {
+                    Ok(()) => added += 1,
+                    Err(error) => warn!("Skipping {}: {}", id, error),
+                }
+            }
+        }
+        if !missing.is_empty() {
+            info!(
+                "Indexed {} of {} rows from {} stamped before the last sync",
+                added - added_incrementally,
+                missing.len(),
+                self.name()
+            );
+        }
+        Ok(HnswSync {
+            added,
+            removed: gone.len(),
+        })
+    }
+
+    /// Every ID currently in the table.
+    fn fetch_ids(&self, conn: &mut PgConnection) -> eyre::Result<HashSet<String>> {
+        let ids = match self {
+            HnswSource::YoutubeVideoEmbeddingsBgeM3 => {
+                use nanuak_schema::youtube::video_embeddings_bge_m3::dsl::*;
+                video_embeddings_bge_m3
+                    .select(video_etag)
+                    .filter(embedding.is_not_null())
+                    .load::<String>(conn)?
+            }
+            HnswSource::FileEmbeddings512 { model } => {
+                use nanuak_schema::files::embeddings_512;
+                use nanuak_schema::files::files;
+                embeddings_512::table
+                    .inner_join(files::table)
+                    .select(files::path)
+                    .filter(embeddings_512::model.eq(model))
+                    .load::<String>(conn)?
+            }
+        };
+        Ok(ids.into_iter().collect())
+    }
+
+    /// `(id, embedding)` for each of `ids` still in the table.
+    fn fetch_by_ids(
+        &self,
+        conn: &mut PgConnection,
+        ids: &[String],
+    ) -> eyre::Result<Vec<(String, Vector)>> {
+        Ok(match self {
+            HnswSource::YoutubeVideoEmbeddingsBgeM3 => {
+                use nanuak_schema::youtube::video_embeddings_bge_m3::dsl::*;
+                video_embeddings_bge_m3
+                    .select((video_etag, embedding.assume_not_null()))
+                    .filter(embedding.is_not_null())
+                    .filter(video_etag.eq_any(ids))
+                    .load::<(String, Vector)>(conn)?
+            }
+            HnswSource::FileEmbeddings512 { model } => {
+                use nanuak_schema::files::embeddings_512;
+                use nanuak_schema::files::files;
+                embeddings_512::table
+                    .inner_join(files::table)
+                    .select((files::path, embeddings_512::embedding))
+                    .filter(embeddings_512::model.eq(model))
+                    .filter(files::path.eq_any(ids))
+                    .load::<(String, Vector)>(conn)?
+            }
+        })
+    }
+
+    /// One page of `(id, timestamp, tiebreak key, embedding)` ordered by timestamp then key.
+    fn fetch_page(
+        &self,
+        conn: &mut PgConnection,
+        since: Option<NaiveDateTime>,
+        after: Option<&(NaiveDateTime, String)>,
+    ) -> eyre::Result<Vec<(String, NaiveDateTime, String, Vector)>> {
+        match self {
+            HnswSource::YoutubeVideoEmbeddingsBgeM3 => {
+                use nanuak_schema::youtube::video_embeddings_bge_m3::dsl::*;
+                let mut query = video_embeddings_bge_m3
+                    .select((video_etag, embedded_on, embedding.assume_not_null()))
+                    .filter(embedding.is_not_null())
+                    .order((embedded_on, video_etag))
+                    .limit(PAGE_SIZE)
+                    .into_boxed();
+                if let Some(since) = since {
+                    query = query.filter(embedded_on.gt(since));
+                }
+                if let Some((at, key)) = after {
+                    query = query.filter(
+                        embedded_on
+                            .gt(*at)
+                            .or(embedded_on.eq(*at).and(video_etag.gt(key.clone()))),
+                    );
+                }
+                Ok(query
+                    .load::<(String, NaiveDateTime, Vector)>(conn)?
+                    .into_iter()
+                    .map(|(etag, at, vector)| (etag.clone(), at, etag, vector))
+                    .collect())
+            }
+            HnswSource::FileEmbeddings512 { model } => {
+                use nanuak_schema::files::embeddings_512;
+                use nanuak_schema::files::files;
+                let mut query = embeddings_512::table
+                    .inner_join(files::table)
+                    .select((
+                        files::path,
+                        embeddings_512::created_at,
+                        embeddings_512::id,
+                        embeddings_512::embedding,
+                    ))
+                    .filter(embeddings_512::model.eq(model))
+                    .order((embeddings_512::created_at, embeddings_512::id))
+                    .limit(PAGE_SIZE)
+                    .into_boxed();
+                if let Some(since) = since {
+                    query = query.filter(embeddings_512::created_at.gt(since));
+                }
+                if let Some((at, key)) = after {
+                    let key: i32 = key.parse()?;
+                    query = query.filter(
+                        embeddings_512::created_at
+                            .gt(*at)
+                            .or(embeddings_512::created_at
+                                .eq(*at)
+                                .and(embeddings_512::id.gt(key))),
+                    );
+                }
+                Ok(query
+                    .load::<(String, NaiveDateTime, i32, Vector)>(conn)?
+                    .into_iter()
+                    .map(|(path, at, id, vector)| (path, at, id.to_string(), vector))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Live IDs the index has no entry for, sorted so they are fetched in a stable order.
+fn missing_ids(live: &HashSet<String>, index: &HnswIndex) -> Vec<String> {
+    let mut missing: Vec<String> = live
+        .iter()
+        .filter(|id| !index.contains(id))
+        .cloned()
+        .collect();
+    missing.sort();
+    missing
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::HnswSource;
+    use super::missing_ids;
+    use crate::embedding::Embedding;
+    use crate::hnsw_index::HnswIndex;
+
+    #[test]
+    fn queries_use_the_model_that_embedded_the_table() {
+        let youtube = HnswSource::YoutubeVideoEmbeddingsBgeM3.embedding_model();
+        assert_eq!(
+            youtube.map(|model| model.name().to_string()).as_deref(),
+            Some("bge-m3:latest")
+        );
+        let clip = HnswSource::FileEmbeddings512 {
+            model: "clip-vit-base-patch32".to_string(),
+        };
+        assert!(clip.embedding_model().is_none());
+    }
+
+    #[test]
+    fn finds_live_rows_missing_from_the_index() -> eyre::Result<()> {
+        let mut index = HnswIndex::default();
+        index.insert("indexed", &Embedding(vec![1.0, 0.0]))?;
+        index.insert("removed", &Embedding(vec![0.0, 1.0]))?;
+        index.remove("removed");
+        let live: HashSet<String> = ["indexed", "removed", "late"]
+            .map(String::from)
+            .into_iter()
+            .collect();
+        assert_eq!(missing_ids(&live, &index), ["late", "removed"]);
+        Ok(())
+    }
+}
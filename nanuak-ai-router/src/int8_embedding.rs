@@ -0,0 +1,27 @@
+use eyre::OptionExt;
+
+use crate::embedding::Embedding;
+
+/// An [`Embedding`] stored as one byte per dimension, with the scale needed to undo it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Int8Embedding {
+    pub values: Vec<i8>,
+    pub scale: f32,
+}
+impl Int8Embedding {
+    pub fn dequantize(&self) -> Embedding {
+        Embedding(self.values.iter().map(|x| *x as f32 * self.scale).collect())
+    }
+    /// Scale-independent, so embeddings quantized with different scales compare fine.
+    pub fn cosine_distance(&self, other: &Self) -> eyre::Result<f64> {
+        use simsimd::SpatialSimilarity;
+        i8::cosine(&self.values, &other.values).ok_or_eyre("Vectors must be of the same length")
+    }
+    /// Approximates the dot product of the original embeddings.
+    pub fn dot_product(&self, other: &Self) -> eyre::Result<f64> {
+        use simsimd::SpatialSimilarity;
+        let dot = i8::dot(&self.values, &other.values)
+            .ok_or_eyre("Vectors must be of the same length")?;
+        Ok(dot * self.scale as f64 * other.scale as f64)
+    }
+}
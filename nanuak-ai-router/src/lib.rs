@@ -1,16 +1,49 @@
+pub mod agent;
+pub mod answer;
 pub mod attributes;
+pub mod benchmark;
+pub mod binary_embedding;
+pub mod classify;
+pub mod conversation;
 pub mod embedding;
 pub mod embedding_model;
 pub mod embedding_provider;
 pub mod embedding_request;
 pub mod embedding_space;
+pub mod embedding_store;
 pub mod embedding_strategy;
+pub mod generative_text_model;
+pub mod generative_text_provider;
+pub mod generative_text_strategy;
+pub mod hnsw_index;
+pub mod hnsw_source;
+pub mod int8_embedding;
+pub mod metadata_filter;
 pub mod modality;
 pub mod model_attributes;
+pub mod model_selection;
 pub mod models;
+pub mod prompt_registry;
+pub mod prompt_template;
+pub mod provider_registry;
 pub mod providers;
-pub mod well_known_embedding_providers;
-pub mod generative_text_provider;
-pub mod generative_text_model;
 pub mod question;
-pub mod answer;
+pub mod rerank;
+pub mod rerank_provider;
+pub mod resilience;
+pub mod retrieval_eval;
+pub mod splitmix;
+pub mod stores;
+pub mod structured_answer;
+pub mod summarize;
+pub mod telemetry;
+pub mod tool;
+pub mod transcript;
+pub mod transcription_model;
+pub mod transcription_provider;
+pub mod transcription_request;
+pub mod well_known_embedding_models;
+pub mod well_known_embedding_providers;
+pub mod well_known_generative_text_models;
+pub mod well_known_generative_text_providers;
+pub mod well_known_transcription_models;
@@ -0,0 +1,536 @@
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use diesel::Connection;
+use diesel::PgConnection;
+use eyre::Context;
+use nanuak_ai_router::benchmark::run_benchmarks;
+use nanuak_ai_router::classify::ClassLabel;
+use nanuak_ai_router::classify::ClassificationMode;
+use nanuak_ai_router::classify::ClassifyOptions;
+use nanuak_ai_router::classify::classify;
+use nanuak_ai_router::embedding_model::EmbeddingModel;
+use nanuak_ai_router::embedding_provider::EmbeddingProvider;
+use nanuak_ai_router::embedding_request::EmbeddingPayload;
+use nanuak_ai_router::hnsw_index::HnswIndex;
+use nanuak_ai_router::hnsw_source::HnswSource;
+use nanuak_ai_router::model_selection::ModelRequirements;
+use nanuak_ai_router::model_selection::SelectionObjective;
+use nanuak_ai_router::model_selection::select_embedding_model;
+use nanuak_ai_router::model_selection::select_generative_text_model;
+use nanuak_ai_router::models::named_embedding_model::NamedEmbeddingModel;
+use nanuak_ai_router::prompt_registry::PromptRegistry;
+use nanuak_ai_router::provider_registry::get_embedding_providers;
+use nanuak_ai_router::providers::ollama_models_api::normalize_model_name;
+#[cfg(feature = "whisper")]
+use nanuak_ai_router::providers::whisper_transcription_provider::WhisperTranscriptionProvider;
+use nanuak_ai_router::rerank::get_rerank_provider;
+use nanuak_ai_router::retrieval_eval::EvalHistory;
+use nanuak_ai_router::retrieval_eval::EvalOptions;
+use nanuak_ai_router::retrieval_eval::EvalSet;
+use nanuak_ai_router::retrieval_eval::changes_since_previous;
+use nanuak_ai_router::retrieval_eval::evaluate_retrieval;
+use nanuak_ai_router::summarize::SummarizeOptions;
+use nanuak_ai_router::summarize::summarize;
+use nanuak_ai_router::telemetry::ReportBucket;
+use nanuak_ai_router::telemetry::call_trends;
+use nanuak_ai_router::telemetry::get_database_url;
+use nanuak_ai_router::telemetry::install_postgres;
+#[cfg(feature = "whisper")]
+use nanuak_ai_router::transcription_provider::TranscriptionProvider;
+#[cfg(feature = "whisper")]
+use nanuak_ai_router::transcription_request::TranscriptionRequest;
+use nanuak_ai_router::well_known_embedding_models::WellKnownEmbeddingModels;
+#[cfg(feature = "whisper")]
+use nanuak_ai_router::well_known_transcription_models::WellKnownTranscriptionModels;
+use nanuak_config::dirs::get_prompts_dir;
+use nanuak_schema::migrations::ensure_schema_current;
+use std::path::PathBuf;
+use strum::VariantArray;
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
+use uom::si::f32::Information;
+use uom::si::information::gigabyte;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Nanuak AI Router CLI")]
+struct Cli {
+    /// If set, enable debug logging
+    #[arg(long)]
+    debug: bool,
+
+    /// Don't record provider calls to ai.calls
+    #[arg(long)]
+    no_telemetry: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ModelKind {
+    Embedding,
+    GenerativeText,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum IndexTable {
+    /// youtube.video_embeddings_bge_m3
+    YoutubeVideos,
+    /// files.embeddings_512, for the model given by --model
+    Files,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Measure latency and throughput of every registered model and provider
+    Benchmark {
+        /// Timed requests per model, after one warm-up request
+        #[arg(long, default_value_t = 5)]
+        iterations: usize,
+    },
+    /// Choose a model by constraints using measured attributes
+    Select {
+        #[arg(long, value_enum, default_value_t = ModelKind::GenerativeText)]
+        kind: ModelKind,
+        #[arg(long, value_enum, default_value_t = SelectionObjective::Fastest)]
+        objective: SelectionObjective,
+        /// Only consider models running on this machine
+        #[arg(long)]
+        local: bool,
+        #[arg(long)]
+        min_context: Option<u32>,
+        #[arg(long)]
+        max_vram_gb: Option<f32>,
+    },
+    /// Transcribe a 16 kHz WAV file to WebVTT with a local whisper model
+    #[cfg(feature = "whisper")]
+    Transcribe {
+        audio: PathBuf,
+        #[arg(long, value_enum, default_value_t = WellKnownTranscriptionModels::WhisperBaseEn)]
+        model: WellKnownTranscriptionModels,
+        /// Spoken language as an ISO 639-1 code, detected if omitted
+        #[arg(long)]
+        language: Option<String>,
+        /// Where to write the .vtt file; printed to stdout if omitted
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Show call throughput and error rates per model from recorded telemetry
+    Report {
+        /// How far back to look
+        #[arg(long, default_value_t = 30)]
+        days: i32,
+        #[arg(long, value_enum, default_value_t = ReportBucket::Day)]
+        bucket: ReportBucket,
+    },
+    /// Bring the on-disk HNSW index of an embedding table up to date, then optionally search it
+    Index {
+        #[arg(long, value_enum, default_value_t = IndexTable::YoutubeVideos)]
+        table: IndexTable,
+        /// Embedding model, for tables that hold several
+        #[arg(long)]
+        model: Option<String>,
+        /// Discard the saved index and rebuild it from scratch
+        #[arg(long)]
+        rebuild: bool,
+        /// Text to search for, embedded with the model that embedded the table
+        #[arg(long)]
+        query: Option<String>,
+        #[arg(long, default_value_t = 10)]
+        k: usize,
+    },
+    /// Score retrieval on a labelled JSONL set with recall@k, MRR and nDCG, and record the run
+    Eval {
+        set: PathBuf,
+        /// Embedding model as its provider names it
+        #[arg(long, default_value = "bge-m3:latest")]
+        model: String,
+        /// Rerank the retrieved candidates before scoring
+        #[arg(long)]
+        rerank: bool,
+        #[arg(long, value_delimiter = ',', default_values_t = [1, 5, 10])]
+        k: Vec<usize>,
+        /// Don't add this run to the history
+        #[arg(long)]
+        no_record: bool,
+    },
+    /// Show recorded evaluation runs with changes since the previous run of the same setup
+    EvalHistory {
+        /// Only runs on this set
+        #[arg(long)]
+        set: Option<String>,
+    },
+    /// Sort text into labels without training examples
+    Classify {
+        /// Texts to classify
+        #[arg(required = true)]
+        items: Vec<String>,
+        /// A label, optionally `name: description`; repeat for each label
+        #[arg(long = "label", required = true)]
+        labels: Vec<String>,
+        #[arg(long, value_enum, default_value_t = ClassificationMode::Embedding)]
+        mode: ClassificationMode,
+        /// Assign every label over the threshold instead of only the best
+        #[arg(long)]
+        multi_label: bool,
+        #[arg(long, default_value_t = 0.5)]
+        threshold: f64,
+    },
+    /// Summarize a document too long for one request, citing the chunks it drew from
+    Summarize {
+        /// File to summarize, stdin if omitted
+        path: Option<PathBuf>,
+        /// Question to focus the summary on
+        #[arg(long)]
+        focus: Option<String>,
+        /// Tokens per chunk, defaulting to what the model's context allows
+        #[arg(long)]
+        chunk_tokens: Option<u32>,
+    },
+    /// List prompt templates, or show one in full
+    Prompts {
+        /// Template to show
+        name: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+
+    let log_level = if cli.debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
+    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+
+    let telemetry = (!cli.no_telemetry).then(|| install_postgres("nanuak-ai-router"));
+    let result = run(cli.command).await;
+    if let Some(telemetry) = telemetry
+        && let Err(error) = telemetry.flush().await
+    {
+        warn!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run(command: Commands) -> eyre::Result<()> {
+    match command {
+        Commands::Benchmark { iterations } => {
+            for record in run_benchmarks(iterations).await? {
+                info!(
+                    "{} on {}: {:.3}s mean latency, {:.0} B/s, VRAM {:?} bytes, accuracy {:?}",
+                    record.model,
+                    record.provider,
+                    record.mean_latency_seconds,
+                    record.throughput_bytes_per_second,
+                    record.vram_bytes,
+                    record.accuracy
+                );
+            }
+        }
+        Commands::Select {
+            kind,
+            objective,
+            local,
+            min_context,
+            max_vram_gb,
+        } => {
+            let mut requirements = ModelRequirements::new(objective);
+            if local {
+                requirements = requirements.local();
+            }
+            if let Some(min_context) = min_context {
+                requirements = requirements.with_min_context_size(min_context);
+            }
+            if let Some(max_vram_gb) = max_vram_gb {
+                requirements =
+                    requirements.with_max_vram(Information::new::<gigabyte>(max_vram_gb));
+            }
+            let (provider, model) = match kind {
+                ModelKind::Embedding => {
+                    let (provider, model) = select_embedding_model(&requirements).await?;
+                    (provider.name().to_string(), model.name().to_string())
+                }
+                ModelKind::GenerativeText => {
+                    let (provider, model) = select_generative_text_model(&requirements).await?;
+                    (provider.name().to_string(), model.name().to_string())
+                }
+            };
+            println!("{} on {}", model, provider);
+        }
+        #[cfg(feature = "whisper")]
+        Commands::Transcribe {
+            audio,
+            model,
+            language,
+            output,
+        } => {
+            let provider = WhisperTranscriptionProvider::from_project_dirs()?;
+            let mut request = TranscriptionRequest::new(audio);
+            if let Some(language) = language {
+                request = request.with_language(language);
+            }
+            let transcript = provider.transcribe(model.get().as_ref(), request).await?;
+            match output {
+                Some(output) => tokio::fs::write(&output, transcript.to_webvtt()).await?,
+                None => print!("{}", transcript.to_webvtt()),
+            }
+        }
+        Commands::Report { days, bucket } => {
+            let mut conn = PgConnection::establish(&get_database_url().await?)?;
+            ensure_schema_current(&mut conn)?;
+            let trends = call_trends(&mut conn, days, bucket)?;
+            if trends.is_empty() {
+                println!("No calls recorded in the last {} days", days);
+            }
+            let mut previous = None;
+            for trend in trends {
+                let key = (trend.model.clone(), trend.provider.clone());
+                if previous.as_ref() != Some(&key) {
+                    println!(
+                        "\n{} on {} ({})",
+                        trend.model, trend.provider, trend.modality
+                    );
+                    println!(
+                        "  {:<19} {:>7} {:>7} {:>12} {:>10}",
+                        "bucket", "calls", "errors", "latency ms", "tokens/s"
+                    );
+                    previous = Some(key);
+                }
+                println!(
+                    "  {:<19} {:>7} {:>6.1}% {:>12.0} {:>10}",
+                    trend.bucket.format("%Y-%m-%d %H:%M").to_string(),
+                    trend.calls,
+                    trend.error_rate() * 100.0,
+                    trend.mean_latency_ms,
+                    trend
+                        .tokens_per_second
+                        .map(|rate| format!("{:.1}", rate))
+                        .unwrap_or_else(|| "-".to_string())
+                );
+            }
+        }
+        Commands::Index {
+            table,
+            model,
+            rebuild,
+            query,
+            k,
+        } => {
+            let source = match (table, model) {
+                (IndexTable::YoutubeVideos, _) => HnswSource::YoutubeVideoEmbeddingsBgeM3,
+                (IndexTable::Files, Some(model)) => HnswSource::FileEmbeddings512 { model },
+                (IndexTable::Files, None) => eyre::bail!("--model is required for the files table"),
+            };
+            let query_model = match (&query, source.embedding_model()) {
+                (None, _) => None,
+                (Some(_), Some(model)) => Some(model),
+                (Some(_), None) => eyre::bail!(
+                    "Can't embed --query for {}: its embedding model isn't a known one",
+                    source.name()
+                ),
+            };
+            let path = source.default_index_path()?;
+            let mut index = if !rebuild && path.exists() {
+                HnswIndex::load(&path)?
+            } else {
+                HnswIndex::default()
+            };
+            let mut conn = PgConnection::establish(&get_database_url().await?)?;
+            ensure_schema_current(&mut conn)?;
+            let synced = source.sync(&mut conn, &mut index)?;
+            if index.tombstones() > index.len() {
+                index.compact()?;
+            }
+            index.save(&path)?;
+            info!(
+                "Added {} entries and removed {}, {} in {}",
+                synced.added,
+                synced.removed,
+                index.len(),
+                path.display()
+            );
+            if let (Some(query), Some(model)) = (query, query_model) {
+                let provider = embedding_provider_for(model.as_ref()).await?;
+                let Some(embedding) = provider
+                    .get_embeddings(model.as_ref(), vec![EmbeddingPayload::Text(query)])
+                    .await?
+                    .pop()
+                else {
+                    eyre::bail!("{} returned no embedding for the query", provider.name());
+                };
+                for found in index.search(&embedding, k)? {
+                    println!("{:.3} {}", found.score, found.id);
+                }
+            }
+        }
+        Commands::Eval {
+            set,
+            model,
+            rerank,
+            k,
+            no_record,
+        } => {
+            let set = EvalSet::load(&set).await?;
+            let model: Box<dyn EmbeddingModel> = match WellKnownEmbeddingModels::VARIANTS
+                .iter()
+                .map(|known| known.get())
+                .find(|known| normalize_model_name(known.name()) == normalize_model_name(&model))
+            {
+                Some(known) => known,
+                None => Box::new(NamedEmbeddingModel::new(model)),
+            };
+            let provider = embedding_provider_for(model.as_ref()).await?;
+            let reranker = match rerank {
+                true => Some(get_rerank_provider().await?),
+                false => None,
+            };
+            let options = EvalOptions {
+                k_values: k,
+                ..Default::default()
+            };
+            let report = evaluate_retrieval(
+                &set,
+                provider.as_ref(),
+                model.as_ref(),
+                reranker.as_deref(),
+                &options,
+            )
+            .await?;
+            println!(
+                "{} ({} queries): {}",
+                set.name,
+                report.queries,
+                report.setup()
+            );
+            for (name, value) in report.metrics() {
+                println!("  {:<10} {:.3}", name, value);
+            }
+            if !no_record {
+                EvalHistory::open()?.append(&report).await?;
+            }
+        }
+        Commands::EvalHistory { set } => {
+            let reports = EvalHistory::open()?.load(set.as_deref()).await?;
+            if reports.is_empty() {
+                println!("No evaluation runs recorded");
+            }
+            for (report, changes) in reports.iter().zip(changes_since_previous(&reports)) {
+                println!(
+                    "\n{} {} {}",
+                    report.evaluated_at.format("%Y-%m-%d %H:%M"),
+                    report.set,
+                    report.setup()
+                );
+                let changes = changes.unwrap_or_default();
+                for (name, value) in report.metrics() {
+                    match changes.iter().find(|(changed, _)| *changed == name) {
+                        Some((_, delta)) => println!("  {:<10} {:.3} ({:+.3})", name, value, delta),
+                        None => println!("  {:<10} {:.3}", name, value),
+                    }
+                }
+            }
+        }
+        Commands::Classify {
+            items,
+            labels,
+            mode,
+            multi_label,
+            threshold,
+        } => {
+            let labels: Vec<ClassLabel> = labels
+                .iter()
+                .map(|label| match label.split_once(':') {
+                    Some((name, description)) => {
+                        ClassLabel::new(name.trim()).with_description(description.trim())
+                    }
+                    None => ClassLabel::new(label.trim()),
+                })
+                .collect();
+            let mut options = ClassifyOptions::new(mode).with_threshold(threshold);
+            options.multi_label = multi_label;
+            for classification in classify(&items, &labels, &options).await? {
+                println!(
+                    "{} => [{}]",
+                    classification.item,
+                    classification.assigned.join(", ")
+                );
+                for score in classification.scores {
+                    println!("  {:.3} {}", score.score, score.label);
+                }
+            }
+        }
+        Commands::Summarize {
+            path,
+            focus,
+            chunk_tokens,
+        } => {
+            let text = match path {
+                Some(path) => tokio::fs::read_to_string(&path)
+                    .await
+                    .wrap_err_with(|| format!("Failed to read {}", path.display()))?,
+                None => std::io::read_to_string(std::io::stdin())?,
+            };
+            let options = SummarizeOptions {
+                focus,
+                chunk_tokens,
+                ..SummarizeOptions::default()
+            };
+            let summary = summarize(&text, &options).await?;
+            println!("{}", summary.text);
+            println!();
+            println!(
+                "{} chunks, {} levels; sources:",
+                summary.chunks.len(),
+                summary.levels
+            );
+            for chunk in summary.source_chunks() {
+                println!("  [{}] bytes {}..{}", chunk.index, chunk.start, chunk.end);
+            }
+        }
+        Commands::Prompts { name } => {
+            let registry = PromptRegistry::load().await?;
+            match name {
+                Some(name) => print!("{}", toml::to_string_pretty(registry.get(&name)?)?),
+                None => {
+                    println!("Templates in {}", get_prompts_dir()?.display());
+                    for template in registry.templates() {
+                        println!(
+                            "{}@{} ({}) {}",
+                            template.name,
+                            template.version,
+                            template
+                                .source
+                                .as_ref()
+                                .map(|source| source.display().to_string())
+                                .unwrap_or_else(|| "built in".to_string()),
+                            template.description.as_deref().unwrap_or_default()
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first configured provider that serves `model`.
+async fn embedding_provider_for(
+    model: &dyn EmbeddingModel,
+) -> eyre::Result<Box<dyn EmbeddingProvider>> {
+    for provider in get_embedding_providers().await? {
+        if provider.is_supported(model).await? {
+            return Ok(provider);
+        }
+    }
+    eyre::bail!("No embedding provider serves {}", model.name())
+}
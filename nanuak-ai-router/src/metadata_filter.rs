@@ -0,0 +1,53 @@
+use serde_json::Value;
+
+use crate::embedding_store::EmbeddingMetadata;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataCondition {
+    Equals { key: String, value: Value },
+    OneOf { key: String, values: Vec<Value> },
+}
+impl MetadataCondition {
+    pub fn matches(&self, metadata: &EmbeddingMetadata) -> bool {
+        match self {
+            MetadataCondition::Equals { key, value } => metadata.get(key) == Some(value),
+            MetadataCondition::OneOf { key, values } => metadata
+                .get(key)
+                .is_some_and(|actual| values.contains(actual)),
+        }
+    }
+}
+
+/// Conditions on [`EmbeddingMetadata`] that must all hold; empty matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataFilter {
+    pub conditions: Vec<MetadataCondition>,
+}
+impl MetadataFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn eq(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.conditions.push(MetadataCondition::Equals {
+            key: key.to_string(),
+            value: value.into(),
+        });
+        self
+    }
+    pub fn one_of<V: Into<Value>>(
+        mut self,
+        key: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.conditions.push(MetadataCondition::OneOf {
+            key: key.to_string(),
+            values: values.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+    pub fn matches(&self, metadata: &EmbeddingMetadata) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(metadata))
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Modality {
     Text,
     Image,
@@ -4,6 +4,7 @@ use crate::attributes::Latency;
 use crate::attributes::Throughput;
 use crate::attributes::VramRequirement;
 
+#[derive(Debug, Clone)]
 pub struct ModelAttributes {
     pub vram_requirement: Option<VramRequirement>,
     pub latency: Option<Latency>,
@@ -11,3 +12,16 @@ pub struct ModelAttributes {
     pub throughput: Option<Throughput>,
     pub context_size: ContextSize,
 }
+
+impl ModelAttributes {
+    /// Attributes with only the static context size known.
+    pub fn unmeasured(context_size: ContextSize) -> Self {
+        ModelAttributes {
+            vram_requirement: None,
+            latency: None,
+            accuracy: None,
+            throughput: None,
+            context_size,
+        }
+    }
+}
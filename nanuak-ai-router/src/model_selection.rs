@@ -0,0 +1,211 @@
+use eyre::bail;
+use strum::VariantArray;
+use tracing::debug;
+use uom::si::f32::Information;
+use uom::si::information::byte;
+use uom::si::information_rate::byte_per_second;
+use uom::si::ratio::ratio;
+use uom::si::time::second;
+
+use crate::attributes::Residency;
+use crate::embedding_model::EmbeddingModel;
+use crate::embedding_provider::EmbeddingProvider;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::model_attributes::ModelAttributes;
+use crate::provider_registry::get_embedding_providers;
+use crate::provider_registry::get_generative_text_providers;
+use crate::well_known_embedding_models::WellKnownEmbeddingModels;
+use crate::well_known_generative_text_models::WellKnownGenerativeTextModels;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, clap::ValueEnum)]
+pub enum SelectionObjective {
+    /// Lowest measured latency
+    Fastest,
+    /// Highest measured throughput
+    HighestThroughput,
+    /// Highest measured accuracy, from benchmark quizzes or retrieval evaluations
+    MostAccurate,
+    /// Smallest VRAM footprint
+    SmallestVram,
+}
+
+/// Constraints a model must satisfy, and what to optimise among those that do.
+///
+/// Models missing a constrained or optimised attribute are excluded, so run the benchmark
+/// command first to populate latency, throughput, VRAM and generative accuracy, and the eval
+/// command for embedding accuracy.
+#[derive(Debug, Clone)]
+pub struct ModelRequirements {
+    pub residency: Option<Residency>,
+    pub min_context_size: Option<u32>,
+    pub max_vram: Option<Information>,
+    pub objective: SelectionObjective,
+}
+
+impl ModelRequirements {
+    pub fn new(objective: SelectionObjective) -> Self {
+        ModelRequirements {
+            residency: None,
+            min_context_size: None,
+            max_vram: None,
+            objective,
+        }
+    }
+    pub fn local(mut self) -> Self {
+        self.residency = Some(Residency::Local);
+        self
+    }
+    pub fn with_min_context_size(mut self, min_context_size: u32) -> Self {
+        self.min_context_size = Some(min_context_size);
+        self
+    }
+    pub fn with_max_vram(mut self, max_vram: Information) -> Self {
+        self.max_vram = Some(max_vram);
+        self
+    }
+
+    /// Lower is better; `None` means the candidate doesn't qualify.
+    fn score(&self, residency: Residency, attributes: &ModelAttributes) -> Option<f32> {
+        if self.residency.is_some_and(|wanted| wanted != residency) {
+            return None;
+        }
+        if self
+            .min_context_size
+            .is_some_and(|min| attributes.context_size.0 < min)
+        {
+            return None;
+        }
+        if let Some(max_vram) = self.max_vram {
+            let vram = attributes.vram_requirement?.0;
+            if vram > max_vram {
+                return None;
+            }
+        }
+        match self.objective {
+            SelectionObjective::Fastest => Some(attributes.latency?.0.get::<second>()),
+            SelectionObjective::HighestThroughput => {
+                Some(-attributes.throughput?.0.get::<byte_per_second>())
+            }
+            SelectionObjective::MostAccurate => Some(-attributes.accuracy?.0.get::<ratio>()),
+            SelectionObjective::SmallestVram => Some(attributes.vram_requirement?.0.get::<byte>()),
+        }
+    }
+}
+
+pub async fn select_generative_text_model(
+    requirements: &ModelRequirements,
+) -> eyre::Result<(
+    Box<dyn GenerativeTextProvider>,
+    Box<dyn GenerativeTextModel>,
+)> {
+    let mut providers = get_generative_text_providers().await?;
+    let mut best: Option<(f32, usize, WellKnownGenerativeTextModels)> = None;
+    for (i, provider) in providers.iter().enumerate() {
+        for model_kind in WellKnownGenerativeTextModels::VARIANTS {
+            let model = model_kind.get();
+            if !provider.is_supported(model.as_ref()).await.unwrap_or(false) {
+                continue;
+            }
+            let attributes = provider.get_attributes(model.as_ref()).await?;
+            let score = requirements.score(provider.get_residency(), &attributes);
+            debug!("{} on {} scored {:?}", model.name(), provider.name(), score);
+            if let Some(score) = score
+                && best.is_none_or(|(best_score, _, _)| score < best_score)
+            {
+                best = Some((score, i, *model_kind));
+            }
+        }
+    }
+    let Some((_, i, model_kind)) = best else {
+        bail!("No generative text model satisfies {:?}", requirements);
+    };
+    Ok((providers.swap_remove(i), model_kind.get()))
+}
+
+pub async fn select_embedding_model(
+    requirements: &ModelRequirements,
+) -> eyre::Result<(Box<dyn EmbeddingProvider>, Box<dyn EmbeddingModel>)> {
+    let mut providers = get_embedding_providers().await?;
+    let mut best: Option<(f32, usize, WellKnownEmbeddingModels)> = None;
+    for (i, provider) in providers.iter().enumerate() {
+        for model_kind in WellKnownEmbeddingModels::VARIANTS {
+            let model = model_kind.get();
+            if !provider.is_supported(model.as_ref()).await.unwrap_or(false) {
+                continue;
+            }
+            let attributes = provider.get_attributes(model.as_ref()).await?;
+            let score = requirements.score(provider.get_residency(), &attributes);
+            debug!("{} on {} scored {:?}", model.name(), provider.name(), score);
+            if let Some(score) = score
+                && best.is_none_or(|(best_score, _, _)| score < best_score)
+            {
+                best = Some((score, i, *model_kind));
+            }
+        }
+    }
+    let Some((_, i, model_kind)) = best else {
+        bail!("No embedding model satisfies {:?}", requirements);
+    };
+    Ok((providers.swap_remove(i), model_kind.get()))
+}
+
+#[cfg(test)]
+mod test {
+    use uom::si::f32::Information;
+    use uom::si::f32::Ratio;
+    use uom::si::f32::Time;
+    use uom::si::information::gigabyte;
+    use uom::si::ratio::ratio;
+    use uom::si::time::second;
+
+    use super::ModelRequirements;
+    use super::SelectionObjective;
+    use crate::attributes::Accuracy;
+    use crate::attributes::ContextSize;
+    use crate::attributes::Latency;
+    use crate::attributes::Residency;
+    use crate::attributes::VramRequirement;
+    use crate::model_attributes::ModelAttributes;
+
+    fn attributes(context: u32, latency: f32, vram_gb: Option<f32>) -> ModelAttributes {
+        ModelAttributes {
+            latency: Some(Latency(Time::new::<second>(latency))),
+            vram_requirement: vram_gb.map(|gb| VramRequirement(Information::new::<gigabyte>(gb))),
+            ..ModelAttributes::unmeasured(ContextSize(context))
+        }
+    }
+
+    #[test]
+    fn excludes_candidates_outside_constraints() {
+        let requirements = ModelRequirements::new(SelectionObjective::Fastest)
+            .local()
+            .with_min_context_size(8192)
+            .with_max_vram(Information::new::<gigabyte>(4.0));
+        let fits = attributes(8192, 0.5, Some(2.0));
+        assert_eq!(requirements.score(Residency::Local, &fits), Some(0.5));
+        assert_eq!(requirements.score(Residency::RemoteAnywhere, &fits), None);
+        assert_eq!(
+            requirements.score(Residency::Local, &attributes(4096, 0.1, Some(2.0))),
+            None
+        );
+        assert_eq!(
+            requirements.score(Residency::Local, &attributes(8192, 0.1, Some(6.0))),
+            None
+        );
+        assert_eq!(
+            requirements.score(Residency::Local, &attributes(8192, 0.1, None)),
+            None
+        );
+    }
+
+    #[test]
+    fn most_accurate_needs_measured_accuracy() {
+        let requirements = ModelRequirements::new(SelectionObjective::MostAccurate)
+            .with_max_vram(Information::new::<gigabyte>(4.0));
+        let mut measured = attributes(8192, 0.5, Some(2.0));
+        assert_eq!(requirements.score(Residency::Local, &measured), None);
+        measured.accuracy = Some(Accuracy(Ratio::new::<ratio>(0.75)));
+        assert_eq!(requirements.score(Residency::Local, &measured), Some(-0.75));
+    }
+}
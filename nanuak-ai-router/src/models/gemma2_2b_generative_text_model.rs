@@ -3,7 +3,7 @@ use crate::generative_text_model::GenerativeTextModel;
 
 pub struct Gemma2_2BGenerativeTextModel;
 impl GenerativeTextModel for Gemma2_2BGenerativeTextModel {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "gemma2:2b"
     }
 
@@ -0,0 +1,13 @@
+use crate::attributes::ContextSize;
+use crate::generative_text_model::GenerativeTextModel;
+
+pub struct Gemma2_9BGenerativeTextModel;
+impl GenerativeTextModel for Gemma2_9BGenerativeTextModel {
+    fn name(&self) -> &str {
+        "gemma2:9b"
+    }
+
+    fn get_context_size(&self) -> ContextSize {
+        ContextSize(8192)
+    }
+}
@@ -0,0 +1,18 @@
+use crate::attributes::ContextSize;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::modality::Modality;
+
+pub struct Llama3_2VisionGenerativeTextModel;
+impl GenerativeTextModel for Llama3_2VisionGenerativeTextModel {
+    fn name(&self) -> &str {
+        "llama3.2-vision:latest"
+    }
+
+    fn get_context_size(&self) -> ContextSize {
+        ContextSize(131072)
+    }
+
+    fn get_modalities(&self) -> Vec<Modality> {
+        vec![Modality::Text, Modality::Image]
+    }
+}
@@ -1,2 +1,9 @@
 pub mod bge_m3_embedding_model;
 pub mod gemma2_2b_generative_text_model;
+pub mod gemma2_9b_generative_text_model;
+pub mod llama3_2_vision_generative_text_model;
+pub mod named_embedding_model;
+pub mod pinned_generative_text_model;
+pub mod qwen2_5_vl_generative_text_model;
+pub mod whisper_base_en_transcription_model;
+pub mod whisper_large_v3_turbo_transcription_model;
@@ -0,0 +1,50 @@
+use crate::attributes::ContextSize;
+use crate::embedding_model::EmbeddingModel;
+use crate::embedding_space::EmbeddingSpace;
+use crate::modality::Modality;
+
+pub struct NamedEmbeddingSpace {
+    pub dimensions: Vec<u16>,
+}
+impl EmbeddingSpace for NamedEmbeddingSpace {
+    fn get_modalities(&self) -> Vec<Modality> {
+        vec![Modality::Text]
+    }
+
+    fn get_dimensions(&self) -> Vec<u16> {
+        self.dimensions.clone()
+    }
+}
+
+/// A text embedding model known only by the name its provider serves it under, e.g. to
+/// evaluate an alternative the registry doesn't list yet.
+pub struct NamedEmbeddingModel {
+    pub name: String,
+    pub context_size: u32,
+    /// Empty when unknown.
+    pub dimensions: Vec<u16>,
+}
+impl NamedEmbeddingModel {
+    pub fn new(name: impl Into<String>) -> Self {
+        NamedEmbeddingModel {
+            name: name.into(),
+            context_size: 512,
+            dimensions: Vec::new(),
+        }
+    }
+}
+impl EmbeddingModel for NamedEmbeddingModel {
+    fn get_embedding_space(&self) -> Box<dyn EmbeddingSpace> {
+        Box::new(NamedEmbeddingSpace {
+            dimensions: self.dimensions.clone(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_context_size(&self) -> ContextSize {
+        ContextSize(self.context_size)
+    }
+}
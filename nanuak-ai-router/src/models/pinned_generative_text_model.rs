@@ -0,0 +1,23 @@
+use crate::attributes::ContextSize;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::modality::Modality;
+
+/// A model named in config rather than known to the registry.
+pub struct PinnedGenerativeTextModel {
+    pub name: String,
+    pub context_size: u32,
+    pub modalities: Vec<Modality>,
+}
+impl GenerativeTextModel for PinnedGenerativeTextModel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_context_size(&self) -> ContextSize {
+        ContextSize(self.context_size)
+    }
+
+    fn get_modalities(&self) -> Vec<Modality> {
+        self.modalities.clone()
+    }
+}
@@ -0,0 +1,18 @@
+use crate::attributes::ContextSize;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::modality::Modality;
+
+pub struct Qwen2_5VLGenerativeTextModel;
+impl GenerativeTextModel for Qwen2_5VLGenerativeTextModel {
+    fn name(&self) -> &str {
+        "qwen2.5vl:7b"
+    }
+
+    fn get_context_size(&self) -> ContextSize {
+        ContextSize(32768)
+    }
+
+    fn get_modalities(&self) -> Vec<Modality> {
+        vec![Modality::Text, Modality::Image]
+    }
+}
@@ -0,0 +1,12 @@
+use crate::transcription_model::TranscriptionModel;
+
+pub struct WhisperBaseEnTranscriptionModel;
+impl TranscriptionModel for WhisperBaseEnTranscriptionModel {
+    fn name(&self) -> &'static str {
+        "whisper-base.en"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "ggml-base.en.bin"
+    }
+}
@@ -0,0 +1,12 @@
+use crate::transcription_model::TranscriptionModel;
+
+pub struct WhisperLargeV3TurboTranscriptionModel;
+impl TranscriptionModel for WhisperLargeV3TurboTranscriptionModel {
+    fn name(&self) -> &'static str {
+        "whisper-large-v3-turbo"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "ggml-large-v3-turbo.bin"
+    }
+}
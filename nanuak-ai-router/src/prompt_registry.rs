@@ -0,0 +1,123 @@
+//! Prompt templates by name: the built-in ones in `prompts/`, overridden by any `.toml`
+//! files in the `prompts` folder of the Nanuak config dir.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use eyre::Context;
+use eyre::OptionExt;
+use nanuak_config::dirs::get_prompts_dir;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::prompt_template::PromptTemplate;
+
+const BUILTIN_TEMPLATES: &[&str] = &[
+    include_str!("../prompts/classify.toml"),
+    include_str!("../prompts/meme-description.toml"),
+    include_str!("../prompts/pick-tie-break.toml"),
+    include_str!("../prompts/question.toml"),
+    include_str!("../prompts/rerank-judge.toml"),
+    include_str!("../prompts/summarize-chunk.toml"),
+    include_str!("../prompts/summarize-reduce.toml"),
+];
+
+static GLOBAL: OnceCell<PromptRegistry> = OnceCell::const_new();
+
+#[derive(Debug, Clone, Default)]
+pub struct PromptRegistry {
+    templates: BTreeMap<String, PromptTemplate>,
+}
+impl PromptRegistry {
+    pub fn builtin() -> Self {
+        let mut registry = PromptRegistry::default();
+        for toml in BUILTIN_TEMPLATES {
+            registry.insert(PromptTemplate::from_toml(toml).expect("built-in prompt is valid"));
+        }
+        registry
+    }
+    /// The built-in templates overridden by those in the config dir.
+    pub async fn load() -> eyre::Result<Self> {
+        let mut registry = Self::builtin();
+        registry.load_dir(&get_prompts_dir()?).await?;
+        Ok(registry)
+    }
+    /// Loaded once per process.
+    pub async fn global() -> eyre::Result<&'static PromptRegistry> {
+        GLOBAL.get_or_try_init(Self::load).await
+    }
+    /// Adds every `.toml` template in `dir`, replacing templates of the same name.
+    pub async fn load_dir(&mut self, dir: &Path) -> eyre::Result<()> {
+        if !tokio::fs::try_exists(dir).await? {
+            return Ok(());
+        }
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+            let toml = tokio::fs::read_to_string(&path).await?;
+            let mut template = PromptTemplate::from_toml(&toml)
+                .wrap_err_with(|| format!("Failed to parse prompt template: {}", path.display()))?;
+            debug!(
+                "Loaded prompt {}@{} from {}",
+                template.name,
+                template.version,
+                path.display()
+            );
+            template.source = Some(path);
+            self.insert(template);
+        }
+        Ok(())
+    }
+    pub fn insert(&mut self, template: PromptTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+    pub fn get(&self, name: &str) -> eyre::Result<&PromptTemplate> {
+        self.templates
+            .get(name)
+            .ok_or_eyre(format!("No prompt template named {}", name))
+    }
+    pub fn templates(&self) -> impl Iterator<Item = &PromptTemplate> {
+        self.templates.values()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PromptRegistry;
+    use crate::prompt_template::PromptVariables;
+
+    #[tokio::test]
+    async fn config_templates_override_builtins() -> eyre::Result<()> {
+        let dir = std::env::temp_dir().join(format!("nanuak-prompts-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(
+            dir.join("meme.toml"),
+            "name = \"meme-description\"\nversion = 2\nbody = \"What is funny here?\"\n",
+        )
+        .await?;
+        let mut registry = PromptRegistry::builtin();
+        registry.load_dir(&dir).await?;
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        let rendered = registry
+            .get("meme-description")?
+            .render("qwen2.5vl:7b", &PromptVariables::new())?;
+        assert_eq!(rendered.body, "What is funny here?");
+        assert_eq!(rendered.template.to_string(), "meme-description@2");
+
+        let question = registry.get("question")?.render(
+            "gemma2:2b",
+            &PromptVariables::new()
+                .with("question", "Why?")
+                .with("context", vec!["Because."]),
+        )?;
+        assert_eq!(
+            question.body,
+            "<context0>\nBecause.\n</context0>\n<question>\nWhy?\n</question>"
+        );
+        Ok(())
+    }
+}
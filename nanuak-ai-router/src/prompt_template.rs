@@ -0,0 +1,296 @@
+//! Named prompts with typed variables and per-model variants.
+//!
+//! Bodies substitute `{{name}}` and repeat `{{#each name}}...{{/each}}` blocks over list
+//! variables, where `{{this}}` is the item and `{{@index}}` its position.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use eyre::OptionExt;
+use eyre::bail;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::conversation::Conversation;
+use crate::conversation::Message;
+use crate::providers::ollama_models_api::normalize_model_name;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptVariableKind {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+    /// A list of text items
+    List,
+}
+impl PromptVariableKind {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            PromptVariableKind::Text => value.is_string(),
+            PromptVariableKind::Integer => value.is_i64() || value.is_u64(),
+            PromptVariableKind::Number => value.is_number(),
+            PromptVariableKind::Boolean => value.is_boolean(),
+            PromptVariableKind::List => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVariable {
+    pub kind: PromptVariableKind,
+    /// Used when the caller doesn't supply the variable; without one the variable is required.
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Replaces the system prompt and/or body for one model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptVariant {
+    #[serde(default)]
+    pub system: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    /// Bump whenever the wording changes, so stored outputs can be traced to the prompt.
+    pub version: u32,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub system: Option<String>,
+    pub body: String,
+    #[serde(default)]
+    pub variables: BTreeMap<String, PromptVariable>,
+    /// Keyed by model name, e.g. `gemma2:9b`, or by family, e.g. `gemma2`.
+    #[serde(default)]
+    pub variants: BTreeMap<String, PromptVariant>,
+    /// The file this template was loaded from, if not built in.
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+/// Identifies the template, version and variant that produced a prompt.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplateRef {
+    pub name: String,
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+impl Display for PromptTemplateRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "/{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PromptVariables(BTreeMap<String, Value>);
+impl PromptVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.0.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedPrompt {
+    pub template: PromptTemplateRef,
+    pub system: Option<String>,
+    pub body: String,
+}
+impl RenderedPrompt {
+    /// A conversation of the system prompt, if any, and the body as a user message.
+    pub fn to_conversation(&self, images: Vec<PathBuf>) -> Conversation {
+        let mut conversation = Conversation::new()
+            .with_message(Message::user(self.body.clone()).with_images(images))
+            .with_prompt(self.template.clone());
+        if let Some(system) = &self.system {
+            conversation = conversation.with_system_prompt(system.clone());
+        }
+        conversation
+    }
+}
+
+impl PromptTemplate {
+    pub fn from_toml(toml: &str) -> eyre::Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+    /// The variant key used for `model`: an exact name match, then its family before the `:`.
+    pub fn variant_for(&self, model: &str) -> Option<&str> {
+        let family = model.split(':').next().unwrap_or(model);
+        self.variants
+            .keys()
+            .find(|key| normalize_model_name(key) == normalize_model_name(model))
+            .or_else(|| self.variants.keys().find(|key| key.as_str() == family))
+            .map(String::as_str)
+    }
+    pub fn render(&self, model: &str, variables: &PromptVariables) -> eyre::Result<RenderedPrompt> {
+        for name in variables.0.keys() {
+            if !self.variables.contains_key(name) {
+                bail!("Prompt {} has no variable named {}", self.name, name);
+            }
+        }
+        let mut values = BTreeMap::new();
+        for (name, variable) in &self.variables {
+            let Some(value) = variables.0.get(name).or(variable.default.as_ref()) else {
+                bail!("Prompt {} requires variable {}", self.name, name);
+            };
+            if !variable.kind.accepts(value) {
+                bail!(
+                    "Prompt {} expects {} to be {:?}, got {}",
+                    self.name,
+                    name,
+                    variable.kind,
+                    value
+                );
+            }
+            values.insert(name.as_str(), value);
+        }
+        let variant_key = self.variant_for(model);
+        let variant = variant_key.and_then(|key| self.variants.get(key));
+        let system = variant
+            .and_then(|variant| variant.system.as_ref())
+            .or(self.system.as_ref());
+        let body = variant
+            .and_then(|variant| variant.body.as_ref())
+            .unwrap_or(&self.body);
+        Ok(RenderedPrompt {
+            template: PromptTemplateRef {
+                name: self.name.clone(),
+                version: self.version,
+                variant: variant_key.map(str::to_string),
+            },
+            system: system
+                .map(|system| render_text(system, &values, None))
+                .transpose()?,
+            body: render_text(body, &values, None)?,
+        })
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => items.iter().map(value_text).collect::<Vec<_>>().join("\n"),
+        other => other.to_string(),
+    }
+}
+
+fn render_text(
+    text: &str,
+    values: &BTreeMap<&str, &Value>,
+    item: Option<(usize, &Value)>,
+) -> eyre::Result<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_eyre("Unclosed {{ in prompt")?;
+        let tag = rest[start + 2..end].trim();
+        rest = &rest[end + 2..];
+        if let Some(name) = tag.strip_prefix("#each ") {
+            let name = name.trim();
+            let close = rest
+                .find("{{/each}}")
+                .ok_or_else(|| eyre::eyre!("{{{{#each {}}}}} is never closed", name))?;
+            let block = &rest[..close];
+            rest = &rest[close + "{{/each}}".len()..];
+            let Some(items) = values.get(name).and_then(|value| value.as_array()) else {
+                bail!("{{{{#each {}}}}} needs a list variable", name);
+            };
+            for (i, value) in items.iter().enumerate() {
+                rendered.push_str(&render_text(block, values, Some((i, value)))?);
+            }
+            continue;
+        }
+        match (tag, item) {
+            ("this", Some((_, value))) => rendered.push_str(&value_text(value)),
+            ("@index", Some((i, _))) => rendered.push_str(&i.to_string()),
+            (name, _) => match values.get(name) {
+                Some(value) => rendered.push_str(&value_text(value)),
+                None => bail!("Unknown prompt variable {}", name),
+            },
+        }
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod test {
+    use super::PromptTemplate;
+    use super::PromptVariables;
+
+    const TEMPLATE: &str = r#"
+name = "summary"
+version = 3
+system = "You summarize."
+body = "{{#each context}}[{{@index}}] {{this}}\n{{/each}}In {{words}} words: {{topic}}"
+
+[variables.topic]
+kind = "text"
+
+[variables.words]
+kind = "integer"
+default = 50
+
+[variables.context]
+kind = "list"
+default = []
+
+[variants.gemma2]
+body = "Summarize {{topic}} in {{words}} words."
+"#;
+
+    #[test]
+    fn renders_variables_and_variants() -> eyre::Result<()> {
+        let template = PromptTemplate::from_toml(TEMPLATE)?;
+        let variables = PromptVariables::new()
+            .with("topic", "rust")
+            .with("context", vec!["a", "b"]);
+
+        let rendered = template.render("qwen2.5:14b", &variables)?;
+        assert_eq!(rendered.body, "[0] a\n[1] b\nIn 50 words: rust");
+        assert_eq!(rendered.system.as_deref(), Some("You summarize."));
+        assert_eq!(rendered.template.to_string(), "summary@3");
+
+        let rendered = template.render("gemma2:9b", &variables)?;
+        assert_eq!(rendered.body, "Summarize rust in 50 words.");
+        assert_eq!(rendered.template.to_string(), "summary@3/gemma2");
+
+        assert!(template.render("x", &PromptVariables::new()).is_err());
+        assert!(
+            template
+                .render("x", &variables.clone().with("words", "fifty"))
+                .is_err()
+        );
+        assert!(
+            template
+                .render("x", &variables.with("tone", "dry"))
+                .is_err()
+        );
+        Ok(())
+    }
+}
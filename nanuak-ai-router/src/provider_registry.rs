@@ -0,0 +1,131 @@
+//! The provider instances requests are routed to.
+//!
+//! Instances come from `PROVIDER_INSTANCES` in the Nanuak config, in the order listed, so
+//! several servers of one kind (a GPU box and a laptop, say) can back the same strategy.
+//! Without that entry, one default instance of each well-known provider is used.
+
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::provider_instances::ProviderInstance;
+use nanuak_config::provider_instances::ProviderInstances;
+use nanuak_config::provider_instances::ProviderKind;
+use strum::VariantArray;
+
+use crate::embedding_provider::EmbeddingProvider;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::providers::http_generate_generative_text_provider::HttpGenerateGenerativeTextProvider;
+use crate::providers::ollama_embedding_provider::OllamaEmbeddingProvider;
+use crate::providers::ollama_generative_text_provider::OllamaGenerativeTextProvider;
+use crate::providers::ollama_instance::OllamaInstance;
+use crate::well_known_embedding_providers::WellKnownEmbeddingProviders;
+use crate::well_known_generative_text_providers::WellKnownGenerativeTextProviders;
+
+/// The configured instances, or `None` when the config doesn't list any.
+pub async fn get_provider_instances() -> eyre::Result<Option<Vec<ProviderInstance>>> {
+    let config = NanuakConfig::acquire().await?;
+    Ok(config
+        .get_optional::<ProviderInstances>()?
+        .filter(|instances| !instances.is_empty()))
+}
+
+pub async fn get_embedding_providers() -> eyre::Result<Vec<Box<dyn EmbeddingProvider>>> {
+    match get_provider_instances().await? {
+        Some(instances) => embedding_providers_from(&instances),
+        None => Ok(WellKnownEmbeddingProviders::VARIANTS
+            .iter()
+            .map(|provider| provider.get())
+            .collect()),
+    }
+}
+
+pub async fn get_generative_text_providers() -> eyre::Result<Vec<Box<dyn GenerativeTextProvider>>> {
+    match get_provider_instances().await? {
+        Some(instances) => generative_text_providers_from(&instances),
+        None => Ok(WellKnownGenerativeTextProviders::VARIANTS
+            .iter()
+            .map(|provider| provider.get())
+            .collect()),
+    }
+}
+
+/// Embedding providers for the instances whose kind can embed.
+pub fn embedding_providers_from(
+    instances: &[ProviderInstance],
+) -> eyre::Result<Vec<Box<dyn EmbeddingProvider>>> {
+    let mut providers: Vec<Box<dyn EmbeddingProvider>> = Vec::new();
+    for instance in instances {
+        match instance.kind {
+            ProviderKind::Ollama => providers.push(Box::new(OllamaEmbeddingProvider::new(
+                OllamaInstance::from_config(instance)?,
+            ))),
+            ProviderKind::HttpGenerate => {}
+        }
+    }
+    Ok(providers)
+}
+
+pub fn generative_text_providers_from(
+    instances: &[ProviderInstance],
+) -> eyre::Result<Vec<Box<dyn GenerativeTextProvider>>> {
+    let mut providers: Vec<Box<dyn GenerativeTextProvider>> = Vec::new();
+    for instance in instances {
+        match instance.kind {
+            ProviderKind::Ollama => providers.push(Box::new(OllamaGenerativeTextProvider::new(
+                OllamaInstance::from_config(instance)?,
+            ))),
+            ProviderKind::HttpGenerate => providers.push(Box::new(
+                HttpGenerateGenerativeTextProvider::from_config(instance),
+            )),
+        }
+    }
+    Ok(providers)
+}
+
+#[cfg(test)]
+mod test {
+    use nanuak_config::provider_instances::ProviderInstance;
+
+    use super::embedding_providers_from;
+    use super::generative_text_providers_from;
+    use crate::attributes::Residency;
+
+    #[test]
+    fn builds_one_provider_per_instance() -> eyre::Result<()> {
+        let instances: Vec<ProviderInstance> = toml::from_str::<toml::Table>(
+            r#"
+            [[value]]
+            name = "gpu-box"
+            kind = "ollama"
+            host = "gpu-box.lan"
+            port = 11434
+            keep_alive = "30m"
+            num_ctx = 16384
+            auth_header = "Bearer abc123"
+
+            [[value]]
+            name = "laptop"
+            kind = "ollama"
+            host = "http://127.0.0.1:11434"
+
+            [[value]]
+            name = "qwen"
+            kind = "http-generate"
+            host = "qwen.example.com"
+            remote = true
+            "#,
+        )?["value"]
+            .clone()
+            .try_into()?;
+        assert_eq!(instances[0].base_url(), "http://gpu-box.lan:11434");
+        assert_eq!(instances[1].base_url(), "http://127.0.0.1:11434");
+
+        let embedding = embedding_providers_from(&instances)?;
+        let names: Vec<&str> = embedding.iter().map(|provider| provider.name()).collect();
+        assert_eq!(names, vec!["gpu-box", "laptop"]);
+
+        let generative = generative_text_providers_from(&instances)?;
+        let names: Vec<&str> = generative.iter().map(|provider| provider.name()).collect();
+        assert_eq!(names, vec!["gpu-box", "laptop", "qwen"]);
+        assert_eq!(generative[2].get_residency(), Residency::RemoteAnywhere);
+        Ok(())
+    }
+}
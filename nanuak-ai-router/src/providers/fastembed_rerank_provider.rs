@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use fastembed::RerankInitOptions;
+use fastembed::RerankerModel;
+use fastembed::TextRerank;
+use nanuak_config::dirs::get_project_dirs;
+use tokio::sync::OnceCell;
+
+use crate::attributes::Residency;
+use crate::rerank_provider::RerankProvider;
+use crate::telemetry::CallModality;
+use crate::telemetry::CallRecord;
+use crate::telemetry::record;
+
+/// A cross-encoder run on the CPU through ONNX Runtime. Weights are downloaded from
+/// Hugging Face into `cache_dir` on first use.
+pub struct FastembedRerankProvider {
+    pub model: RerankerModel,
+    pub cache_dir: PathBuf,
+    inner: OnceCell<Arc<TextRerank>>,
+}
+impl FastembedRerankProvider {
+    pub fn new(model: RerankerModel, cache_dir: PathBuf) -> Self {
+        FastembedRerankProvider {
+            model,
+            cache_dir,
+            inner: OnceCell::new(),
+        }
+    }
+    /// bge-reranker-v2-m3, matching the bge-m3 embeddings we search with.
+    pub fn from_project_dirs() -> eyre::Result<Self> {
+        Ok(Self::new(
+            RerankerModel::BGERerankerV2M3,
+            get_project_dirs()?.cache_dir().join("fastembed"),
+        ))
+    }
+    /// Downloads and loads the model if that hasn't happened yet.
+    pub async fn load(&self) -> eyre::Result<()> {
+        self.reranker().await.map(|_| ())
+    }
+    async fn reranker(&self) -> eyre::Result<Arc<TextRerank>> {
+        let reranker = self
+            .inner
+            .get_or_try_init(|| async {
+                let options = RerankInitOptions::new(self.model.clone())
+                    .with_cache_dir(self.cache_dir.clone())
+                    .with_show_download_progress(false);
+                let reranker = tokio::task::spawn_blocking(move || TextRerank::try_new(options))
+                    .await?
+                    .map_err(|error| eyre::eyre!("Failed to load reranker: {}", error))?;
+                Ok::<_, eyre::Report>(Arc::new(reranker))
+            })
+            .await?;
+        Ok(reranker.clone())
+    }
+}
+
+#[async_trait]
+impl RerankProvider for FastembedRerankProvider {
+    fn name(&self) -> &str {
+        "fastembed"
+    }
+    async fn score(&self, query: &str, documents: &[String]) -> eyre::Result<Vec<f32>> {
+        let reranker = self.reranker().await?;
+        let query = query.to_string();
+        let documents = documents.to_vec();
+        let count = documents.len();
+        let start = Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            reranker.rerank(
+                query.as_str(),
+                documents.iter().map(String::as_str).collect(),
+                false,
+                None,
+            )
+        })
+        .await?
+        .map_err(|error| eyre::eyre!("Reranking failed: {}", error));
+        let call = CallRecord::new(
+            self.name(),
+            &self.model.to_string(),
+            CallModality::Rerank,
+            start.elapsed(),
+        );
+        let results = match result {
+            Ok(results) => {
+                record(call);
+                results
+            }
+            Err(error) => {
+                record(call.with_error(&error));
+                return Err(error);
+            }
+        };
+        // Results come back sorted by score; put them back in document order.
+        let mut scores = vec![f32::NEG_INFINITY; count];
+        for result in results {
+            scores[result.index] = result.score;
+        }
+        Ok(scores)
+    }
+    fn get_residency(&self) -> Residency {
+        Residency::Local
+    }
+}
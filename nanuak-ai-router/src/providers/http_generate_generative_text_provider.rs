@@ -0,0 +1,256 @@
+//! Client for servers shaped like nanuak-qwen's `server.py`: `POST /generate` with
+//! `{"messages": [...]}` in the Hugging Face chat-template format, answering `{"response"}`
+//! or `{"error"}`.
+
+use std::path::Path;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use eyre::Context;
+use eyre::bail;
+use nanuak_config::provider_instances::ProviderInstance;
+use reqwest::header::AUTHORIZATION;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::answer::Answer;
+use crate::attributes::Residency;
+use crate::benchmark::measured_attributes;
+use crate::conversation::Conversation;
+use crate::conversation::Message;
+use crate::conversation::Role;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::model_attributes::ModelAttributes;
+use crate::question::Question;
+use crate::telemetry::CallModality;
+use crate::telemetry::CallRecord;
+use crate::telemetry::record;
+
+/// Where `flask run` listens by default.
+pub const DEFAULT_HTTP_GENERATE_URL: &str = "http://127.0.0.1:5000";
+
+/// The model nanuak-qwen serves.
+pub const DEFAULT_HTTP_GENERATE_MODEL: &str = "qwen2.5vl:7b";
+
+pub struct HttpGenerateGenerativeTextProvider {
+    pub name: String,
+    pub base_url: String,
+    /// The one model the server has loaded, named as in the model registry.
+    pub served_model: String,
+    /// Sent verbatim as the `Authorization` header.
+    pub auth_header: Option<String>,
+    pub residency: Residency,
+}
+impl Default for HttpGenerateGenerativeTextProvider {
+    fn default() -> Self {
+        HttpGenerateGenerativeTextProvider {
+            name: "http-generate".to_string(),
+            base_url: DEFAULT_HTTP_GENERATE_URL.to_string(),
+            served_model: DEFAULT_HTTP_GENERATE_MODEL.to_string(),
+            auth_header: None,
+            residency: Residency::Local,
+        }
+    }
+}
+impl HttpGenerateGenerativeTextProvider {
+    pub fn from_config(instance: &ProviderInstance) -> Self {
+        HttpGenerateGenerativeTextProvider {
+            name: instance.name.clone(),
+            base_url: instance.base_url(),
+            served_model: instance
+                .served_model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_HTTP_GENERATE_MODEL.to_string()),
+            auth_header: instance.auth_header.clone(),
+            residency: if instance.remote {
+                Residency::RemoteAnywhere
+            } else {
+                Residency::Local
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GenerateRequest {
+    messages: Vec<GenerateMessage>,
+}
+
+#[derive(Debug, Serialize)]
+struct GenerateMessage {
+    role: &'static str,
+    content: Vec<GenerateContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum GenerateContent {
+    Text { text: String },
+    Image { image: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: Option<String>,
+    error: Option<String>,
+}
+
+impl GenerateMessage {
+    async fn from_message(message: &Message) -> eyre::Result<Self> {
+        let role = match message.role {
+            Role::System => "system",
+            Role::User | Role::Tool => "user",
+            Role::Assistant => "assistant",
+        };
+        let mut content = Vec::with_capacity(message.images.len() + 1);
+        for path in &message.images {
+            content.push(GenerateContent::Image {
+                image: image_data_uri(path).await?,
+            });
+        }
+        content.push(GenerateContent::Text {
+            text: message.content.clone(),
+        });
+        Ok(GenerateMessage { role, content })
+    }
+}
+
+async fn image_data_uri(path: &Path) -> eyre::Result<String> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .wrap_err_with(|| format!("Failed to read image: {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let mime = match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    };
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+#[async_trait]
+impl GenerativeTextProvider for HttpGenerateGenerativeTextProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    async fn is_supported(&self, model: &dyn GenerativeTextModel) -> eyre::Result<bool> {
+        if model.name() != self.served_model {
+            return Ok(false);
+        }
+        // Any response, even a 404, means the server is up.
+        Ok(self
+            .request(reqwest::Method::GET, &self.base_url)
+            .send()
+            .await
+            .is_ok())
+    }
+    async fn get_attributes(
+        &self,
+        model: &dyn GenerativeTextModel,
+    ) -> eyre::Result<ModelAttributes> {
+        let attributes = ModelAttributes::unmeasured(model.get_context_size());
+        Ok(measured_attributes(self.name(), model.name(), attributes).await)
+    }
+    fn get_residency(&self) -> Residency {
+        self.residency
+    }
+    async fn answer_question(
+        &self,
+        model: &dyn GenerativeTextModel,
+        question: Question,
+    ) -> eyre::Result<Answer> {
+        let images = question.images.clone();
+        let conversation = question.render(Some(model)).await?.to_conversation(images);
+        self.chat(model, &conversation).await
+    }
+    async fn chat(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+    ) -> eyre::Result<Answer> {
+        if model.name() != self.served_model {
+            bail!(
+                "{} serves {}, not {}",
+                self.base_url,
+                self.served_model,
+                model.name()
+            );
+        }
+        let mut messages = Vec::with_capacity(conversation.messages.len());
+        for message in &conversation.messages {
+            messages.push(GenerateMessage::from_message(message).await?);
+        }
+        let start = Instant::now();
+        let result = self.send(&GenerateRequest { messages }).await;
+        let call = CallRecord::new(
+            self.name(),
+            model.name(),
+            CallModality::GenerativeText,
+            start.elapsed(),
+        )
+        .with_prompt(conversation.prompt.as_ref());
+        match result {
+            Ok(body) => {
+                record(call);
+                Ok(Answer::new(body).with_prompt(conversation.prompt.clone()))
+            }
+            Err(error) => {
+                record(call.with_error(&error));
+                Err(error)
+            }
+        }
+    }
+    /// The server can't constrain decoding, so the schema is only asked for in the prompt;
+    /// pair this with [`crate::structured_answer::StructuredAnswerExt`] to retry bad output.
+    async fn chat_with_schema(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        schema: &serde_json::Value,
+    ) -> eyre::Result<Answer> {
+        let conversation = conversation.clone().with_message(Message::user(format!(
+            "Respond with only JSON matching this schema:\n{}",
+            schema
+        )));
+        self.chat(model, &conversation).await
+    }
+    async fn format_question(&self, question: Question) -> eyre::Result<String> {
+        Ok(question.render(None).await?.body)
+    }
+}
+impl HttpGenerateGenerativeTextProvider {
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = reqwest::Client::new().request(method, url);
+        match &self.auth_header {
+            Some(auth_header) => request.header(AUTHORIZATION, auth_header),
+            None => request,
+        }
+    }
+    async fn send(&self, request: &GenerateRequest) -> eyre::Result<String> {
+        let url = format!("{}/generate", self.base_url.trim_end_matches('/'));
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(request)
+            .send()
+            .await?;
+        let status = response.status();
+        let body: GenerateResponse = response
+            .json()
+            .await
+            .wrap_err_with(|| format!("Failed to parse response from {}", url))?;
+        match (body.response, body.error) {
+            (Some(response), None) if status.is_success() => Ok(response),
+            (_, Some(error)) => bail!("{} returned {}: {}", url, status, error),
+            _ => bail!("{} returned {} without a response", url, status),
+        }
+    }
+}
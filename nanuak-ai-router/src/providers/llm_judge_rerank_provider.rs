@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::attributes::Residency;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use crate::prompt_registry::PromptRegistry;
+use crate::prompt_template::PromptVariables;
+use crate::rerank_provider::RerankProvider;
+use crate::structured_answer::StructuredAnswerExt;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct Judgement {
+    /// 0 to 10
+    relevance: u8,
+}
+
+/// Asks a chat model to rate each (query, document) pair, for when no cross-encoder is
+/// available. Much slower than a cross-encoder, so keep the top-N small.
+pub struct LlmJudgeRerankProvider {
+    pub provider: Box<dyn GenerativeTextProvider>,
+    pub model: Box<dyn GenerativeTextModel>,
+}
+impl LlmJudgeRerankProvider {
+    pub fn new(
+        provider: Box<dyn GenerativeTextProvider>,
+        model: Box<dyn GenerativeTextModel>,
+    ) -> Self {
+        LlmJudgeRerankProvider { provider, model }
+    }
+    pub async fn from_strategy(strategy: WellKnownGenerativeTextStrategy) -> eyre::Result<Self> {
+        let (provider, model) = strategy.resolve().await?;
+        Ok(Self::new(provider, model))
+    }
+}
+
+#[async_trait]
+impl RerankProvider for LlmJudgeRerankProvider {
+    fn name(&self) -> &str {
+        "llm-judge"
+    }
+    async fn score(&self, query: &str, documents: &[String]) -> eyre::Result<Vec<f32>> {
+        let template = PromptRegistry::global().await?.get("rerank-judge")?;
+        let mut scores = Vec::with_capacity(documents.len());
+        for document in documents {
+            let conversation = template
+                .render(
+                    self.model.name(),
+                    &PromptVariables::new()
+                        .with("query", query)
+                        .with("document", document.as_str()),
+                )?
+                .to_conversation(Vec::new());
+            let judgement: Judgement = self
+                .provider
+                .answer_structured(self.model.as_ref(), &conversation)
+                .await?;
+            scores.push(judgement.relevance.min(10) as f32 / 10.0);
+        }
+        Ok(scores)
+    }
+    fn get_residency(&self) -> Residency {
+        self.provider.get_residency()
+    }
+}
@@ -0,0 +1,160 @@
+//! Deterministic embeddings for hermetic tests.
+//!
+//! Texts found in the fixture table get their recorded vector. Anything else gets the sum of
+//! pseudo-random unit vectors seeded by a stable hash of each lowercase word, so the same text
+//! always embeds the same way and texts sharing words score as similar.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use eyre::Context;
+use eyre::bail;
+
+use crate::attributes::Residency;
+use crate::embedding::Embedding;
+use crate::embedding_model::EmbeddingModel;
+use crate::embedding_provider::EmbeddingProvider;
+use crate::embedding_request::EmbeddingPayload;
+use crate::model_attributes::ModelAttributes;
+use crate::providers::mock_registry;
+use crate::providers::mock_registry::MockInstallation;
+use crate::splitmix::splitmix64;
+
+#[derive(Debug, Clone, Default)]
+pub struct MockEmbeddingProvider {
+    /// Length of hashed vectors; the model's first advertised dimension when unset.
+    pub dimensions: Option<usize>,
+    fixtures: Arc<HashMap<String, Vec<f32>>>,
+}
+impl MockEmbeddingProvider {
+    pub fn new(dimensions: usize) -> Self {
+        MockEmbeddingProvider {
+            dimensions: Some(dimensions),
+            fixtures: Default::default(),
+        }
+    }
+    /// Loads a JSON object mapping texts to vectors, e.g. `{"dog": [0.9, 0.1]}`.
+    pub async fn from_fixture_file(path: &Path) -> eyre::Result<Self> {
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .wrap_err_with(|| format!("Failed to read embedding fixtures: {}", path.display()))?;
+        let fixtures: HashMap<String, Vec<f32>> = serde_json::from_str(&json)
+            .wrap_err_with(|| format!("Failed to parse embedding fixtures: {}", path.display()))?;
+        let mut provider = MockEmbeddingProvider::default();
+        for (text, vector) in fixtures {
+            provider = provider.with_fixture(&text, vector);
+        }
+        Ok(provider)
+    }
+    /// Records the vector returned for `text`, matched after trimming whitespace.
+    pub fn with_fixture(mut self, text: &str, vector: Vec<f32>) -> Self {
+        Arc::make_mut(&mut self.fixtures).insert(text.trim().to_string(), vector);
+        self
+    }
+    /// Makes this the provider behind [`crate::embedding_strategy::WellKnownEmbeddingStrategy::Mock`]
+    /// for the whole process until the returned installation is dropped.
+    pub fn install(self) -> MockInstallation {
+        mock_registry::install(|mocks| mocks.embedding = Some(self))
+    }
+    pub fn installed() -> Option<Self> {
+        mock_registry::installed(|mocks| mocks.embedding.clone())
+    }
+    pub fn embed(&self, text: &str, dimensions: usize) -> Embedding {
+        let text = text.trim();
+        if let Some(vector) = self.fixtures.get(text) {
+            return Embedding(vector.clone());
+        }
+        let mut words = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .peekable();
+        let mut vector = vec![0.0; dimensions];
+        if words.peek().is_none() {
+            add_hashed(&mut vector, text);
+        }
+        for word in words {
+            add_hashed(&mut vector, &word);
+        }
+        Embedding(vector).normalized()
+    }
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn add_hashed(vector: &mut [f32], text: &str) {
+    let mut state = stable_hash(text);
+    for value in vector.iter_mut() {
+        let z = splitmix64(&mut state);
+        *value += (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0;
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for MockEmbeddingProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+    async fn is_supported(&self, _model: &dyn EmbeddingModel) -> eyre::Result<bool> {
+        Ok(true)
+    }
+    async fn get_embeddings(
+        &self,
+        model: &dyn EmbeddingModel,
+        payloads: Vec<EmbeddingPayload>,
+    ) -> eyre::Result<Vec<Embedding>> {
+        let dimensions = match self.dimensions {
+            Some(dimensions) => dimensions,
+            None => model
+                .get_embedding_space()
+                .get_dimensions()
+                .first()
+                .copied()
+                .unwrap_or(1024) as usize,
+        };
+        let mut embeddings = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            match payload {
+                EmbeddingPayload::Text(text) => embeddings.push(self.embed(&text, dimensions)),
+                EmbeddingPayload::ImagePath(path) => match path.to_str() {
+                    Some(path) => embeddings.push(self.embed(path, dimensions)),
+                    None => bail!("Image path is not valid UTF-8: {}", path.display()),
+                },
+            }
+        }
+        Ok(embeddings)
+    }
+    async fn get_attributes(&self, model: &dyn EmbeddingModel) -> eyre::Result<ModelAttributes> {
+        Ok(ModelAttributes::unmeasured(model.get_context_size()))
+    }
+    fn get_residency(&self) -> Residency {
+        Residency::Local
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MockEmbeddingProvider;
+
+    #[test]
+    fn embeds_deterministically_with_word_overlap() -> eyre::Result<()> {
+        let provider = MockEmbeddingProvider::new(64).with_fixture("animal", vec![1.0, 0.0]);
+        assert_eq!(provider.embed(" animal\n", 64).0, vec![1.0, 0.0]);
+
+        let a = provider.embed("Rust borrow checker", 64);
+        assert_eq!(a.0, provider.embed("rust borrow checker", 64).0);
+        assert_eq!(a.dimensions(), 64);
+        // Hashed vectors are unit length, so the dot product is the cosine similarity.
+        let related = a.dot_product(&provider.embed("the borrow checker", 64))?;
+        let unrelated = a.dot_product(&provider.embed("cast iron steak", 64))?;
+        assert!(related > unrelated, "{} <= {}", related, unrelated);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Scripted answers for hermetic tests.
+//!
+//! Queued replies are returned in order first, then the first rule whose pattern appears in
+//! the latest message, then the fallback. Every conversation received is recorded so tests
+//! can assert on prompts.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use eyre::bail;
+
+use crate::answer::Answer;
+use crate::attributes::Residency;
+use crate::conversation::Conversation;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::model_attributes::ModelAttributes;
+use crate::providers::mock_registry;
+use crate::providers::mock_registry::MockInstallation;
+use crate::question::Question;
+use crate::tool::ToolCall;
+use crate::tool::ToolDefinition;
+
+#[derive(Debug, Clone)]
+struct ScriptedReply {
+    body: String,
+    tool_calls: Vec<ToolCall>,
+}
+
+/// Clones share their script and recorded conversations.
+#[derive(Debug, Clone, Default)]
+pub struct MockGenerativeTextProvider {
+    replies: Arc<Mutex<VecDeque<ScriptedReply>>>,
+    rules: Arc<Mutex<Vec<(String, String)>>>,
+    fallback: Arc<Mutex<Option<String>>>,
+    conversations: Arc<Mutex<Vec<Conversation>>>,
+}
+impl MockGenerativeTextProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Queues a reply for the next call.
+    pub fn with_reply(self, body: impl Into<String>) -> Self {
+        self.with_tool_calls(body, Vec::new())
+    }
+    /// Queues a reply that asks for `tool_calls`.
+    pub fn with_tool_calls(self, body: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        self.replies.lock().unwrap().push_back(ScriptedReply {
+            body: body.into(),
+            tool_calls,
+        });
+        self
+    }
+    /// Answers `body` whenever the latest message contains `pattern` and nothing is queued.
+    pub fn with_rule(self, pattern: impl Into<String>, body: impl Into<String>) -> Self {
+        self.rules
+            .lock()
+            .unwrap()
+            .push((pattern.into(), body.into()));
+        self
+    }
+    /// Answers `body` when nothing else matches, instead of failing.
+    pub fn with_fallback(self, body: impl Into<String>) -> Self {
+        *self.fallback.lock().unwrap() = Some(body.into());
+        self
+    }
+    /// Makes this the provider behind [`crate::generative_text_strategy::WellKnownGenerativeTextStrategy::Mock`]
+    /// for the whole process until the returned installation is dropped.
+    pub fn install(self) -> MockInstallation {
+        mock_registry::install(|mocks| mocks.generative_text = Some(self))
+    }
+    pub fn installed() -> Option<Self> {
+        mock_registry::installed(|mocks| mocks.generative_text.clone())
+    }
+    /// Every conversation received so far, oldest first.
+    pub fn conversations(&self) -> Vec<Conversation> {
+        self.conversations.lock().unwrap().clone()
+    }
+    fn reply(&self, conversation: &Conversation) -> eyre::Result<Answer> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .push(conversation.clone());
+        if let Some(reply) = self.replies.lock().unwrap().pop_front() {
+            return Ok(Answer::new(reply.body)
+                .with_tool_calls(reply.tool_calls)
+                .with_prompt(conversation.prompt.clone()));
+        }
+        let latest = conversation
+            .messages
+            .last()
+            .map(|message| message.content.as_str())
+            .unwrap_or_default();
+        if let Some((_, body)) = self
+            .rules
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(pattern, _)| latest.contains(pattern.as_str()))
+        {
+            return Ok(Answer::new(body.clone()).with_prompt(conversation.prompt.clone()));
+        }
+        match self.fallback.lock().unwrap().as_ref() {
+            Some(body) => Ok(Answer::new(body.clone()).with_prompt(conversation.prompt.clone())),
+            None => bail!("No scripted reply for: {}", latest),
+        }
+    }
+}
+
+#[async_trait]
+impl GenerativeTextProvider for MockGenerativeTextProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+    async fn is_supported(&self, _model: &dyn GenerativeTextModel) -> eyre::Result<bool> {
+        Ok(true)
+    }
+    async fn get_attributes(
+        &self,
+        model: &dyn GenerativeTextModel,
+    ) -> eyre::Result<ModelAttributes> {
+        Ok(ModelAttributes::unmeasured(model.get_context_size()))
+    }
+    fn get_residency(&self) -> Residency {
+        Residency::Local
+    }
+    async fn answer_question(
+        &self,
+        model: &dyn GenerativeTextModel,
+        question: Question,
+    ) -> eyre::Result<Answer> {
+        let images = question.images.clone();
+        let conversation = question.render(Some(model)).await?.to_conversation(images);
+        self.chat(model, &conversation).await
+    }
+    async fn chat(
+        &self,
+        _model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+    ) -> eyre::Result<Answer> {
+        self.reply(conversation)
+    }
+    /// The script is expected to supply JSON; pair with
+    /// [`crate::structured_answer::StructuredAnswerExt`] to exercise validation retries.
+    async fn chat_with_schema(
+        &self,
+        _model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        _schema: &serde_json::Value,
+    ) -> eyre::Result<Answer> {
+        self.reply(conversation)
+    }
+    async fn chat_with_tools(
+        &self,
+        _model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        _tools: &[ToolDefinition],
+    ) -> eyre::Result<Answer> {
+        self.reply(conversation)
+    }
+    async fn format_question(&self, question: Question) -> eyre::Result<String> {
+        Ok(question.render(None).await?.body)
+    }
+}
@@ -0,0 +1,82 @@
+//! Process-wide home of the installed mock providers.
+//!
+//! Mocks are read from whichever thread the provider call lands on, so `multi_thread` runtimes
+//! see them too. Tests in one binary share the registry, so an installation holds it: installs
+//! from other threads wait until every [`MockInstallation`] on the holding thread is dropped,
+//! which also clears the mocks.
+
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::thread::ThreadId;
+
+use crate::providers::mock_embedding_provider::MockEmbeddingProvider;
+use crate::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+
+#[derive(Default)]
+pub(crate) struct InstalledMocks {
+    pub embedding: Option<MockEmbeddingProvider>,
+    pub generative_text: Option<MockGenerativeTextProvider>,
+}
+
+struct Registry {
+    holder: Option<ThreadId>,
+    installations: usize,
+    mocks: InstalledMocks,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    holder: None,
+    installations: 0,
+    mocks: InstalledMocks {
+        embedding: None,
+        generative_text: None,
+    },
+});
+static RELEASED: Condvar = Condvar::new();
+
+fn registry() -> MutexGuard<'static, Registry> {
+    // A test that panics mid-assertion shouldn't fail every test after it.
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Keeps the installed mocks in place; bind it for the length of the test.
+#[must_use = "the mocks are uninstalled as soon as this is dropped"]
+#[derive(Debug)]
+pub struct MockInstallation(());
+impl MockInstallation {
+    /// Holds the registry with nothing new installed, for tests of the uninstalled path.
+    pub fn hold() -> Self {
+        install(|_| {})
+    }
+}
+impl Drop for MockInstallation {
+    fn drop(&mut self) {
+        let mut registry = registry();
+        registry.installations -= 1;
+        if registry.installations == 0 {
+            registry.holder = None;
+            registry.mocks = InstalledMocks::default();
+            RELEASED.notify_all();
+        }
+    }
+}
+
+pub(crate) fn install(update: impl FnOnce(&mut InstalledMocks)) -> MockInstallation {
+    let thread = std::thread::current().id();
+    let mut registry = registry();
+    while registry.holder.is_some_and(|holder| holder != thread) {
+        registry = RELEASED
+            .wait(registry)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+    registry.holder = Some(thread);
+    registry.installations += 1;
+    update(&mut registry.mocks);
+    MockInstallation(())
+}
+
+pub(crate) fn installed<T>(read: impl FnOnce(&InstalledMocks) -> T) -> T {
+    read(&registry().mocks)
+}
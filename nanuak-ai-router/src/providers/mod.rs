@@ -1,2 +1,18 @@
+#[cfg(feature = "fastembed")]
+pub mod fastembed_rerank_provider;
+pub mod http_generate_generative_text_provider;
+pub mod llm_judge_rerank_provider;
+#[cfg(feature = "mock")]
+pub mod mock_embedding_provider;
+#[cfg(feature = "mock")]
+pub mod mock_generative_text_provider;
+#[cfg(feature = "mock")]
+pub mod mock_registry;
+pub mod ollama_chat_api;
+pub mod ollama_embed_api;
 pub mod ollama_embedding_provider;
 pub mod ollama_generative_text_provider;
+pub mod ollama_instance;
+pub mod ollama_models_api;
+#[cfg(feature = "whisper")]
+pub mod whisper_transcription_provider;
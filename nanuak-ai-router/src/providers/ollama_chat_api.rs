@@ -0,0 +1,133 @@
+//! Minimal client for Ollama's `/api/chat` endpoint.
+//!
+//! ollama-rs 0.2 can only send `format: "json"`, but Ollama also accepts a full JSON schema
+//! in that field, so chat requests are built by hand here.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use eyre::Context;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::conversation::Message;
+use crate::conversation::Role;
+use crate::providers::ollama_instance::OllamaOptions;
+use crate::tool::ToolCall;
+use crate::tool::ToolDefinition;
+
+pub const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
+
+/// Honours `OLLAMA_HOST` the same way the Ollama CLI does, falling back to the default port.
+pub fn ollama_url() -> String {
+    match std::env::var("OLLAMA_HOST") {
+        Ok(host) if host.contains("://") => host,
+        Ok(host) if !host.is_empty() => format!("http://{}", host),
+        _ => DEFAULT_OLLAMA_URL.to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ApiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTool {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ToolDefinition,
+}
+
+impl From<&ToolDefinition> for ApiTool {
+    fn from(definition: &ToolDefinition) -> Self {
+        ApiTool {
+            kind: "function".to_string(),
+            function: definition.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToolCall {
+    pub function: ToolCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ApiToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatResponse {
+    pub message: Option<ChatMessage>,
+    #[serde(default)]
+    pub prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    pub eval_count: Option<u32>,
+}
+
+impl ChatMessage {
+    pub async fn from_message(message: &Message) -> eyre::Result<Self> {
+        let role = match message.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        };
+        let mut images = Vec::with_capacity(message.images.len());
+        for path in &message.images {
+            let bytes = tokio::fs::read(path)
+                .await
+                .wrap_err_with(|| format!("Failed to read image: {}", path.display()))?;
+            images.push(STANDARD.encode(bytes));
+        }
+        Ok(ChatMessage {
+            role: role.to_string(),
+            content: message.content.clone(),
+            images,
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(|call| ApiToolCall {
+                    function: call.clone(),
+                })
+                .collect(),
+            tool_name: message.tool_name.clone(),
+        })
+    }
+}
+
+pub async fn send_chat(
+    client: &reqwest::Client,
+    base_url: &str,
+    request: &ChatRequest,
+) -> eyre::Result<ChatResponse> {
+    let url = format!("{}/api/chat", base_url.trim_end_matches('/'));
+    let response = client.post(&url).json(request).send().await?;
+    if let Err(error) = response.error_for_status_ref() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre::Report::new(error)
+            .wrap_err(format!("Ollama chat request to {} failed: {}", url, body)));
+    }
+    response
+        .json()
+        .await
+        .wrap_err("Failed to parse Ollama chat response")
+}
@@ -0,0 +1,42 @@
+//! Minimal client for Ollama's `/api/embed` endpoint.
+
+use eyre::Context;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::providers::ollama_instance::OllamaOptions;
+
+#[derive(Debug, Serialize)]
+pub struct EmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbedResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    #[serde(default)]
+    pub prompt_eval_count: Option<u32>,
+}
+
+pub async fn send_embed(
+    client: &reqwest::Client,
+    base_url: &str,
+    request: &EmbedRequest,
+) -> eyre::Result<EmbedResponse> {
+    let url = format!("{}/api/embed", base_url.trim_end_matches('/'));
+    let response = client.post(&url).json(request).send().await?;
+    if let Err(error) = response.error_for_status_ref() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre::Report::new(error)
+            .wrap_err(format!("Ollama embed request to {} failed: {}", url, body)));
+    }
+    response
+        .json()
+        .await
+        .wrap_err("Failed to parse Ollama embed response")
+}
@@ -1,69 +1,107 @@
 use std::time::Instant;
 
 use crate::attributes::Residency;
+use crate::attributes::VramRequirement;
+use crate::benchmark::measured_attributes;
 use crate::embedding::Embedding;
 use crate::embedding_model::EmbeddingModel;
 use crate::embedding_provider::EmbeddingProvider;
 use crate::embedding_request::EmbeddingPayload;
 use crate::model_attributes::ModelAttributes;
+use crate::providers::ollama_embed_api::EmbedRequest;
+use crate::providers::ollama_embed_api::send_embed;
+use crate::providers::ollama_instance::OllamaInstance;
+use crate::providers::ollama_models_api::has_model;
+use crate::providers::ollama_models_api::loaded_vram;
+use crate::telemetry::CallModality;
+use crate::telemetry::CallRecord;
+use crate::telemetry::record;
 use async_trait::async_trait;
-use ollama_rs::Ollama;
-use ollama_rs::generation::embeddings::request::EmbeddingsInput;
-use ollama_rs::generation::embeddings::request::GenerateEmbeddingsRequest;
-use tracing::debug;
+use eyre::bail;
 
-pub struct OllamaEmbeddingProvider;
+#[derive(Default)]
+pub struct OllamaEmbeddingProvider {
+    pub instance: OllamaInstance,
+}
+impl OllamaEmbeddingProvider {
+    pub fn new(instance: OllamaInstance) -> Self {
+        OllamaEmbeddingProvider { instance }
+    }
+}
 #[async_trait]
 impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn name(&self) -> &str {
+        &self.instance.name
+    }
     async fn is_supported(&self, model: &dyn EmbeddingModel) -> eyre::Result<bool> {
-        todo!()
+        has_model(
+            self.instance.client(),
+            &self.instance.base_url,
+            model.name(),
+        )
+        .await
     }
     async fn get_embeddings(
         &self,
         model: &dyn EmbeddingModel,
         payloads: Vec<EmbeddingPayload>,
     ) -> eyre::Result<Vec<Embedding>> {
-        let mut rtn = Vec::with_capacity(payloads.len());
-        let mut string_payloads: Vec<String> = Vec::new();
+        let mut string_payloads: Vec<String> = Vec::with_capacity(payloads.len());
         for payload in payloads {
             match payload {
                 EmbeddingPayload::Text(text) => {
                     string_payloads.push(text);
                 }
-                EmbeddingPayload::ImagePath(_image) => {
-                    todo!()
+                EmbeddingPayload::ImagePath(image) => {
+                    bail!(
+                        "Ollama can't embed images, got {} for {}",
+                        image.display(),
+                        model.name()
+                    );
                 }
             }
         }
 
-        let request = GenerateEmbeddingsRequest::new(
-            model.name().to_string(),
-            EmbeddingsInput::Multiple(string_payloads),
-        );
+        let request = EmbedRequest {
+            model: model.name().to_string(),
+            input: string_payloads,
+            keep_alive: self.instance.keep_alive.clone(),
+            options: self.instance.options(),
+        };
         let start = Instant::now();
-        let response = Ollama::default().generate_embeddings(request).await?;
-        let elapsed = start.elapsed();
-        debug!(
-            "Embedding generation size {} with model {} took {:?}",
-            response.embeddings.len(),
+        let result = send_embed(self.instance.client(), &self.instance.base_url, &request).await;
+        let call = CallRecord::new(
+            self.name(),
             model.name(),
-            elapsed
+            CallModality::Embedding,
+            start.elapsed(),
         );
-        for embedding in response.embeddings {
-            rtn.push(Embedding(embedding));
-        }
-        Ok(rtn)
+        let response = match result {
+            Ok(response) => {
+                record(call.with_tokens(response.prompt_eval_count, None));
+                response
+            }
+            Err(error) => {
+                record(call.with_error(&error));
+                return Err(error);
+            }
+        };
+        Ok(response.embeddings.into_iter().map(Embedding).collect())
     }
     async fn get_attributes(&self, model: &dyn EmbeddingModel) -> eyre::Result<ModelAttributes> {
-        Ok(ModelAttributes {
-            vram_requirement: None,
-            latency: None,
-            accuracy: None,
-            throughput: None,
-            context_size: model.get_context_size(),
-        })
+        let mut attributes = ModelAttributes::unmeasured(model.get_context_size());
+        if let Some(vram) = loaded_vram(
+            self.instance.client(),
+            &self.instance.base_url,
+            model.name(),
+        )
+        .await
+        {
+            attributes.vram_requirement = Some(VramRequirement(vram));
+        }
+        Ok(measured_attributes(self.name(), model.name(), attributes).await)
     }
     fn get_residency(&self) -> Residency {
-        Residency::Local
+        self.instance.residency
     }
 }
@@ -1,81 +1,175 @@
 use crate::answer::Answer;
 use crate::attributes::Residency;
+use crate::attributes::VramRequirement;
+use crate::benchmark::measured_attributes;
+use crate::conversation::Conversation;
 use crate::generative_text_model::GenerativeTextModel;
 use crate::generative_text_provider::GenerativeTextProvider;
 use crate::model_attributes::ModelAttributes;
+use crate::providers::ollama_chat_api::ApiTool;
+use crate::providers::ollama_chat_api::ChatMessage;
+use crate::providers::ollama_chat_api::ChatRequest;
+use crate::providers::ollama_chat_api::send_chat;
+use crate::providers::ollama_instance::OllamaInstance;
+use crate::providers::ollama_models_api::has_model;
+use crate::providers::ollama_models_api::loaded_vram;
 use crate::question::Question;
+use crate::telemetry::CallModality;
+use crate::telemetry::CallRecord;
+use crate::telemetry::record;
+use crate::tool::ToolDefinition;
 use async_trait::async_trait;
 use eyre::bail;
-use ollama_rs::Ollama;
-use ollama_rs::generation::chat::ChatMessage;
-use ollama_rs::generation::chat::request::ChatMessageRequest;
 use std::time::Instant;
-use tracing::debug;
 
-pub struct OllamaGenerativeTextProvider;
+#[derive(Default)]
+pub struct OllamaGenerativeTextProvider {
+    pub instance: OllamaInstance,
+}
+impl OllamaGenerativeTextProvider {
+    pub fn new(instance: OllamaInstance) -> Self {
+        OllamaGenerativeTextProvider { instance }
+    }
+}
 #[async_trait]
 impl GenerativeTextProvider for OllamaGenerativeTextProvider {
+    fn name(&self) -> &str {
+        &self.instance.name
+    }
     async fn is_supported(&self, model: &dyn GenerativeTextModel) -> eyre::Result<bool> {
-        todo!()
+        has_model(
+            self.instance.client(),
+            &self.instance.base_url,
+            model.name(),
+        )
+        .await
     }
     async fn get_attributes(
         &self,
         model: &dyn GenerativeTextModel,
     ) -> eyre::Result<ModelAttributes> {
-        Ok(ModelAttributes {
-            vram_requirement: None,
-            latency: None,
-            accuracy: None,
-            throughput: None,
-            context_size: model.get_context_size(),
-        })
+        let mut attributes = ModelAttributes::unmeasured(model.get_context_size());
+        if let Some(vram) = loaded_vram(
+            self.instance.client(),
+            &self.instance.base_url,
+            model.name(),
+        )
+        .await
+        {
+            attributes.vram_requirement = Some(VramRequirement(vram));
+        }
+        Ok(measured_attributes(self.name(), model.name(), attributes).await)
     }
     fn get_residency(&self) -> Residency {
-        Residency::Local
+        self.instance.residency
     }
     async fn answer_question(
         &self,
         model: &dyn GenerativeTextModel,
         question: Question,
     ) -> eyre::Result<Answer> {
-        let ollama = Ollama::default();
-        let mut messages: Vec<ChatMessage> = Vec::new();
-        let user_message = ChatMessage::user(self.format_question(question).await?);
-        messages.push(user_message);
-        let request = ChatMessageRequest::new(model.name().to_string(), messages);
+        let images = question.images.clone();
+        let conversation = question.render(Some(model)).await?.to_conversation(images);
+        self.chat(model, &conversation).await
+    }
+    async fn chat(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+    ) -> eyre::Result<Answer> {
+        self.send(model, conversation, None, &[]).await
+    }
+    async fn chat_with_schema(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        schema: &serde_json::Value,
+    ) -> eyre::Result<Answer> {
+        self.send(model, conversation, Some(schema.clone()), &[])
+            .await
+    }
+    async fn chat_with_tools(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        tools: &[ToolDefinition],
+    ) -> eyre::Result<Answer> {
+        self.send(model, conversation, None, tools).await
+    }
+    async fn format_question(&self, question: Question) -> eyre::Result<String> {
+        Ok(question.render(None).await?.body)
+    }
+}
+impl OllamaGenerativeTextProvider {
+    async fn send(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        format: Option<serde_json::Value>,
+        tools: &[ToolDefinition],
+    ) -> eyre::Result<Answer> {
+        if conversation.has_images() && !model.accepts_images() {
+            bail!("Model {} does not accept images", model.name());
+        }
+        let mut messages = Vec::with_capacity(conversation.messages.len());
+        for message in &conversation.messages {
+            messages.push(ChatMessage::from_message(message).await?);
+        }
+        let request = ChatRequest {
+            model: model.name().to_string(),
+            messages,
+            stream: false,
+            format,
+            tools: tools.iter().map(ApiTool::from).collect(),
+            keep_alive: self.instance.keep_alive.clone(),
+            options: self.instance.options(),
+        };
         let start = Instant::now();
-        let response = ollama.send_chat_messages(request).await?;
-        let elapsed = start.elapsed();
-        debug!(
-            "Answering question with model {} took {:?}",
+        let result = send_chat(self.instance.client(), &self.instance.base_url, &request).await;
+        let call = CallRecord::new(
+            self.name(),
             model.name(),
-            elapsed
-        );
+            CallModality::GenerativeText,
+            start.elapsed(),
+        )
+        .with_prompt(conversation.prompt.as_ref());
+        let response = match result {
+            Ok(response) => {
+                record(call.with_tokens(response.prompt_eval_count, response.eval_count));
+                response
+            }
+            Err(error) => {
+                record(call.with_error(&error));
+                return Err(error);
+            }
+        };
         let Some(answer) = response.message else {
             bail!("No answer found in response");
         };
-        Ok(Answer::new(answer.content))
-    }
-    async fn format_question(&self, question: Question) -> eyre::Result<String> {
-        let mut text = String::new();
-        for (i, context) in question.context.iter().enumerate() {
-            text.push_str(&format!("<context{}>\n{}\n</context{}>\n", i, context, i));
-        }
-        text.push_str(&format!("<question>\n{}\n</question>", question.text));
-        Ok(text)
+        let tool_calls = answer
+            .tool_calls
+            .into_iter()
+            .map(|call| call.function)
+            .collect();
+        Ok(Answer::new(answer.content)
+            .with_tool_calls(tool_calls)
+            .with_prompt(conversation.prompt.clone()))
     }
 }
+
 #[cfg(test)]
 mod test {
     use crate::generative_text_provider::GenerativeTextProvider;
     use crate::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
 
     #[tokio::test]
+    #[ignore = "needs a local Ollama with gemma2:2b"]
     async fn it_works() -> eyre::Result<()> {
-        let provider = super::OllamaGenerativeTextProvider;
+        let provider = super::OllamaGenerativeTextProvider::default();
         let model = Gemma2_2BGenerativeTextModel;
         let question = super::Question {
             context: vec![],
+            images: vec![],
             text: "Why is the sky blue?".to_string(),
         };
         let answer = provider.answer_question(&model, question).await?;
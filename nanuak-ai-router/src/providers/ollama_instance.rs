@@ -0,0 +1,79 @@
+//! Connection settings for one Ollama server.
+
+use eyre::Context;
+use nanuak_config::provider_instances::ProviderInstance;
+use reqwest::header::AUTHORIZATION;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderValue;
+use serde::Serialize;
+
+use crate::attributes::Residency;
+use crate::providers::ollama_chat_api::ollama_url;
+
+#[derive(Debug, Clone)]
+pub struct OllamaInstance {
+    pub name: String,
+    pub base_url: String,
+    pub keep_alive: Option<String>,
+    pub num_ctx: Option<u32>,
+    pub residency: Residency,
+    client: reqwest::Client,
+}
+impl Default for OllamaInstance {
+    fn default() -> Self {
+        OllamaInstance::new("ollama", ollama_url())
+    }
+}
+impl OllamaInstance {
+    pub fn new(name: impl Into<String>, base_url: impl Into<String>) -> Self {
+        OllamaInstance {
+            name: name.into(),
+            base_url: base_url.into(),
+            keep_alive: None,
+            num_ctx: None,
+            residency: Residency::Local,
+            client: reqwest::Client::new(),
+        }
+    }
+    pub fn from_config(instance: &ProviderInstance) -> eyre::Result<Self> {
+        let mut ollama = OllamaInstance::new(&instance.name, instance.base_url());
+        ollama.keep_alive = instance.keep_alive.clone();
+        ollama.num_ctx = instance.num_ctx;
+        if instance.remote {
+            ollama.residency = Residency::RemoteAnywhere;
+        }
+        if let Some(auth_header) = &instance.auth_header {
+            ollama = ollama
+                .with_auth_header(auth_header)
+                .wrap_err_with(|| format!("Invalid auth header for provider {}", instance.name))?;
+        }
+        Ok(ollama)
+    }
+    /// Sends `auth_header` as the `Authorization` header on every request, for servers
+    /// behind a reverse proxy.
+    pub fn with_auth_header(mut self, auth_header: &str) -> eyre::Result<Self> {
+        let mut value = HeaderValue::from_str(auth_header)?;
+        value.set_sensitive(true);
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, value);
+        self.client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+        Ok(self)
+    }
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+    /// Per-request `options`, or `None` when nothing overrides the server's defaults.
+    pub fn options(&self) -> Option<OllamaOptions> {
+        self.num_ctx.map(|num_ctx| OllamaOptions {
+            num_ctx: Some(num_ctx),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+}
@@ -0,0 +1,99 @@
+//! Model listing endpoints: `/api/tags` for pulled models and `/api/ps` for loaded ones.
+
+use eyre::Context;
+use serde::Deserialize;
+use tracing::debug;
+use uom::si::f32::Information;
+use uom::si::information::byte;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunningModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub size_vram: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList<T> {
+    models: Vec<T>,
+}
+
+/// Ollama implies `:latest` when a model is referenced without a tag.
+pub fn normalize_model_name(name: &str) -> String {
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("{}:latest", name)
+    }
+}
+
+pub async fn list_local_models(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> eyre::Result<Vec<LocalModel>> {
+    get_models(client, base_url, "tags").await
+}
+
+pub async fn list_running_models(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> eyre::Result<Vec<RunningModel>> {
+    get_models(client, base_url, "ps").await
+}
+
+pub async fn has_model(
+    client: &reqwest::Client,
+    base_url: &str,
+    model: &str,
+) -> eyre::Result<bool> {
+    let wanted = normalize_model_name(model);
+    Ok(list_local_models(client, base_url)
+        .await?
+        .iter()
+        .any(|local| normalize_model_name(&local.name) == wanted))
+}
+
+/// VRAM used by `model` if it is currently loaded; errors are logged and treated as unknown.
+pub async fn loaded_vram(
+    client: &reqwest::Client,
+    base_url: &str,
+    model: &str,
+) -> Option<Information> {
+    let wanted = normalize_model_name(model);
+    match list_running_models(client, base_url).await {
+        Ok(running) => running
+            .iter()
+            .find(|loaded| normalize_model_name(&loaded.name) == wanted)
+            .map(|loaded| Information::new::<byte>(loaded.size_vram as f32)),
+        Err(error) => {
+            debug!("Failed to list running Ollama models: {:?}", error);
+            None
+        }
+    }
+}
+
+async fn get_models<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    base_url: &str,
+    endpoint: &str,
+) -> eyre::Result<Vec<T>> {
+    let url = format!("{}/api/{}", base_url.trim_end_matches('/'), endpoint);
+    let list: ModelList<T> = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .wrap_err_with(|| format!("Failed to parse Ollama model list from {}", url))?;
+    Ok(list.models)
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use eyre::Context;
+use eyre::bail;
+use nanuak_config::dirs::get_project_dirs;
+use whisper_rs::FullParams;
+use whisper_rs::SamplingStrategy;
+use whisper_rs::WhisperContext;
+use whisper_rs::WhisperContextParameters;
+
+use crate::attributes::Residency;
+use crate::telemetry::CallModality;
+use crate::telemetry::CallRecord;
+use crate::telemetry::record;
+use crate::transcript::Transcript;
+use crate::transcript::TranscriptSegment;
+use crate::transcription_model::TranscriptionModel;
+use crate::transcription_provider::TranscriptionProvider;
+use crate::transcription_request::TranscriptionRequest;
+
+/// Whisper runs on 16 kHz mono audio.
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// whisper.cpp on the CPU, loading ggml model files from `models_dir`.
+pub struct WhisperTranscriptionProvider {
+    pub models_dir: PathBuf,
+    contexts: Mutex<HashMap<PathBuf, Arc<WhisperContext>>>,
+}
+impl WhisperTranscriptionProvider {
+    pub fn new(models_dir: PathBuf) -> Self {
+        WhisperTranscriptionProvider {
+            models_dir,
+            contexts: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Looks for model files in the `whisper` folder of the project data dir.
+    pub fn from_project_dirs() -> eyre::Result<Self> {
+        Ok(Self::new(get_project_dirs()?.data_dir().join("whisper")))
+    }
+    pub fn model_path(&self, model: &dyn TranscriptionModel) -> PathBuf {
+        self.models_dir.join(model.file_name())
+    }
+    /// Loading weights is slow, so it runs on the blocking pool and contexts are kept for the
+    /// life of the provider.
+    async fn context(&self, path: &Path) -> eyre::Result<Arc<WhisperContext>> {
+        if let Some(context) = self.contexts.lock().unwrap().get(path) {
+            return Ok(context.clone());
+        }
+        let Some(path_str) = path.to_str().map(str::to_string) else {
+            bail!("Model path is not valid UTF-8: {}", path.display());
+        };
+        let context = tokio::task::spawn_blocking(move || {
+            WhisperContext::new_with_params(&path_str, WhisperContextParameters::default())
+        })
+        .await?
+        .wrap_err_with(|| format!("Failed to load whisper model: {}", path.display()))?;
+        let mut contexts = self.contexts.lock().unwrap();
+        Ok(contexts
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Arc::new(context))
+            .clone())
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for WhisperTranscriptionProvider {
+    fn name(&self) -> &str {
+        "whisper-cpp"
+    }
+    async fn is_supported(&self, model: &dyn TranscriptionModel) -> eyre::Result<bool> {
+        Ok(tokio::fs::try_exists(self.model_path(model)).await?)
+    }
+    async fn transcribe(
+        &self,
+        model: &dyn TranscriptionModel,
+        request: TranscriptionRequest,
+    ) -> eyre::Result<Transcript> {
+        let model_path = self.model_path(model);
+        if !tokio::fs::try_exists(&model_path).await? {
+            bail!(
+                "Whisper model {} not found at {}",
+                model.name(),
+                model_path.display()
+            );
+        }
+        let context = self.context(&model_path).await?;
+        let start = Instant::now();
+        let result = tokio::task::spawn_blocking(move || run_whisper(&context, &request)).await?;
+        let call = CallRecord::new(
+            self.name(),
+            model.name(),
+            CallModality::Transcription,
+            start.elapsed(),
+        );
+        match result {
+            Ok(transcript) => {
+                record(call);
+                Ok(transcript)
+            }
+            Err(error) => {
+                record(call.with_error(&error));
+                Err(error)
+            }
+        }
+    }
+    fn get_residency(&self) -> Residency {
+        Residency::Local
+    }
+}
+
+fn run_whisper(
+    context: &WhisperContext,
+    request: &TranscriptionRequest,
+) -> eyre::Result<Transcript> {
+    let samples = read_wav(&request.audio)?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(request.language.as_deref().unwrap_or("auto")));
+    params.set_translate(request.translate);
+    params.set_n_threads(
+        std::thread::available_parallelism()
+            .map(|threads| threads.get() as i32)
+            .unwrap_or(4),
+    );
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    let mut state = context.create_state()?;
+    state.full(params, &samples)?;
+    let mut segments = Vec::new();
+    for i in 0..state.full_n_segments()? {
+        // whisper.cpp reports timestamps in centiseconds.
+        segments.push(TranscriptSegment {
+            start: Duration::from_millis(state.full_get_segment_t0(i)?.max(0) as u64 * 10),
+            end: Duration::from_millis(state.full_get_segment_t1(i)?.max(0) as u64 * 10),
+            text: state.full_get_segment_text(i)?,
+        });
+    }
+    Ok(Transcript { segments })
+}
+
+/// Reads a 16 kHz WAV file as mono f32 samples, averaging channels.
+fn read_wav(path: &Path) -> eyre::Result<Vec<f32>> {
+    let mut reader = hound::WavReader::open(path)
+        .wrap_err_with(|| format!("Failed to open WAV file: {}", path.display()))?;
+    let spec = reader.spec();
+    if spec.sample_rate != WHISPER_SAMPLE_RATE {
+        bail!(
+            "{} is {} Hz but whisper needs {} Hz, convert it with `ffmpeg -i <input> -ar 16000 -ac 1 <output>.wav`",
+            path.display(),
+            spec.sample_rate,
+            WHISPER_SAMPLE_RATE
+        );
+    }
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    let channels = spec.channels.max(1) as usize;
+    Ok(interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect())
+}
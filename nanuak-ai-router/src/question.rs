@@ -1,6 +1,14 @@
+use std::path::PathBuf;
+
+use crate::generative_text_model::GenerativeTextModel;
+use crate::prompt_registry::PromptRegistry;
+use crate::prompt_template::PromptVariables;
+use crate::prompt_template::RenderedPrompt;
+
 pub struct Question {
     pub text: String,
     pub context: Vec<String>,
+    pub images: Vec<PathBuf>,
 }
 
 impl Question {
@@ -8,10 +16,27 @@ impl Question {
         Question {
             text: question,
             context: Vec::new(),
+            images: Vec::new(),
         }
     }
     pub fn with_context(mut self, context: Vec<String>) -> Self {
         self.context = context;
         self
     }
+    pub fn with_images(mut self, images: Vec<PathBuf>) -> Self {
+        self.images = images;
+        self
+    }
+    /// Renders the `question` template, using `model`'s variant when given.
+    pub async fn render(
+        self,
+        model: Option<&dyn GenerativeTextModel>,
+    ) -> eyre::Result<RenderedPrompt> {
+        PromptRegistry::global().await?.get("question")?.render(
+            model.map(|model| model.name()).unwrap_or_default(),
+            &PromptVariables::new()
+                .with("question", self.text)
+                .with("context", self.context),
+        )
+    }
 }
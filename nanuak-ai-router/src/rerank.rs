@@ -0,0 +1,129 @@
+use eyre::bail;
+use tracing::warn;
+
+use crate::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use crate::providers::llm_judge_rerank_provider::LlmJudgeRerankProvider;
+use crate::rerank_provider::RerankProvider;
+
+#[derive(Debug, Clone)]
+pub struct Reranked<T> {
+    pub item: T,
+    /// `None` for candidates past the top-N, which keep their original order.
+    pub score: Option<f32>,
+    /// Position in the list handed to [`rerank_top_n`].
+    pub original_rank: usize,
+}
+
+/// Reorders the first `top_n` candidates of a search by reranker score.
+///
+/// `text` renders each candidate as the document the reranker sees, e.g. a video's title
+/// and description.
+pub async fn rerank_top_n<T>(
+    provider: &dyn RerankProvider,
+    query: &str,
+    candidates: Vec<T>,
+    top_n: usize,
+    text: impl Fn(&T) -> String,
+) -> eyre::Result<Vec<Reranked<T>>> {
+    let top_n = top_n.min(candidates.len());
+    let documents: Vec<String> = candidates[..top_n].iter().map(&text).collect();
+    let scores = if documents.is_empty() {
+        Vec::new()
+    } else {
+        provider.score(query, &documents).await?
+    };
+    if scores.len() != documents.len() {
+        bail!(
+            "Reranker {} returned {} scores for {} documents",
+            provider.name(),
+            scores.len(),
+            documents.len()
+        );
+    }
+    let mut reranked: Vec<Reranked<T>> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(original_rank, item)| Reranked {
+            item,
+            score: scores.get(original_rank).copied(),
+            original_rank,
+        })
+        .collect();
+    // Stable, so ties keep the search's order.
+    reranked[..top_n].sort_by(|a, b| b.score.unwrap().total_cmp(&a.score.unwrap()));
+    Ok(reranked)
+}
+
+/// The local cross-encoder when built with the `fastembed` feature and it loads, otherwise
+/// an LLM judging relevance with the best local chat model.
+pub async fn get_rerank_provider() -> eyre::Result<Box<dyn RerankProvider>> {
+    #[cfg(feature = "fastembed")]
+    {
+        use crate::providers::fastembed_rerank_provider::FastembedRerankProvider;
+        match FastembedRerankProvider::from_project_dirs() {
+            Ok(provider) => match provider.load().await {
+                Ok(()) => return Ok(Box::new(provider)),
+                Err(error) => warn!(
+                    "Failed to load cross-encoder, falling back to LLM judge: {:?}",
+                    error
+                ),
+            },
+            Err(error) => warn!("No cross-encoder available: {:?}", error),
+        }
+    }
+    #[cfg(not(feature = "fastembed"))]
+    warn!("Built without the fastembed feature, reranking with an LLM judge");
+    Ok(Box::new(
+        LlmJudgeRerankProvider::from_strategy(WellKnownGenerativeTextStrategy::Fast).await?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+
+    use super::rerank_top_n;
+    use crate::attributes::Residency;
+    use crate::rerank_provider::RerankProvider;
+
+    /// Scores by how many query words appear in the document.
+    struct OverlapReranker;
+    #[async_trait]
+    impl RerankProvider for OverlapReranker {
+        fn name(&self) -> &str {
+            "overlap"
+        }
+        async fn score(&self, query: &str, documents: &[String]) -> eyre::Result<Vec<f32>> {
+            Ok(documents
+                .iter()
+                .map(|document| {
+                    query
+                        .split_whitespace()
+                        .filter(|word| document.contains(word))
+                        .count() as f32
+                })
+                .collect())
+        }
+        fn get_residency(&self) -> Residency {
+            Residency::Local
+        }
+    }
+
+    #[tokio::test]
+    async fn reorders_only_the_top_n() -> eyre::Result<()> {
+        let candidates = vec!["cats", "rust borrow checker", "rust", "borrow checker rust"];
+        let reranked = rerank_top_n(
+            &OverlapReranker,
+            "rust borrow checker",
+            candidates,
+            3,
+            |c| c.to_string(),
+        )
+        .await?;
+        let order: Vec<usize> = reranked.iter().map(|r| r.original_rank).collect();
+        assert_eq!(order, vec![1, 2, 0, 3]);
+        assert_eq!(reranked[0].score, Some(3.0));
+        assert_eq!(reranked[3].score, None);
+        Ok(())
+    }
+}
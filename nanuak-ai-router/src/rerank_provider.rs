@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::attributes::Residency;
+
+/// Scores how relevant each document is to a query, looking at both together rather than
+/// comparing separately computed embeddings.
+#[async_trait]
+pub trait RerankProvider: Send + Sync {
+    fn name(&self) -> &str;
+    /// One score per document, in the same order; higher means more relevant.
+    async fn score(&self, query: &str, documents: &[String]) -> eyre::Result<Vec<f32>>;
+    fn get_residency(&self) -> Residency;
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use eyre::bail;
+use nanuak_config::resilience_policies::ResiliencePolicyOverride;
+use reqwest::StatusCode;
+use tokio::sync::Semaphore;
+use tracing::debug;
+use tracing::warn;
+
+/// How calls to a provider are bounded, retried and short-circuited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResiliencePolicy {
+    /// Upper bound on a single attempt.
+    pub timeout: Duration,
+    /// Total attempts per call, including the first.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles on each subsequent retry.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// How many calls may be in flight against the provider at once.
+    pub max_concurrency: usize,
+    /// Minimum spacing between attempts, for providers with rate limits.
+    pub min_interval: Option<Duration>,
+    /// Consecutive failed calls before the circuit opens.
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects calls before letting one through again.
+    pub cooldown: Duration,
+}
+
+impl Default for ResiliencePolicy {
+    fn default() -> Self {
+        ResiliencePolicy {
+            timeout: Duration::from_secs(120),
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            max_concurrency: 4,
+            min_interval: None,
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ResiliencePolicy {
+    /// This policy with every field set in `config` replaced.
+    pub fn with_override(self, config: &ResiliencePolicyOverride) -> Self {
+        let millis = Duration::from_millis;
+        ResiliencePolicy {
+            timeout: config.timeout_ms.map(millis).unwrap_or(self.timeout),
+            max_attempts: config.max_attempts.unwrap_or(self.max_attempts),
+            initial_backoff: config
+                .initial_backoff_ms
+                .map(millis)
+                .unwrap_or(self.initial_backoff),
+            max_backoff: config
+                .max_backoff_ms
+                .map(millis)
+                .unwrap_or(self.max_backoff),
+            max_concurrency: config.max_concurrency.unwrap_or(self.max_concurrency),
+            min_interval: config.min_interval_ms.map(millis).or(self.min_interval),
+            failure_threshold: config.failure_threshold.unwrap_or(self.failure_threshold),
+            cooldown: config.cooldown_ms.map(millis).unwrap_or(self.cooldown),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TimedOut(pub Duration);
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out after {:?}", self.0)
+    }
+}
+impl std::error::Error for TimedOut {}
+
+/// Whether an error is worth retrying: timeouts, connection failures, 429s and 5xxs.
+pub fn is_transient(error: &eyre::Report) -> bool {
+    error.chain().any(|cause| {
+        if cause.is::<TimedOut>() {
+            return true;
+        }
+        if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+            return is_transient_io(error.kind());
+        }
+        if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+            return error.is_timeout()
+                || error.is_connect()
+                || error.status().is_some_and(|status| {
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                });
+        }
+        false
+    })
+}
+
+/// Missing files, bad permissions and malformed data fail the same way on every attempt.
+fn is_transient_io(kind: std::io::ErrorKind) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        kind,
+        ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Shared per-provider state enforcing a [`ResiliencePolicy`].
+#[derive(Debug)]
+pub struct ProviderGuard {
+    name: String,
+    policy: Mutex<ResiliencePolicy>,
+    permits: Semaphore,
+    /// Permits still to be retired after lowering `max_concurrency` while calls were in flight.
+    excess_permits: Mutex<usize>,
+    next_slot: Mutex<Instant>,
+    breaker: Mutex<Breaker>,
+}
+
+static GUARDS: LazyLock<Mutex<HashMap<String, Arc<ProviderGuard>>>> =
+    LazyLock::new(Default::default);
+
+impl ProviderGuard {
+    pub fn new(name: String, policy: ResiliencePolicy) -> Self {
+        ProviderGuard {
+            name,
+            permits: Semaphore::new(policy.max_concurrency.max(1)),
+            next_slot: Mutex::new(Instant::now()),
+            excess_permits: Mutex::new(0),
+            breaker: Mutex::new(Breaker::default()),
+            policy: Mutex::new(policy),
+        }
+    }
+
+    /// Returns the process-wide guard for `name`, creating it if needed.
+    ///
+    /// An existing guard asked for with a different policy adopts it in place, keeping its
+    /// circuit state and in-flight calls.
+    pub fn acquire(name: &str, policy: &ResiliencePolicy) -> Arc<ProviderGuard> {
+        let mut guards = GUARDS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match guards.get(name) {
+            Some(guard) => {
+                guard.set_policy(policy);
+                guard.clone()
+            }
+            None => {
+                let guard = Arc::new(ProviderGuard::new(name.to_string(), policy.clone()));
+                guards.insert(name.to_string(), guard.clone());
+                guard
+            }
+        }
+    }
+
+    pub fn policy(&self) -> ResiliencePolicy {
+        self.policy
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Switches to `policy`; lowered concurrency takes effect as in-flight calls finish.
+    pub fn set_policy(&self, policy: &ResiliencePolicy) {
+        let mut current = self.policy.lock().unwrap_or_else(|p| p.into_inner());
+        if *current == *policy {
+            return;
+        }
+        let from = current.max_concurrency.max(1);
+        let to = policy.max_concurrency.max(1);
+        let mut excess = self
+            .excess_permits
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        if to > from {
+            let cancelled = (*excess).min(to - from);
+            *excess -= cancelled;
+            self.permits.add_permits(to - from - cancelled);
+        } else if from > to {
+            let forgotten = self.permits.forget_permits(from - to);
+            *excess += from - to - forgotten;
+        }
+        *current = policy.clone();
+    }
+
+    pub fn is_open(&self) -> bool {
+        let breaker = self.breaker.lock().unwrap_or_else(|p| p.into_inner());
+        breaker
+            .open_until
+            .is_some_and(|open_until| Instant::now() < open_until)
+    }
+
+    /// Runs `operation` under the policy, retrying transient failures with exponential backoff.
+    pub async fn call<T, F, Fut>(&self, mut operation: F) -> eyre::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = eyre::Result<T>>,
+    {
+        if self.is_open() {
+            bail!("Circuit open for provider {}, skipping call", self.name);
+        }
+        let _permit = loop {
+            let permit = self.permits.acquire().await?;
+            let mut excess = self
+                .excess_permits
+                .lock()
+                .unwrap_or_else(|p| p.into_inner());
+            if *excess == 0 {
+                break permit;
+            }
+            *excess -= 1;
+            permit.forget();
+        };
+        let policy = self.policy();
+        let mut backoff = policy.initial_backoff;
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            self.wait_for_slot(policy.min_interval).await;
+            let result = match tokio::time::timeout(policy.timeout, operation()).await {
+                Ok(result) => result,
+                Err(_) => Err(eyre::Report::new(TimedOut(policy.timeout))),
+            };
+            match result {
+                Ok(value) => {
+                    self.record_success();
+                    return Ok(value);
+                }
+                Err(error) if attempt < max_attempts && is_transient(&error) => {
+                    warn!(
+                        "Attempt {}/{} against {} failed, retrying in {:?}: {}",
+                        attempt, max_attempts, self.name, backoff, error
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(policy.max_backoff);
+                    attempt += 1;
+                }
+                Err(error) => {
+                    self.record_failure(&policy);
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    async fn wait_for_slot(&self, min_interval: Option<Duration>) {
+        let Some(interval) = min_interval else {
+            return;
+        };
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|p| p.into_inner());
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|p| p.into_inner());
+        *breaker = Breaker::default();
+    }
+
+    fn record_failure(&self, policy: &ResiliencePolicy) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|p| p.into_inner());
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= policy.failure_threshold {
+            debug!(
+                "Opening circuit for {} after {} consecutive failures",
+                self.name, breaker.consecutive_failures
+            );
+            breaker.open_until = Some(Instant::now() + policy.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use nanuak_config::resilience_policies::ResiliencePolicyOverride;
+
+    use super::ProviderGuard;
+    use super::ResiliencePolicy;
+    use super::is_transient;
+
+    fn fast_policy() -> ResiliencePolicy {
+        ResiliencePolicy {
+            timeout: Duration::from_millis(50),
+            initial_backoff: Duration::from_millis(1),
+            failure_threshold: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_then_opens_circuit() -> eyre::Result<()> {
+        let guard = ProviderGuard::new("test".to_string(), fast_policy());
+        let calls = AtomicU32::new(0);
+        let result = guard
+            .call(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(!guard.is_open());
+
+        // Non-transient errors are not retried, and the second failure trips the breaker.
+        let result: eyre::Result<()> = guard
+            .call(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                eyre::bail!("bad request")
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(guard.is_open());
+        assert!(guard.call(|| async { Ok(()) }).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn new_policy_keeps_the_circuit_open() -> eyre::Result<()> {
+        let guard = ProviderGuard::acquire("policy-change", &fast_policy());
+        for _ in 0..2 {
+            let result: eyre::Result<()> =
+                guard.call(|| async { eyre::bail!("bad request") }).await;
+            assert!(result.is_err());
+        }
+        assert!(guard.is_open());
+
+        let policy = ResiliencePolicy {
+            max_attempts: 1,
+            max_concurrency: 1,
+            ..fast_policy()
+        };
+        let same = ProviderGuard::acquire("policy-change", &policy);
+        assert!(Arc::ptr_eq(&guard, &same));
+        assert!(same.is_open());
+        assert_eq!(same.policy(), policy);
+        Ok(())
+    }
+
+    #[test]
+    fn changing_concurrency_resizes_permits() {
+        let guard = ProviderGuard::new("concurrency".to_string(), fast_policy());
+        guard.set_policy(&ResiliencePolicy {
+            max_concurrency: 1,
+            ..fast_policy()
+        });
+        assert_eq!(guard.permits.available_permits(), 1);
+        guard.set_policy(&ResiliencePolicy {
+            max_concurrency: 3,
+            ..fast_policy()
+        });
+        assert_eq!(guard.permits.available_permits(), 3);
+    }
+
+    #[test]
+    fn classifies_io_errors_by_kind() {
+        let io = |kind| eyre::Report::new(std::io::Error::from(kind));
+        assert!(is_transient(&io(std::io::ErrorKind::ConnectionRefused)));
+        assert!(is_transient(&io(std::io::ErrorKind::TimedOut)));
+        assert!(!is_transient(&io(std::io::ErrorKind::NotFound)));
+        assert!(!is_transient(&io(std::io::ErrorKind::PermissionDenied)));
+        assert!(!is_transient(&io(std::io::ErrorKind::InvalidData)));
+    }
+
+    #[test]
+    fn config_overrides_only_the_fields_set() {
+        let policy = ResiliencePolicy::default().with_override(&ResiliencePolicyOverride {
+            timeout_ms: Some(1500),
+            max_attempts: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(policy.timeout, Duration::from_millis(1500));
+        assert_eq!(policy.max_attempts, 1);
+        assert_eq!(policy.cooldown, ResiliencePolicy::default().cooldown);
+    }
+}
@@ -0,0 +1,500 @@
+//! Scores retrieval quality on labelled query sets, so embedding models and rerankers can be
+//! compared on data that looks like ours instead of by eyeballing similarity scores.
+//!
+//! A set is JSONL mixing documents and queries:
+//!
+//! ```json
+//! {"type": "document", "id": "cat", "text": "A small domesticated feline"}
+//! {"type": "query", "query": "pet that purrs", "relevant": ["cat"]}
+//! ```
+//!
+//! Queries may grade relevance with `"grades": {"cat": 3, "lion": 1}` for nDCG; otherwise each
+//! relevant item has grade 1. Every query needs at least one relevant item.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use eyre::Context;
+use eyre::bail;
+use nanuak_config::dirs::get_project_dirs;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use crate::embedding_model::EmbeddingModel;
+use crate::embedding_provider::EmbeddingProvider;
+use crate::embedding_request::EmbeddingPayload;
+use crate::embedding_store::EmbeddingStore;
+use crate::embedding_store::StoredEmbedding;
+use crate::metadata_filter::MetadataFilter;
+use crate::rerank::rerank_top_n;
+use crate::rerank_provider::RerankProvider;
+use crate::stores::memory_embedding_store::MemoryEmbeddingStore;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalDocument {
+    pub id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalQuery {
+    pub query: String,
+    #[serde(default)]
+    pub relevant: Vec<String>,
+    #[serde(default)]
+    pub grades: BTreeMap<String, f64>,
+}
+impl EvalQuery {
+    pub fn grade(&self, id: &str) -> f64 {
+        match self.grades.get(id) {
+            Some(grade) => *grade,
+            None if self.relevant.iter().any(|relevant| relevant == id) => 1.0,
+            None => 0.0,
+        }
+    }
+    /// Graded items and the plain relevant list combined, each once.
+    pub fn relevant_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.relevant.iter().map(String::as_str).collect();
+        for (id, grade) in &self.grades {
+            if *grade > 0.0 && !ids.contains(&id.as_str()) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EvalLine {
+    Document(EvalDocument),
+    Query(EvalQuery),
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalSet {
+    /// Runs are compared within a set, so this defaults to the file stem.
+    pub name: String,
+    pub documents: Vec<EvalDocument>,
+    pub queries: Vec<EvalQuery>,
+}
+impl EvalSet {
+    pub async fn load(path: &Path) -> eyre::Result<Self> {
+        let jsonl = tokio::fs::read_to_string(path)
+            .await
+            .wrap_err_with(|| format!("Failed to read eval set: {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        Self::from_jsonl(&name, &jsonl)
+            .wrap_err_with(|| format!("Failed to parse eval set: {}", path.display()))
+    }
+    pub fn from_jsonl(name: &str, jsonl: &str) -> eyre::Result<Self> {
+        let mut set = EvalSet {
+            name: name.to_string(),
+            documents: Vec::new(),
+            queries: Vec::new(),
+        };
+        let mut query_lines = Vec::new();
+        for (i, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line).wrap_err_with(|| format!("Line {}", i + 1))? {
+                EvalLine::Document(document) => set.documents.push(document),
+                EvalLine::Query(query) => {
+                    query_lines.push(i + 1);
+                    set.queries.push(query);
+                }
+            }
+        }
+        if set.queries.is_empty() {
+            bail!("Eval set {} has no queries", name);
+        }
+        for (line, query) in query_lines.into_iter().zip(&set.queries) {
+            // It would score zero on every metric and drag down the means.
+            if query.relevant_ids().is_empty() {
+                bail!(
+                    "Line {}: query {:?} marks no document relevant",
+                    line,
+                    query.query
+                );
+            }
+            for id in query.relevant_ids() {
+                if !set.documents.iter().any(|document| document.id == id) {
+                    bail!(
+                        "Line {}: query {:?} marks unknown document {} relevant",
+                        line,
+                        query.query,
+                        id
+                    );
+                }
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    pub k_values: Vec<usize>,
+    /// How many retrieved candidates the reranker reorders, when one is given.
+    pub rerank_top_n: usize,
+    pub batch_size: usize,
+}
+impl Default for EvalOptions {
+    fn default() -> Self {
+        EvalOptions {
+            k_values: vec![1, 5, 10],
+            rerank_top_n: 20,
+            batch_size: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub set: String,
+    pub model: String,
+    pub provider: String,
+    pub reranker: Option<String>,
+    pub documents: usize,
+    pub queries: usize,
+    /// Mean recall@k, keyed by k.
+    pub recall: BTreeMap<usize, f64>,
+    /// Mean nDCG@k with linear gains, keyed by k.
+    pub ndcg: BTreeMap<usize, f64>,
+    /// Mean reciprocal rank of the first relevant item within the retrieved list.
+    pub mrr: f64,
+    pub duration_seconds: f64,
+    pub evaluated_at: NaiveDateTime,
+}
+impl EvalReport {
+    /// Metrics as `(name, value)` pairs, e.g. `recall@5`, in a stable order for tables.
+    pub fn metrics(&self) -> Vec<(String, f64)> {
+        let mut metrics: Vec<(String, f64)> = self
+            .recall
+            .iter()
+            .map(|(k, value)| (format!("recall@{}", k), *value))
+            .collect();
+        metrics.push(("mrr".to_string(), self.mrr));
+        metrics.extend(
+            self.ndcg
+                .iter()
+                .map(|(k, value)| (format!("ndcg@{}", k), *value)),
+        );
+        metrics
+    }
+    /// Runs with the same model, provider and reranker are compared against each other.
+    pub fn setup(&self) -> String {
+        match &self.reranker {
+            Some(reranker) => format!("{} on {} + {}", self.model, self.provider, reranker),
+            None => format!("{} on {}", self.model, self.provider),
+        }
+    }
+}
+
+/// Metrics for one query's ranking, best first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryScores {
+    pub recall: BTreeMap<usize, f64>,
+    pub ndcg: BTreeMap<usize, f64>,
+    pub reciprocal_rank: f64,
+}
+
+pub fn score_ranking(query: &EvalQuery, ranking: &[String], k_values: &[usize]) -> QueryScores {
+    let relevant = query.relevant_ids();
+    let mut ideal: Vec<f64> = relevant.iter().map(|id| query.grade(id)).collect();
+    ideal.sort_by(|a, b| b.total_cmp(a));
+    let dcg = |gains: &mut dyn Iterator<Item = f64>, k: usize| -> f64 {
+        gains
+            .take(k)
+            .enumerate()
+            .map(|(i, gain)| gain / (i as f64 + 2.0).log2())
+            .sum()
+    };
+    let mut recall = BTreeMap::new();
+    let mut ndcg = BTreeMap::new();
+    for &k in k_values {
+        let found = ranking
+            .iter()
+            .take(k)
+            .filter(|id| relevant.contains(&id.as_str()))
+            .count();
+        recall.insert(k, found as f64 / relevant.len().max(1) as f64);
+        let ideal_dcg = dcg(&mut ideal.iter().copied(), k);
+        let actual = dcg(&mut ranking.iter().map(|id| query.grade(id)), k);
+        ndcg.insert(
+            k,
+            if ideal_dcg > 0.0 {
+                actual / ideal_dcg
+            } else {
+                0.0
+            },
+        );
+    }
+    let reciprocal_rank = ranking
+        .iter()
+        .position(|id| relevant.contains(&id.as_str()))
+        .map(|rank| 1.0 / (rank as f64 + 1.0))
+        .unwrap_or_default();
+    QueryScores {
+        recall,
+        ndcg,
+        reciprocal_rank,
+    }
+}
+
+async fn embed_all(
+    provider: &dyn EmbeddingProvider,
+    model: &dyn EmbeddingModel,
+    texts: Vec<String>,
+    batch_size: usize,
+) -> eyre::Result<Vec<crate::embedding::Embedding>> {
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(batch_size.max(1)) {
+        let payloads = batch.iter().cloned().map(EmbeddingPayload::Text).collect();
+        let batch_embeddings = provider.get_embeddings(model, payloads).await?;
+        if batch_embeddings.len() != batch.len() {
+            bail!(
+                "Expected {} embeddings, got {}",
+                batch.len(),
+                batch_embeddings.len()
+            );
+        }
+        embeddings.extend(batch_embeddings);
+    }
+    Ok(embeddings)
+}
+
+/// Embeds the set's documents and queries with `model` on `provider`, retrieves by cosine
+/// similarity, optionally reranks, and averages the per-query metrics.
+pub async fn evaluate_retrieval(
+    set: &EvalSet,
+    provider: &dyn EmbeddingProvider,
+    model: &dyn EmbeddingModel,
+    reranker: Option<&dyn RerankProvider>,
+    options: &EvalOptions,
+) -> eyre::Result<EvalReport> {
+    let start = Instant::now();
+    let texts: HashMap<&str, &str> = set
+        .documents
+        .iter()
+        .map(|document| (document.id.as_str(), document.text.as_str()))
+        .collect();
+    let store = MemoryEmbeddingStore::new();
+    let document_embeddings = embed_all(
+        provider,
+        model,
+        set.documents.iter().map(|d| d.text.clone()).collect(),
+        options.batch_size,
+    )
+    .await?;
+    store
+        .upsert(
+            set.documents
+                .iter()
+                .zip(document_embeddings)
+                .map(|(document, embedding)| StoredEmbedding::new(&document.id, embedding))
+                .collect(),
+        )
+        .await?;
+    info!(
+        "Embedded {} documents with {} on {}",
+        set.documents.len(),
+        model.name(),
+        provider.name()
+    );
+    let query_embeddings = embed_all(
+        provider,
+        model,
+        set.queries.iter().map(|q| q.query.clone()).collect(),
+        options.batch_size,
+    )
+    .await?;
+
+    let max_k = options.k_values.iter().copied().max().unwrap_or(10);
+    let depth = match reranker {
+        Some(_) => max_k.max(options.rerank_top_n),
+        None => max_k,
+    };
+    let mut recall: BTreeMap<usize, f64> = BTreeMap::new();
+    let mut ndcg: BTreeMap<usize, f64> = BTreeMap::new();
+    let mut mrr = 0.0;
+    for (query, embedding) in set.queries.iter().zip(query_embeddings) {
+        let matches = store
+            .top_k(&embedding, depth, &MetadataFilter::new())
+            .await?;
+        let ranking: Vec<String> = match reranker {
+            Some(reranker) => rerank_top_n(
+                reranker,
+                &query.query,
+                matches,
+                options.rerank_top_n,
+                |found| texts[found.id.as_str()].to_string(),
+            )
+            .await?
+            .into_iter()
+            .map(|reranked| reranked.item.id)
+            .collect(),
+            None => matches.into_iter().map(|found| found.id).collect(),
+        };
+        let scores = score_ranking(query, &ranking, &options.k_values);
+        for (k, value) in scores.recall {
+            *recall.entry(k).or_default() += value;
+        }
+        for (k, value) in scores.ndcg {
+            *ndcg.entry(k).or_default() += value;
+        }
+        mrr += scores.reciprocal_rank;
+    }
+    let count = set.queries.len() as f64;
+    recall.values_mut().for_each(|value| *value /= count);
+    ndcg.values_mut().for_each(|value| *value /= count);
+    Ok(EvalReport {
+        set: set.name.clone(),
+        model: model.name().to_string(),
+        provider: provider.name().to_string(),
+        reranker: reranker.map(|reranker| reranker.name().to_string()),
+        documents: set.documents.len(),
+        queries: set.queries.len(),
+        recall,
+        ndcg,
+        mrr: mrr / count,
+        duration_seconds: start.elapsed().as_secs_f64(),
+        evaluated_at: Utc::now().naive_utc(),
+    })
+}
+
+/// Past reports, one JSON object per line in the project data dir.
+pub struct EvalHistory {
+    pub path: PathBuf,
+}
+impl EvalHistory {
+    pub fn default_path() -> eyre::Result<PathBuf> {
+        Ok(get_project_dirs()?.data_dir().join("retrieval_evals.jsonl"))
+    }
+    pub fn open() -> eyre::Result<Self> {
+        Ok(EvalHistory {
+            path: Self::default_path()?,
+        })
+    }
+    pub async fn append(&self, report: &EvalReport) -> eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .wrap_err_with(|| format!("Failed to open eval history: {}", self.path.display()))?;
+        let mut line = serde_json::to_string(report)?;
+        line.push('\n');
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+    /// Mean nDCG at the deepest k over the latest run of each set with `model` on `provider`
+    /// and no reranker, or `None` if it has never been evaluated.
+    pub async fn accuracy(&self, provider: &str, model: &str) -> eyre::Result<Option<f64>> {
+        let mut latest: BTreeMap<String, f64> = BTreeMap::new();
+        for report in self.load(None).await? {
+            if report.provider != provider || report.model != model || report.reranker.is_some() {
+                continue;
+            }
+            if let Some((_, ndcg)) = report.ndcg.last_key_value() {
+                latest.insert(report.set.clone(), *ndcg);
+            }
+        }
+        if latest.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(latest.values().sum::<f64>() / latest.len() as f64))
+    }
+    /// Reports for `set`, or every set, in the order they were recorded.
+    pub async fn load(&self, set: Option<&str>) -> eyre::Result<Vec<EvalReport>> {
+        if !tokio::fs::try_exists(&self.path).await? {
+            return Ok(Vec::new());
+        }
+        let jsonl = tokio::fs::read_to_string(&self.path).await?;
+        let mut reports = Vec::new();
+        for line in jsonl.lines().filter(|line| !line.trim().is_empty()) {
+            let report: EvalReport = serde_json::from_str(line).wrap_err_with(|| {
+                format!("Failed to parse eval history: {}", self.path.display())
+            })?;
+            if set.is_none_or(|set| report.set == set) {
+                reports.push(report);
+            }
+        }
+        Ok(reports)
+    }
+}
+
+/// Each metric's change since the previous report with the same set and setup.
+pub fn changes_since_previous(reports: &[EvalReport]) -> Vec<Option<Vec<(String, f64)>>> {
+    reports
+        .iter()
+        .enumerate()
+        .map(|(i, report)| {
+            let previous = reports[..i]
+                .iter()
+                .rev()
+                .find(|earlier| earlier.set == report.set && earlier.setup() == report.setup())?;
+            let before: HashMap<String, f64> = previous.metrics().into_iter().collect();
+            Some(
+                report
+                    .metrics()
+                    .into_iter()
+                    .filter_map(|(name, value)| Some((name.clone(), value - before.get(&name)?)))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::EvalQuery;
+    use super::EvalSet;
+    use super::score_ranking;
+
+    #[test]
+    fn scores_a_ranking() {
+        let query = EvalQuery {
+            query: "big cats".to_string(),
+            relevant: vec!["lion".to_string(), "tiger".to_string()],
+            grades: [("lion".to_string(), 2.0)].into_iter().collect(),
+        };
+        let ranking = ["house", "tiger", "lion", "car"].map(String::from);
+        let scores = score_ranking(&query, &ranking, &[1, 2, 3]);
+        assert_eq!(scores.recall[&1], 0.0);
+        assert_eq!(scores.recall[&2], 0.5);
+        assert_eq!(scores.recall[&3], 1.0);
+        assert_eq!(scores.reciprocal_rank, 0.5);
+        // Ideal is lion(2) then tiger(1); actual puts tiger at rank 2 and lion at rank 3.
+        let ideal = 2.0 + 1.0 / 3f64.log2();
+        let actual = 1.0 / 3f64.log2() + 2.0 / 4f64.log2();
+        assert!((scores.ndcg[&3] - actual / ideal).abs() < 1e-9);
+        assert_eq!(scores.ndcg[&1], 0.0);
+    }
+
+    #[test]
+    fn rejects_queries_without_relevant_documents() {
+        let jsonl = [
+            r#"{"type": "document", "id": "cat", "text": "A small domesticated feline"}"#,
+            r#"{"type": "query", "query": "pet that purrs", "relevant": ["cat"]}"#,
+            r#"{"type": "query", "query": "pet that barks"}"#,
+        ]
+        .join("\n");
+        let error = EvalSet::from_jsonl("pets", &jsonl).unwrap_err();
+        assert!(error.to_string().starts_with("Line 3:"), "{}", error);
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::embedding::Embedding;
+use crate::embedding_store::EmbeddingMatch;
+use crate::embedding_store::EmbeddingMetadata;
+use crate::embedding_store::EmbeddingStore;
+use crate::embedding_store::StoredEmbedding;
+use crate::embedding_store::ensure_dimensions;
+use crate::metadata_filter::MetadataFilter;
+
+/// Brute-force cosine search with simsimd; fine up to tens of thousands of records.
+#[derive(Debug, Default)]
+pub struct MemoryEmbeddingStore {
+    records: RwLock<HashMap<String, (Embedding, EmbeddingMetadata)>>,
+}
+impl MemoryEmbeddingStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn stored_dimensions(stored: &HashMap<String, (Embedding, EmbeddingMetadata)>) -> Option<usize> {
+    stored
+        .values()
+        .next()
+        .map(|(embedding, _)| embedding.dimensions())
+}
+
+#[async_trait]
+impl EmbeddingStore for MemoryEmbeddingStore {
+    fn name(&self) -> &str {
+        "memory"
+    }
+    async fn upsert(&self, records: Vec<StoredEmbedding>) -> eyre::Result<()> {
+        let mut stored = self.records.write().unwrap();
+        let dimensions = stored_dimensions(&stored)
+            .or_else(|| records.first().map(|record| record.embedding.dimensions()));
+        for record in &records {
+            ensure_dimensions(
+                dimensions,
+                &format!("Embedding {}", record.id),
+                &record.embedding,
+            )?;
+        }
+        for record in records {
+            stored.insert(record.id, (record.embedding, record.metadata));
+        }
+        Ok(())
+    }
+    async fn delete(&self, ids: &[String]) -> eyre::Result<usize> {
+        let mut stored = self.records.write().unwrap();
+        Ok(ids.iter().filter(|id| stored.remove(*id).is_some()).count())
+    }
+    async fn top_k(
+        &self,
+        query: &Embedding,
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> eyre::Result<Vec<EmbeddingMatch>> {
+        let stored = self.records.read().unwrap();
+        ensure_dimensions(stored_dimensions(&stored), "The query", query)?;
+        let mut scored = Vec::new();
+        for (id, (embedding, metadata)) in stored.iter() {
+            if filter.matches(metadata) {
+                scored.push((query.cosine_similarity(embedding)?, id, metadata));
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        Ok(scored
+            .into_iter()
+            .take(k)
+            .map(|(score, id, metadata)| EmbeddingMatch {
+                id: id.clone(),
+                score,
+                metadata: metadata.clone(),
+            })
+            .collect())
+    }
+    async fn count(&self) -> eyre::Result<usize> {
+        Ok(self.records.read().unwrap().len())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod memory_embedding_store;
+pub mod pgvector_embedding_store;
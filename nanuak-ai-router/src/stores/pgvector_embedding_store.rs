@@ -0,0 +1,394 @@
+//! Stores embeddings in `ai.embeddings`, one named collection per use, or in one of the older
+//! per-source tables through [`PgVectorCollection`].
+//!
+//! In `ai.embeddings`, metadata is JSONB and filters become `@>` containment checks, which the
+//! table's GIN index serves. Search is exact; add a per-collection HNSW expression index if a collection grows
+//! past what a sequential scan handles.
+
+use async_trait::async_trait;
+use diesel::Connection;
+use diesel::OptionalExtension;
+use diesel::PgConnection;
+use diesel::QueryableByName;
+use diesel::RunQueryDsl;
+use diesel::pg::Pg;
+use diesel::r2d2::ConnectionManager;
+use diesel::sql_types::Array;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Double;
+use diesel::sql_types::Integer;
+use diesel::sql_types::Text;
+use eyre::bail;
+use nanuak_schema::migrations::ensure_schema_current;
+use r2d2::Pool;
+use serde_json::Value;
+
+use crate::embedding::Embedding;
+use crate::embedding_store::EmbeddingMatch;
+use crate::embedding_store::EmbeddingMetadata;
+use crate::embedding_store::EmbeddingStore;
+use crate::embedding_store::StoredEmbedding;
+use crate::embedding_store::ensure_dimensions;
+use crate::metadata_filter::MetadataCondition;
+use crate::metadata_filter::MetadataFilter;
+use crate::telemetry::get_database_url;
+
+#[derive(QueryableByName)]
+struct MatchRow {
+    #[diesel(sql_type = Text)]
+    id: String,
+    #[diesel(sql_type = Double)]
+    score: f64,
+    #[diesel(sql_type = Text)]
+    metadata: String,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct DimensionsRow {
+    #[diesel(sql_type = Integer)]
+    dimensions: i32,
+}
+
+/// Where a [`PgVectorEmbeddingStore`] keeps its records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgVectorCollection {
+    /// A named collection in `ai.embeddings`, with JSONB metadata.
+    Named(String),
+    /// `youtube.video_embeddings_bge_m3`, keyed by video etag. Has no metadata.
+    YoutubeVideos,
+    /// `files.embeddings_512` rows for one model, keyed by the path in `files.files`. Has no
+    /// metadata, and files must already be known before their embeddings are stored.
+    Files { model: String },
+}
+impl From<&str> for PgVectorCollection {
+    fn from(name: &str) -> Self {
+        PgVectorCollection::Named(name.to_string())
+    }
+}
+impl PgVectorCollection {
+    fn has_metadata(&self) -> bool {
+        matches!(self, PgVectorCollection::Named(_))
+    }
+}
+
+pub struct PgVectorEmbeddingStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+    collection: PgVectorCollection,
+}
+impl PgVectorEmbeddingStore {
+    pub fn new(
+        pool: Pool<ConnectionManager<PgConnection>>,
+        collection: impl Into<PgVectorCollection>,
+    ) -> Self {
+        PgVectorEmbeddingStore {
+            pool,
+            collection: collection.into(),
+        }
+    }
+    /// Connects with the database password from the Nanuak config.
+    pub async fn connect(collection: impl Into<PgVectorCollection>) -> eyre::Result<Self> {
+        let manager = ConnectionManager::<PgConnection>::new(get_database_url().await?);
+        let pool = Pool::builder().build(manager)?;
+        ensure_schema_current(&mut *pool.get()?)?;
+        Ok(Self::new(pool, collection))
+    }
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut PgConnection, &PgVectorCollection) -> eyre::Result<T> + Send + 'static,
+    ) -> eyre::Result<T> {
+        let pool = self.pool.clone();
+        let collection = self.collection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            f(&mut conn, &collection)
+        })
+        .await?
+    }
+}
+
+/// The dimensions of any one stored embedding, or `None` when the collection is empty.
+fn stored_dimensions(
+    conn: &mut PgConnection,
+    collection: &PgVectorCollection,
+) -> eyre::Result<Option<usize>> {
+    let row = match collection {
+        PgVectorCollection::Named(name) => diesel::sql_query(
+            "SELECT vector_dims(embedding) AS dimensions FROM ai.embeddings
+             WHERE collection = $1 LIMIT 1",
+        )
+        .bind::<Text, _>(name)
+        .get_result::<DimensionsRow>(conn)
+        .optional()?,
+        PgVectorCollection::YoutubeVideos => diesel::sql_query(
+            "SELECT vector_dims(embedding) AS dimensions FROM youtube.video_embeddings_bge_m3
+             WHERE embedding IS NOT NULL LIMIT 1",
+        )
+        .get_result::<DimensionsRow>(conn)
+        .optional()?,
+        PgVectorCollection::Files { model } => diesel::sql_query(
+            "SELECT vector_dims(embedding) AS dimensions FROM files.embeddings_512
+             WHERE model = $1 LIMIT 1",
+        )
+        .bind::<Text, _>(model)
+        .get_result::<DimensionsRow>(conn)
+        .optional()?,
+    };
+    Ok(row.map(|row| row.dimensions as usize))
+}
+
+fn upsert_record(
+    conn: &mut PgConnection,
+    collection: &PgVectorCollection,
+    record: &StoredEmbedding,
+) -> eyre::Result<()> {
+    match collection {
+        PgVectorCollection::Named(name) => {
+            diesel::sql_query(
+                "INSERT INTO ai.embeddings (collection, id, embedding, metadata, updated_at)
+                 VALUES ($1, $2, $3, $4::jsonb, CURRENT_TIMESTAMP)
+                 ON CONFLICT (collection, id) DO UPDATE SET
+                     embedding = EXCLUDED.embedding,
+                     metadata = EXCLUDED.metadata,
+                     updated_at = EXCLUDED.updated_at",
+            )
+            .bind::<Text, _>(name)
+            .bind::<Text, _>(&record.id)
+            .bind::<pgvector::sql_types::Vector, _>(record.embedding.to_vector())
+            .bind::<Text, _>(Value::Object(record.metadata.clone()).to_string())
+            .execute(conn)?;
+        }
+        PgVectorCollection::YoutubeVideos => {
+            diesel::sql_query(
+                "INSERT INTO youtube.video_embeddings_bge_m3 (video_etag, embedded_on, embedding)
+                 VALUES ($1, CURRENT_TIMESTAMP, $2)
+                 ON CONFLICT (video_etag) DO UPDATE SET
+                     embedded_on = EXCLUDED.embedded_on,
+                     embedding = EXCLUDED.embedding",
+            )
+            .bind::<Text, _>(&record.id)
+            .bind::<pgvector::sql_types::Vector, _>(record.embedding.to_vector())
+            .execute(conn)?;
+        }
+        PgVectorCollection::Files { model } => {
+            // The table has no unique key on file and model, so replace rather than upsert.
+            diesel::sql_query(
+                "DELETE FROM files.embeddings_512 e USING files.files f
+                 WHERE e.file_id = f.id AND f.path = $1 AND e.model = $2",
+            )
+            .bind::<Text, _>(&record.id)
+            .bind::<Text, _>(model)
+            .execute(conn)?;
+            let inserted = diesel::sql_query(
+                "INSERT INTO files.embeddings_512 (file_id, model, embedding)
+                 SELECT id, $2, $3 FROM files.files WHERE path = $1",
+            )
+            .bind::<Text, _>(&record.id)
+            .bind::<Text, _>(model)
+            .bind::<pgvector::sql_types::Vector, _>(record.embedding.to_vector())
+            .execute(conn)?;
+            if inserted == 0 {
+                bail!("No file in files.files has path {}", record.id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Appends `condition` as SQL to `sql`, pushing its JSONB parameters onto `params`.
+fn push_condition(
+    sql: &mut String,
+    params: &mut Vec<String>,
+    first_param: usize,
+    condition: &MetadataCondition,
+) {
+    let mut containment = |key: &str, value: &Value| {
+        params.push(
+            Value::Object(EmbeddingMetadata::from_iter([(
+                key.to_string(),
+                value.clone(),
+            )]))
+            .to_string(),
+        );
+        format!("metadata @> ${}::jsonb", first_param + params.len() - 1)
+    };
+    match condition {
+        MetadataCondition::Equals { key, value } => {
+            sql.push_str(&containment(key, value));
+        }
+        MetadataCondition::OneOf { values, .. } if values.is_empty() => {
+            sql.push_str("FALSE");
+        }
+        MetadataCondition::OneOf { key, values } => {
+            let alternatives: Vec<String> =
+                values.iter().map(|value| containment(key, value)).collect();
+            sql.push_str(&format!("({})", alternatives.join(" OR ")));
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingStore for PgVectorEmbeddingStore {
+    fn name(&self) -> &str {
+        "pgvector"
+    }
+    async fn upsert(&self, records: Vec<StoredEmbedding>) -> eyre::Result<()> {
+        if !self.collection.has_metadata()
+            && let Some(record) = records.iter().find(|record| !record.metadata.is_empty())
+        {
+            bail!(
+                "{:?} has no metadata column, but {} has metadata",
+                self.collection,
+                record.id
+            );
+        }
+        self.with_conn(move |conn, collection| {
+            conn.transaction::<_, eyre::Report, _>(|conn| {
+                let dimensions = stored_dimensions(conn, collection)?
+                    .or_else(|| records.first().map(|record| record.embedding.dimensions()));
+                for record in &records {
+                    ensure_dimensions(
+                        dimensions,
+                        &format!("Embedding {}", record.id),
+                        &record.embedding,
+                    )?;
+                    upsert_record(conn, collection, record)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+    async fn delete(&self, ids: &[String]) -> eyre::Result<usize> {
+        let ids = ids.to_vec();
+        self.with_conn(move |conn, collection| {
+            let deleted = match collection {
+                PgVectorCollection::Named(name) => diesel::sql_query(
+                    "DELETE FROM ai.embeddings WHERE collection = $1 AND id = ANY($2)",
+                )
+                .bind::<Text, _>(name)
+                .bind::<Array<Text>, _>(ids)
+                .execute(conn)?,
+                PgVectorCollection::YoutubeVideos => diesel::sql_query(
+                    "DELETE FROM youtube.video_embeddings_bge_m3 WHERE video_etag = ANY($1)",
+                )
+                .bind::<Array<Text>, _>(ids)
+                .execute(conn)?,
+                PgVectorCollection::Files { model } => diesel::sql_query(
+                    "DELETE FROM files.embeddings_512 e USING files.files f
+                     WHERE e.file_id = f.id AND e.model = $1 AND f.path = ANY($2)",
+                )
+                .bind::<Text, _>(model)
+                .bind::<Array<Text>, _>(ids)
+                .execute(conn)?,
+            };
+            Ok(deleted)
+        })
+        .await
+    }
+    async fn top_k(
+        &self,
+        query: &Embedding,
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> eyre::Result<Vec<EmbeddingMatch>> {
+        if !self.collection.has_metadata() && !filter.conditions.is_empty() {
+            bail!("{:?} has no metadata to filter on", self.collection);
+        }
+        // $1 query, $2 limit, $3 the collection name or model, then the filter's parameters.
+        let (select, order) = match &self.collection {
+            PgVectorCollection::Named(_) => (
+                "SELECT id, 1 - (embedding <=> $1) AS score, metadata::text AS metadata
+                 FROM ai.embeddings
+                 WHERE collection = $3",
+                "embedding <=> $1, id",
+            ),
+            PgVectorCollection::YoutubeVideos => (
+                "SELECT video_etag AS id, 1 - (embedding <=> $1) AS score, '{}' AS metadata
+                 FROM youtube.video_embeddings_bge_m3
+                 WHERE embedding IS NOT NULL",
+                "embedding <=> $1, video_etag",
+            ),
+            PgVectorCollection::Files { .. } => (
+                "SELECT f.path AS id, 1 - (e.embedding <=> $1) AS score, '{}' AS metadata
+                 FROM files.embeddings_512 e JOIN files.files f ON f.id = e.file_id
+                 WHERE e.model = $3",
+                "e.embedding <=> $1, f.path",
+            ),
+        };
+        let mut sql = select.to_string();
+        let mut params = Vec::new();
+        for condition in &filter.conditions {
+            sql.push_str(" AND ");
+            push_condition(&mut sql, &mut params, 4, condition);
+        }
+        sql.push_str(&format!(" ORDER BY {} LIMIT $2", order));
+        let query = query.clone();
+        let limit = i64::try_from(k)?;
+        let rows = self
+            .with_conn(move |conn, collection| {
+                ensure_dimensions(stored_dimensions(conn, collection)?, "The query", &query)?;
+                let mut statement = diesel::sql_query(sql)
+                    .into_boxed::<Pg>()
+                    .bind::<pgvector::sql_types::Vector, _>(query.to_vector())
+                    .bind::<BigInt, _>(limit);
+                match collection {
+                    PgVectorCollection::Named(name) => {
+                        statement = statement.bind::<Text, _>(name.clone());
+                    }
+                    PgVectorCollection::YoutubeVideos => {}
+                    PgVectorCollection::Files { model } => {
+                        statement = statement.bind::<Text, _>(model.clone());
+                    }
+                }
+                for param in params {
+                    statement = statement.bind::<Text, _>(param);
+                }
+                Ok(statement.load::<MatchRow>(conn)?)
+            })
+            .await?;
+        rows.into_iter()
+            .map(|row| {
+                let Value::Object(metadata) = serde_json::from_str(&row.metadata)? else {
+                    bail!("Metadata for {} is not a JSON object", row.id);
+                };
+                Ok(EmbeddingMatch {
+                    id: row.id,
+                    score: row.score,
+                    metadata,
+                })
+            })
+            .collect()
+    }
+    async fn count(&self) -> eyre::Result<usize> {
+        let row = self
+            .with_conn(|conn, collection| {
+                let row = match collection {
+                    PgVectorCollection::Named(name) => diesel::sql_query(
+                        "SELECT count(*) AS count FROM ai.embeddings WHERE collection = $1",
+                    )
+                    .bind::<Text, _>(name)
+                    .get_result::<CountRow>(conn)?,
+                    PgVectorCollection::YoutubeVideos => diesel::sql_query(
+                        "SELECT count(*) AS count FROM youtube.video_embeddings_bge_m3
+                         WHERE embedding IS NOT NULL",
+                    )
+                    .get_result::<CountRow>(conn)?,
+                    PgVectorCollection::Files { model } => diesel::sql_query(
+                        "SELECT count(DISTINCT file_id) AS count FROM files.embeddings_512
+                         WHERE model = $1",
+                    )
+                    .bind::<Text, _>(model)
+                    .get_result::<CountRow>(conn)?,
+                };
+                Ok(row)
+            })
+            .await?;
+        Ok(usize::try_from(row.count)?)
+    }
+}
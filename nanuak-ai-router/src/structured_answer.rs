@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use eyre::bail;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use tracing::warn;
+
+use crate::conversation::Conversation;
+use crate::conversation::Message;
+use crate::generative_text_model::GenerativeTextModel;
+use crate::generative_text_provider::GenerativeTextProvider;
+
+pub const DEFAULT_STRUCTURED_ATTEMPTS: usize = 3;
+
+/// Schema-constrained generation for any [`GenerativeTextProvider`].
+#[async_trait]
+pub trait StructuredAnswerExt {
+    /// Asks for a reply shaped like `T`, feeding deserialization errors back to the model
+    /// until it produces valid output or `max_attempts` is exhausted.
+    async fn answer_structured_with_attempts<T>(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        max_attempts: usize,
+    ) -> eyre::Result<T>
+    where
+        T: DeserializeOwned + JsonSchema + Send;
+
+    async fn answer_structured<T>(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+    ) -> eyre::Result<T>
+    where
+        T: DeserializeOwned + JsonSchema + Send,
+    {
+        self.answer_structured_with_attempts(model, conversation, DEFAULT_STRUCTURED_ATTEMPTS)
+            .await
+    }
+}
+
+#[async_trait]
+impl<P: GenerativeTextProvider + ?Sized> StructuredAnswerExt for P {
+    async fn answer_structured_with_attempts<T>(
+        &self,
+        model: &dyn GenerativeTextModel,
+        conversation: &Conversation,
+        max_attempts: usize,
+    ) -> eyre::Result<T>
+    where
+        T: DeserializeOwned + JsonSchema + Send,
+    {
+        let schema = serde_json::to_value(schemars::schema_for!(T))?;
+        let mut conversation = conversation.clone();
+        let mut last_error = None;
+        for attempt in 1..=max_attempts {
+            let answer = self.chat_with_schema(model, &conversation, &schema).await?;
+            match serde_json::from_str::<T>(&answer.body) {
+                Ok(value) => {
+                    debug!("Structured answer parsed on attempt {}", attempt);
+                    return Ok(value);
+                }
+                Err(error) => {
+                    warn!(
+                        "Structured answer attempt {}/{} from model {} was invalid: {}",
+                        attempt,
+                        max_attempts,
+                        model.name(),
+                        error
+                    );
+                    conversation.push(Message::assistant(answer.body));
+                    conversation.push(Message::user(format!(
+                        "That reply did not match the required JSON schema: {}\nRespond again with only JSON matching this schema:\n{}",
+                        error, schema
+                    )));
+                    last_error = Some(error);
+                }
+            }
+        }
+        match last_error {
+            Some(error) => bail!(
+                "Model {} failed to produce valid structured output after {} attempts: {}",
+                model.name(),
+                max_attempts,
+                error
+            ),
+            None => bail!("max_attempts must be at least 1"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use eyre::bail;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    use super::StructuredAnswerExt;
+    use crate::answer::Answer;
+    use crate::attributes::Residency;
+    use crate::conversation::Conversation;
+    use crate::conversation::Message;
+    use crate::generative_text_model::GenerativeTextModel;
+    use crate::generative_text_provider::GenerativeTextProvider;
+    use crate::model_attributes::ModelAttributes;
+    use crate::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+    use crate::question::Question;
+
+    struct ScriptedProvider {
+        replies: Mutex<Vec<&'static str>>,
+        seen: Mutex<Vec<usize>>,
+    }
+    #[async_trait]
+    impl GenerativeTextProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+        async fn is_supported(&self, _model: &dyn GenerativeTextModel) -> eyre::Result<bool> {
+            Ok(true)
+        }
+        async fn get_attributes(
+            &self,
+            model: &dyn GenerativeTextModel,
+        ) -> eyre::Result<ModelAttributes> {
+            Ok(ModelAttributes::unmeasured(model.get_context_size()))
+        }
+        fn get_residency(&self) -> Residency {
+            Residency::Local
+        }
+        async fn answer_question(
+            &self,
+            model: &dyn GenerativeTextModel,
+            question: Question,
+        ) -> eyre::Result<Answer> {
+            let conversation = Conversation::new()
+                .with_message(Message::user(self.format_question(question).await?));
+            self.chat(model, &conversation).await
+        }
+        async fn chat(
+            &self,
+            _model: &dyn GenerativeTextModel,
+            conversation: &Conversation,
+        ) -> eyre::Result<Answer> {
+            self.seen.lock().unwrap().push(conversation.messages.len());
+            let mut replies = self.replies.lock().unwrap();
+            if replies.is_empty() {
+                bail!("No scripted reply left");
+            }
+            Ok(Answer::new(replies.remove(0).to_string()))
+        }
+        async fn chat_with_schema(
+            &self,
+            model: &dyn GenerativeTextModel,
+            conversation: &Conversation,
+            _schema: &serde_json::Value,
+        ) -> eyre::Result<Answer> {
+            self.chat(model, conversation).await
+        }
+        async fn format_question(&self, question: Question) -> eyre::Result<String> {
+            Ok(question.text)
+        }
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema, PartialEq)]
+    struct Tags {
+        tags: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn retries_with_validation_error() -> eyre::Result<()> {
+        let provider = ScriptedProvider {
+            replies: Mutex::new(vec!["{\"tag\": \"cat\"}", "{\"tags\": [\"cat\"]}"]),
+            seen: Mutex::new(Vec::new()),
+        };
+        let conversation = Conversation::new().with_message(Message::user("Tag this".to_string()));
+        let tags: Tags = provider
+            .answer_structured(&Gemma2_2BGenerativeTextModel, &conversation)
+            .await?;
+        assert_eq!(tags.tags, vec!["cat".to_string()]);
+        // The retry carries the bad reply and the error message.
+        assert_eq!(*provider.seen.lock().unwrap(), vec![1, 3]);
+        Ok(())
+    }
+}
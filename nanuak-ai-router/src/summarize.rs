@@ -0,0 +1,324 @@
+//! Summaries of text longer than a model's context window.
+//!
+//! The input is split into chunks that fit the token budget, each chunk is summarized
+//! concurrently, and the summaries are merged in groups until one remains. Chunk summaries
+//! cite their chunk as `[n]` and merges keep those citations, so the final text points back at
+//! the source chunks it drew from.
+
+use eyre::bail;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use serde::Serialize;
+use tracing::debug;
+
+use crate::answer::Answer;
+use crate::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use crate::prompt_registry::PromptRegistry;
+use crate::prompt_template::PromptVariables;
+
+/// Tokens reserved per request for the template and the reply.
+const PROMPT_RESERVE_TOKENS: u32 = 512;
+
+/// A rough count for budgeting, at about four characters per token of English text.
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceChunk {
+    pub index: usize,
+    /// Byte offset of the chunk in the input.
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Splits at line breaks where possible, and at whitespace inside lines that are too long.
+pub fn split_by_tokens(text: &str, max_tokens: u32) -> Vec<SourceChunk> {
+    let budget = (max_tokens.max(1) as usize).saturating_mul(4);
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let mut start = offset;
+        let end = offset + line.len();
+        while text[start..end].chars().count() > budget {
+            let limit = start
+                + text[start..end]
+                    .char_indices()
+                    .nth(budget)
+                    .map(|(i, _)| i)
+                    .unwrap_or(end - start);
+            let cut = text[start..limit]
+                .rfind(char::is_whitespace)
+                .map(|i| start + i + 1)
+                .filter(|cut| *cut > start)
+                .unwrap_or(limit);
+            pieces.push((start, cut));
+            start = cut;
+        }
+        pieces.push((start, end));
+        offset = end;
+    }
+
+    let mut chunks: Vec<SourceChunk> = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let flush = |range: (usize, usize), chunks: &mut Vec<SourceChunk>| {
+        let chunk = &text[range.0..range.1];
+        if !chunk.trim().is_empty() {
+            chunks.push(SourceChunk {
+                index: chunks.len(),
+                start: range.0,
+                end: range.1,
+                text: chunk.to_string(),
+            });
+        }
+    };
+    for (start, end) in pieces {
+        current = match current {
+            Some((current_start, current_end))
+                if text[current_start..end].chars().count() > budget =>
+            {
+                flush((current_start, current_end), &mut chunks);
+                Some((start, end))
+            }
+            Some((current_start, _)) => Some((current_start, end)),
+            None => Some((start, end)),
+        };
+    }
+    if let Some(range) = current {
+        flush(range, &mut chunks);
+    }
+    chunks
+}
+
+/// Chunk indexes cited as `[n]` or `[n, m]` in `text`, sorted, ignoring any out of range.
+pub fn cited_chunks(text: &str, chunk_count: usize) -> Vec<usize> {
+    let mut cited = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(']') else {
+            break;
+        };
+        let numbers: Option<Vec<usize>> = rest[..close]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().ok())
+            .collect();
+        cited.extend(
+            numbers
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|index| *index < chunk_count),
+        );
+        rest = &rest[close + 1..];
+    }
+    cited.sort();
+    cited.dedup();
+    cited
+}
+
+#[derive(Debug, Clone)]
+pub struct SummarizeOptions {
+    pub strategy: WellKnownGenerativeTextStrategy,
+    /// A question to steer every summary towards, e.g. "what changed in the API?".
+    pub focus: Option<String>,
+    /// Defaults to half the smallest candidate model's context, less room for the prompt.
+    pub chunk_tokens: Option<u32>,
+    /// Chunks or merges in flight at once.
+    pub concurrency: usize,
+    pub chunk_words: u32,
+    pub summary_words: u32,
+}
+impl Default for SummarizeOptions {
+    fn default() -> Self {
+        SummarizeOptions {
+            strategy: WellKnownGenerativeTextStrategy::BestLocal,
+            focus: None,
+            chunk_tokens: None,
+            concurrency: 4,
+            chunk_words: 150,
+            summary_words: 250,
+        }
+    }
+}
+impl SummarizeOptions {
+    pub fn with_focus(mut self, focus: impl Into<String>) -> Self {
+        self.focus = Some(focus.into());
+        self
+    }
+    async fn chunk_tokens(&self) -> eyre::Result<u32> {
+        if let Some(chunk_tokens) = self.chunk_tokens {
+            return Ok(chunk_tokens);
+        }
+        let Some(context) = self
+            .strategy
+            .get_candidate_models()
+            .await?
+            .iter()
+            .map(|model| model.get_context_size().0)
+            .min()
+        else {
+            bail!("Strategy {:?} has no candidate models", self.strategy);
+        };
+        Ok((context / 2).saturating_sub(PROMPT_RESERVE_TOKENS).max(256))
+    }
+    fn variables(&self) -> PromptVariables {
+        match &self.focus {
+            Some(focus) => PromptVariables::new().with("focus", focus.as_str()),
+            None => PromptVariables::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub text: String,
+    pub chunks: Vec<SourceChunk>,
+    /// One per chunk, in order.
+    pub chunk_summaries: Vec<String>,
+    /// Indexes of the chunks the final text cites.
+    pub sources: Vec<usize>,
+    /// Rounds of summarization, 1 when the chunk summaries needed no merging.
+    pub levels: usize,
+}
+impl Summary {
+    pub fn source_chunks(&self) -> impl Iterator<Item = &SourceChunk> {
+        self.sources.iter().map(|index| &self.chunks[*index])
+    }
+}
+
+pub async fn summarize(text: &str, options: &SummarizeOptions) -> eyre::Result<Summary> {
+    let budget = options.chunk_tokens().await?;
+    let chunks = split_by_tokens(text, budget);
+    if chunks.is_empty() {
+        bail!("Nothing to summarize");
+    }
+    debug!(
+        "Summarizing {} chunks of up to {} tokens",
+        chunks.len(),
+        budget
+    );
+    let registry = PromptRegistry::global().await?;
+    let map_template = registry.get("summarize-chunk")?;
+    let reduce_template = registry.get("summarize-reduce")?;
+
+    let total = chunks.len();
+    let chunk_summaries: Vec<String> = futures::stream::iter(&chunks)
+        .map(|chunk| {
+            let variables = options
+                .variables()
+                .with("chunk", chunk.text.as_str())
+                .with("index", chunk.index)
+                .with("total", total)
+                .with("words", options.chunk_words);
+            async move {
+                let answer = Answer::try_generate_from_template(
+                    options.strategy,
+                    map_template,
+                    &variables,
+                    Vec::new(),
+                )
+                .await?;
+                eyre::Ok(answer.body.trim().to_string())
+            }
+        })
+        .buffered(options.concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut summaries = chunk_summaries.clone();
+    let mut levels = 1;
+    while summaries.len() > 1 {
+        let groups = group_by_budget(summaries, budget);
+        debug!("Merging into {} summaries", groups.len());
+        summaries = futures::stream::iter(groups)
+            .map(|group| {
+                let variables = options
+                    .variables()
+                    .with("summaries", group)
+                    .with("words", options.summary_words);
+                async move {
+                    let answer = Answer::try_generate_from_template(
+                        options.strategy,
+                        reduce_template,
+                        &variables,
+                        Vec::new(),
+                    )
+                    .await?;
+                    eyre::Ok(answer.body.trim().to_string())
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+        levels += 1;
+    }
+    let text = summaries.remove(0);
+    Ok(Summary {
+        sources: cited_chunks(&text, chunks.len()),
+        text,
+        chunks,
+        chunk_summaries,
+        levels,
+    })
+}
+
+/// Consecutive summaries packed to fit `budget`, at least two per group so every round
+/// shrinks the count even when single summaries are large.
+fn group_by_budget(summaries: Vec<String>, budget: u32) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut tokens: u32 = 0;
+    for summary in summaries {
+        let size = estimate_tokens(&summary);
+        match groups.last_mut() {
+            Some(group) if group.len() < 2 || tokens.saturating_add(size) <= budget => {
+                tokens = tokens.saturating_add(size);
+                group.push(summary);
+            }
+            _ => {
+                tokens = size;
+                groups.push(vec![summary]);
+            }
+        }
+    }
+    // A lone trailing summary joins the previous group rather than being merged with itself.
+    if groups.len() > 1 && groups.last().is_some_and(|group| group.len() == 1) {
+        let last = groups.pop().unwrap();
+        groups.last_mut().unwrap().extend(last);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod test {
+    use super::cited_chunks;
+    use super::group_by_budget;
+    use super::split_by_tokens;
+
+    #[test]
+    fn splits_within_budget_and_keeps_offsets() {
+        let text = "first line\nsecond line\n\na very long line that must be cut somewhere\n";
+        let chunks = split_by_tokens(text, 4);
+        assert!(chunks.len() > 3);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= 16, "{:?}", chunk.text);
+            assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+        }
+        let rejoined: String = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(rejoined.replace('\n', ""), text.replace('\n', ""));
+        assert_eq!(split_by_tokens(text, 1000).len(), 1);
+        assert_eq!(split_by_tokens(text, u32::MAX).len(), 1);
+    }
+
+    #[test]
+    fn parses_citations_and_groups_summaries() {
+        assert_eq!(
+            cited_chunks("A [2]. B [0, 1][7] and [see above] [1]", 3),
+            vec![0, 1, 2]
+        );
+        let groups = group_by_budget(["a".repeat(40), "b".repeat(40), "c".repeat(40)].into(), 5);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 3);
+    }
+}
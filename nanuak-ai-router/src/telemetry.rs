@@ -0,0 +1,280 @@
+//! Usage and latency records for every provider call, written to `ai.calls`.
+//!
+//! Recording is a no-op until a binary calls [`install_postgres`], so library users that
+//! don't want a database connection pay nothing. The database is connected to when the first
+//! call is recorded, and writes happen on a background task in batches; call
+//! [`TelemetryHandle::flush`] before exiting so the tail isn't lost.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use diesel::PgConnection;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Double;
+use diesel::sql_types::Integer;
+use diesel::sql_types::Nullable;
+use diesel::sql_types::Text;
+use diesel::sql_types::Timestamp;
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::db_url::DatabasePassword;
+use nanuak_schema::ai_models::NewCall;
+use nanuak_schema::migrations::ensure_schema_current;
+use r2d2::Pool;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::warn;
+
+use crate::prompt_template::PromptTemplateRef;
+
+const MAX_BATCH: usize = 256;
+
+static SINK: Mutex<Option<mpsc::UnboundedSender<CallRecord>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CallModality {
+    Embedding,
+    GenerativeText,
+    Transcription,
+    Rerank,
+}
+impl CallModality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallModality::Embedding => "embedding",
+            CallModality::GenerativeText => "generative_text",
+            CallModality::Transcription => "transcription",
+            CallModality::Rerank => "rerank",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    pub provider: String,
+    pub model: String,
+    pub modality: CallModality,
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+    pub latency: Duration,
+    pub error: Option<String>,
+    /// The prompt template, as `name@version`, for generative calls built from one.
+    pub prompt: Option<String>,
+    pub called_at: NaiveDateTime,
+}
+impl CallRecord {
+    pub fn new(provider: &str, model: &str, modality: CallModality, latency: Duration) -> Self {
+        CallRecord {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            modality,
+            input_tokens: None,
+            output_tokens: None,
+            latency,
+            error: None,
+            prompt: None,
+            called_at: Utc::now().naive_utc(),
+        }
+    }
+    pub fn with_tokens(mut self, input_tokens: Option<u32>, output_tokens: Option<u32>) -> Self {
+        self.input_tokens = input_tokens;
+        self.output_tokens = output_tokens;
+        self
+    }
+    pub fn with_prompt(mut self, prompt: Option<&PromptTemplateRef>) -> Self {
+        self.prompt = prompt.map(|prompt| prompt.to_string());
+        self
+    }
+    pub fn with_error(mut self, error: &eyre::Report) -> Self {
+        self.error = Some(format!("{:#}", error));
+        self
+    }
+    fn into_new_call(self, tool: &str) -> NewCall {
+        NewCall {
+            provider: self.provider,
+            model: self.model,
+            modality: self.modality.as_str().to_string(),
+            input_tokens: self.input_tokens.map(|tokens| tokens as i32),
+            output_tokens: self.output_tokens.map(|tokens| tokens as i32),
+            latency_ms: self.latency.as_millis() as i64,
+            success: self.error.is_none(),
+            error: self.error,
+            tool: tool.to_string(),
+            prompt: self.prompt,
+            called_at: self.called_at,
+        }
+    }
+}
+
+/// Queues a record for the installed sink, if any.
+pub fn record(record: CallRecord) {
+    debug!(
+        "{} call to {} on {} took {:?} (tokens in {:?}, out {:?}, error {:?})",
+        record.modality.as_str(),
+        record.model,
+        record.provider,
+        record.latency,
+        record.input_tokens,
+        record.output_tokens,
+        record.error
+    );
+    let sink = SINK.lock().unwrap();
+    if let Some(sender) = sink.as_ref()
+        && sender.send(record).is_err()
+    {
+        warn!("Telemetry writer has stopped, dropping call record");
+    }
+}
+
+pub struct TelemetryHandle {
+    writer: JoinHandle<()>,
+}
+impl TelemetryHandle {
+    /// Stops accepting records and waits for queued ones to be written.
+    pub async fn flush(self) -> eyre::Result<()> {
+        SINK.lock().unwrap().take();
+        self.writer.await?;
+        Ok(())
+    }
+}
+
+pub async fn get_database_url() -> eyre::Result<String> {
+    let mut config = NanuakConfig::acquire().await?;
+    let password = config.get::<DatabasePassword>().await?;
+    Ok(DatabasePassword::format_url(&password))
+}
+
+async fn connect_postgres() -> eyre::Result<Pool<ConnectionManager<PgConnection>>> {
+    let manager = ConnectionManager::<PgConnection>::new(get_database_url().await?);
+    tokio::task::spawn_blocking(move || -> eyre::Result<_> {
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_timeout(Duration::from_secs(5))
+            .build(manager)?;
+        ensure_schema_current(&mut *pool.get()?)?;
+        Ok(pool)
+    })
+    .await?
+}
+
+/// Starts recording calls made by this process to `ai.calls`, attributed to `tool`.
+///
+/// Processes that never call a provider never connect. If connecting fails, recording is
+/// switched off with a warning and the process carries on.
+pub fn install_postgres(tool: &str) -> TelemetryHandle {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<CallRecord>();
+    let tool = tool.to_string();
+    let writer = tokio::spawn(async move {
+        let mut batch = Vec::new();
+        let mut pool = None;
+        while receiver.recv_many(&mut batch, MAX_BATCH).await > 0 {
+            let pool = match pool.as_ref() {
+                Some(pool) => pool,
+                None => match connect_postgres().await {
+                    Ok(connected) => pool.insert(connected),
+                    Err(error) => {
+                        warn!("Telemetry disabled, failed to connect: {:?}", error);
+                        SINK.lock().unwrap().take();
+                        return;
+                    }
+                },
+            };
+            let rows: Vec<NewCall> = batch
+                .drain(..)
+                .map(|record| record.into_new_call(&tool))
+                .collect();
+            let pool = pool.clone();
+            let result = tokio::task::spawn_blocking(move || -> eyre::Result<usize> {
+                use nanuak_schema::ai::calls::dsl::calls;
+                let mut conn = pool.get()?;
+                Ok(diesel::insert_into(calls)
+                    .values(&rows)
+                    .execute(&mut conn)?)
+            })
+            .await;
+            match result {
+                Ok(Ok(count)) => debug!("Wrote {} call records", count),
+                Ok(Err(error)) => warn!("Failed to write call records: {:?}", error),
+                Err(error) => warn!("Call record writer panicked: {:?}", error),
+            }
+        }
+    });
+    *SINK.lock().unwrap() = Some(sender);
+    TelemetryHandle { writer }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ReportBucket {
+    Hour,
+    Day,
+    Week,
+}
+impl ReportBucket {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReportBucket::Hour => "hour",
+            ReportBucket::Day => "day",
+            ReportBucket::Week => "week",
+        }
+    }
+}
+
+/// Aggregated calls for one model over one time bucket.
+#[derive(Debug, QueryableByName)]
+pub struct CallTrend {
+    #[diesel(sql_type = Text)]
+    pub provider: String,
+    #[diesel(sql_type = Text)]
+    pub model: String,
+    #[diesel(sql_type = Text)]
+    pub modality: String,
+    #[diesel(sql_type = Timestamp)]
+    pub bucket: NaiveDateTime,
+    #[diesel(sql_type = BigInt)]
+    pub calls: i64,
+    #[diesel(sql_type = BigInt)]
+    pub errors: i64,
+    #[diesel(sql_type = Double)]
+    pub mean_latency_ms: f64,
+    /// Output tokens (input tokens for embeddings) per second of successful call time.
+    #[diesel(sql_type = Nullable<Double>)]
+    pub tokens_per_second: Option<f64>,
+}
+impl CallTrend {
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.calls.max(1) as f64
+    }
+}
+
+pub fn call_trends(
+    conn: &mut PgConnection,
+    days: i32,
+    bucket: ReportBucket,
+) -> eyre::Result<Vec<CallTrend>> {
+    let trends = diesel::sql_query(
+        r#"
+        SELECT provider,
+               model,
+               modality,
+               date_trunc($1, called_at) AS bucket,
+               COUNT(*) AS calls,
+               COUNT(*) FILTER (WHERE NOT success) AS errors,
+               AVG(latency_ms)::float8 AS mean_latency_ms,
+               (SUM(COALESCE(output_tokens, input_tokens)) FILTER (WHERE success))::float8
+                   / NULLIF(SUM(latency_ms) FILTER (WHERE success), 0)::float8
+                   * 1000.0 AS tokens_per_second
+        FROM ai.calls
+        WHERE called_at >= (NOW() AT TIME ZONE 'UTC') - make_interval(days => $2)
+        GROUP BY provider, model, modality, bucket
+        ORDER BY model, provider, bucket
+        "#,
+    )
+    .bind::<Text, _>(bucket.as_str())
+    .bind::<Integer, _>(days)
+    .load(conn)?;
+    Ok(trends)
+}
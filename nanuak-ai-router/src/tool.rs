@@ -0,0 +1,85 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use eyre::Context;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// A request from the model to invoke one of the tools it was offered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// The part of a [`Tool`] that is shown to the model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+type ToolFuture = Pin<Box<dyn Future<Output = eyre::Result<serde_json::Value>> + Send>>;
+type ToolHandler = dyn Fn(serde_json::Value) -> ToolFuture + Send + Sync;
+
+#[derive(Clone)]
+pub struct Tool {
+    pub definition: ToolDefinition,
+    handler: Arc<ToolHandler>,
+}
+
+impl std::fmt::Debug for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tool")
+            .field("definition", &self.definition)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Tool {
+    /// Binds an async function to a tool whose parameter schema is derived from `Args`.
+    pub fn new<Args, Output, F, Fut>(
+        name: &str,
+        description: &str,
+        handler: F,
+    ) -> eyre::Result<Self>
+    where
+        Args: DeserializeOwned + JsonSchema + Send + 'static,
+        Output: Serialize + Send + 'static,
+        F: Fn(Args) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = eyre::Result<Output>> + Send + 'static,
+    {
+        let parameters = serde_json::to_value(schemars::schema_for!(Args))?;
+        let handler = Arc::new(handler);
+        let tool_name = name.to_string();
+        Ok(Tool {
+            definition: ToolDefinition {
+                name: name.to_string(),
+                description: description.to_string(),
+                parameters,
+            },
+            handler: Arc::new(move |arguments: serde_json::Value| -> ToolFuture {
+                let handler = handler.clone();
+                let tool_name = tool_name.clone();
+                Box::pin(async move {
+                    let args: Args = serde_json::from_value(arguments)
+                        .wrap_err_with(|| format!("Invalid arguments for tool {}", tool_name))?;
+                    let output = handler(args).await?;
+                    Ok(serde_json::to_value(output)?)
+                })
+            }),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub async fn call(&self, arguments: serde_json::Value) -> eyre::Result<serde_json::Value> {
+        (self.handler)(arguments).await
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+}
+impl Transcript {
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Renders the segments as WebVTT cues, readable by `vtt::prelude::WebVtt::from_str`.
+    pub fn to_webvtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n");
+        for segment in &self.segments {
+            let text = segment.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            // A blank line would end the cue early, and "-->" would start a new one.
+            let text = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+                .replace("-->", "->");
+            let _ = write!(
+                vtt,
+                "\n{} --> {}\n{}\n",
+                format_timestamp(segment.start),
+                format_timestamp(segment.end),
+                text
+            );
+        }
+        vtt
+    }
+}
+
+fn format_timestamp(time: Duration) -> String {
+    let millis = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use eyre::Context;
+    use eyre::eyre;
+    use vtt::prelude::WebVtt;
+
+    use super::Transcript;
+    use super::TranscriptSegment;
+
+    #[test]
+    fn renders_webvtt_cues() {
+        let transcript = Transcript {
+            segments: vec![
+                TranscriptSegment {
+                    start: Duration::from_millis(0),
+                    end: Duration::from_millis(2_500),
+                    text: " Hello there.".to_string(),
+                },
+                TranscriptSegment {
+                    start: Duration::from_millis(3_661_010),
+                    end: Duration::from_millis(3_662_000),
+                    text: "A --> B\n\nC".to_string(),
+                },
+            ],
+        };
+        assert_eq!(
+            transcript.to_webvtt(),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello there.\n\n01:01:01.010 --> 01:01:02.000\nA -> B\nC\n"
+        );
+        assert_eq!(transcript.text(), "Hello there. A --> B\n\nC");
+    }
+
+    #[test]
+    fn webvtt_round_trips_through_the_vtt_parser() -> eyre::Result<()> {
+        let transcript = Transcript {
+            segments: vec![
+                TranscriptSegment {
+                    start: Duration::from_millis(0),
+                    end: Duration::from_millis(2_500),
+                    text: " Hello there.".to_string(),
+                },
+                TranscriptSegment {
+                    start: Duration::from_millis(2_500),
+                    end: Duration::from_millis(3_000),
+                    text: "   ".to_string(),
+                },
+                TranscriptSegment {
+                    start: Duration::from_millis(3_661_010),
+                    end: Duration::from_millis(3_662_000),
+                    text: "General Kenobi --> you are\n\na bold one".to_string(),
+                },
+            ],
+        };
+        let vtt = WebVtt::from_str(&transcript.to_webvtt())
+            .wrap_err(eyre!("Failed to parse rendered VTT"))?;
+        let text = vtt.deduplicated_text();
+        assert!(text.contains("Hello there."), "{text}");
+        assert!(text.contains("General Kenobi -> you are"), "{text}");
+        assert!(text.contains("a bold one"), "{text}");
+        Ok(())
+    }
+}
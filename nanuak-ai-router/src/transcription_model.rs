@@ -0,0 +1,5 @@
+pub trait TranscriptionModel: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// File name of the weights inside the provider's model directory.
+    fn file_name(&self) -> &'static str;
+}
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use crate::attributes::Residency;
+use crate::transcript::Transcript;
+use crate::transcription_model::TranscriptionModel;
+use crate::transcription_request::TranscriptionRequest;
+
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn is_supported(&self, model: &dyn TranscriptionModel) -> eyre::Result<bool>;
+    async fn transcribe(
+        &self,
+        model: &dyn TranscriptionModel,
+        request: TranscriptionRequest,
+    ) -> eyre::Result<Transcript>;
+    fn get_residency(&self) -> Residency;
+}
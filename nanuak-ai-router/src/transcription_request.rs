@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct TranscriptionRequest {
+    pub audio: PathBuf,
+    /// Spoken language as an ISO 639-1 code; `None` lets the model detect it.
+    pub language: Option<String>,
+    /// Translate the speech to English instead of transcribing it verbatim.
+    pub translate: bool,
+}
+impl TranscriptionRequest {
+    pub fn new(audio: PathBuf) -> Self {
+        TranscriptionRequest {
+            audio,
+            language: None,
+            translate: false,
+        }
+    }
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+    pub fn with_translate(mut self, translate: bool) -> Self {
+        self.translate = translate;
+        self
+    }
+}
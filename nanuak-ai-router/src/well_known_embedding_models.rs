@@ -0,0 +1,16 @@
+use crate::embedding_model::EmbeddingModel;
+use crate::models::bge_m3_embedding_model::BgeM3EmbeddingModel;
+use strum::VariantArray;
+
+#[derive(Debug, Clone, Copy, VariantArray)]
+#[non_exhaustive]
+pub enum WellKnownEmbeddingModels {
+    BgeM3,
+}
+impl WellKnownEmbeddingModels {
+    pub fn get(&self) -> Box<dyn EmbeddingModel> {
+        match self {
+            WellKnownEmbeddingModels::BgeM3 => Box::new(BgeM3EmbeddingModel),
+        }
+    }
+}
@@ -2,15 +2,15 @@ use crate::embedding_provider::EmbeddingProvider;
 use crate::providers::ollama_embedding_provider::OllamaEmbeddingProvider;
 use strum::VariantArray;
 
-#[derive(Debug, VariantArray)]
+#[derive(Debug, Clone, Copy, VariantArray)]
 #[non_exhaustive]
 pub enum WellKnownEmbeddingProviders {
     Ollama,
 }
 impl WellKnownEmbeddingProviders {
-    pub fn get(&self) -> impl EmbeddingProvider {
+    pub fn get(&self) -> Box<dyn EmbeddingProvider> {
         match self {
-            WellKnownEmbeddingProviders::Ollama => OllamaEmbeddingProvider,
+            WellKnownEmbeddingProviders::Ollama => Box::new(OllamaEmbeddingProvider::default()),
         }
     }
 }
@@ -0,0 +1,27 @@
+use crate::generative_text_model::GenerativeTextModel;
+use crate::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+use crate::models::gemma2_9b_generative_text_model::Gemma2_9BGenerativeTextModel;
+use crate::models::llama3_2_vision_generative_text_model::Llama3_2VisionGenerativeTextModel;
+use crate::models::qwen2_5_vl_generative_text_model::Qwen2_5VLGenerativeTextModel;
+use strum::VariantArray;
+
+#[derive(Debug, Clone, Copy, VariantArray)]
+#[non_exhaustive]
+pub enum WellKnownGenerativeTextModels {
+    Gemma2_2B,
+    Gemma2_9B,
+    Llama3_2Vision,
+    Qwen2_5VL,
+}
+impl WellKnownGenerativeTextModels {
+    pub fn get(&self) -> Box<dyn GenerativeTextModel> {
+        match self {
+            WellKnownGenerativeTextModels::Gemma2_2B => Box::new(Gemma2_2BGenerativeTextModel),
+            WellKnownGenerativeTextModels::Gemma2_9B => Box::new(Gemma2_9BGenerativeTextModel),
+            WellKnownGenerativeTextModels::Llama3_2Vision => {
+                Box::new(Llama3_2VisionGenerativeTextModel)
+            }
+            WellKnownGenerativeTextModels::Qwen2_5VL => Box::new(Qwen2_5VLGenerativeTextModel),
+        }
+    }
+}
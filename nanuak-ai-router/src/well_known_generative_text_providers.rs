@@ -0,0 +1,23 @@
+use crate::generative_text_provider::GenerativeTextProvider;
+use crate::providers::http_generate_generative_text_provider::HttpGenerateGenerativeTextProvider;
+use crate::providers::ollama_generative_text_provider::OllamaGenerativeTextProvider;
+use strum::VariantArray;
+
+#[derive(Debug, Clone, Copy, VariantArray)]
+#[non_exhaustive]
+pub enum WellKnownGenerativeTextProviders {
+    Ollama,
+    HttpGenerate,
+}
+impl WellKnownGenerativeTextProviders {
+    pub fn get(&self) -> Box<dyn GenerativeTextProvider> {
+        match self {
+            WellKnownGenerativeTextProviders::Ollama => {
+                Box::new(OllamaGenerativeTextProvider::default())
+            }
+            WellKnownGenerativeTextProviders::HttpGenerate => {
+                Box::new(HttpGenerateGenerativeTextProvider::default())
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::models::whisper_base_en_transcription_model::WhisperBaseEnTranscriptionModel;
+use crate::models::whisper_large_v3_turbo_transcription_model::WhisperLargeV3TurboTranscriptionModel;
+use crate::transcription_model::TranscriptionModel;
+use strum::VariantArray;
+
+#[derive(Debug, Clone, Copy, VariantArray, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum WellKnownTranscriptionModels {
+    WhisperBaseEn,
+    WhisperLargeV3Turbo,
+}
+impl WellKnownTranscriptionModels {
+    pub fn get(&self) -> Box<dyn TranscriptionModel> {
+        match self {
+            WellKnownTranscriptionModels::WhisperBaseEn => {
+                Box::new(WhisperBaseEnTranscriptionModel)
+            }
+            WellKnownTranscriptionModels::WhisperLargeV3Turbo => {
+                Box::new(WhisperLargeV3TurboTranscriptionModel)
+            }
+        }
+    }
+}
@@ -1,8 +1,11 @@
+use std::path::Path;
+
 use eyre::OptionExt;
 use itertools::Itertools;
 use nanuak_ai_router::embedding::Embedding;
 use nanuak_ai_router::embedding_request::EmbeddingPayload;
 use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
 use strum::VariantNames;
 use tracing::info;
 use tracing::level_filters::LevelFilter;
@@ -39,6 +42,11 @@ async fn main() -> eyre::Result<()> {
         )
         .without_time()
         .init();
+    let _mock = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .install();
 
     let words = Word::VARIANTS;
     let word_embedding_request = words
@@ -47,7 +55,7 @@ async fn main() -> eyre::Result<()> {
     let query = "animal";
     let query_embedding_request = std::iter::once(EmbeddingPayload::Text(query.to_string()));
     let embeddings = Embedding::try_generate(
-        WellKnownEmbeddingStrategy::BestLocal,
+        WellKnownEmbeddingStrategy::Mock,
         query_embedding_request
             .chain(word_embedding_request)
             .collect_vec(),
@@ -68,9 +76,17 @@ async fn main() -> eyre::Result<()> {
         .map(|(word, (dist, _))| (word, dist))
         .collect_vec();
     info!("Query: {query}");
-    for (word, dist) in sorted_words {
+    for (word, dist) in &sorted_words {
         info!("{}: {}", word, dist);
     }
+    // The first eight variants are the animals.
+    let top = sorted_words
+        .iter()
+        .take(8)
+        .map(|(word, _)| **word)
+        .sorted()
+        .collect_vec();
+    assert_eq!(top, words[..8].iter().copied().sorted().collect_vec());
 
     Ok(())
 }
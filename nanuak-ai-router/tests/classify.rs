@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use nanuak_ai_router::classify::ClassLabel;
+use nanuak_ai_router::classify::ClassificationMode;
+use nanuak_ai_router::classify::ClassifyOptions;
+use nanuak_ai_router::classify::classify;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use nanuak_ai_router::providers::mock_registry::MockInstallation;
+
+async fn install_mocks() -> eyre::Result<(MockGenerativeTextProvider, [MockInstallation; 2])> {
+    let embeddings = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .install();
+    let llm = MockGenerativeTextProvider::new()
+        .with_rule(
+            "Toaster",
+            r#"{"ratings": [{"label": "appliance", "score": 9}, {"label": "Vehicle", "score": 2}]}"#,
+        )
+        .with_fallback(r#"{"ratings": [{"label": "animal", "score": 8}]}"#);
+    let generative_text = llm.clone().install();
+    Ok((llm, [embeddings, generative_text]))
+}
+
+fn options(mode: ClassificationMode) -> ClassifyOptions {
+    ClassifyOptions {
+        embedding_strategy: WellKnownEmbeddingStrategy::Mock,
+        generative_text_strategy: WellKnownGenerativeTextStrategy::Mock,
+        ..ClassifyOptions::new(mode)
+    }
+}
+
+#[tokio::test]
+async fn classifies_by_embedding_llm_and_both() -> eyre::Result<()> {
+    let (llm, _mocks) = install_mocks().await?;
+    let labels: Vec<ClassLabel> = ["animal", "vehicle", "appliance"]
+        .map(ClassLabel::from)
+        .to_vec();
+    let items = ["Dog", "Tractor", "Toaster"].map(String::from);
+
+    let results = classify(&items, &labels, &options(ClassificationMode::Embedding)).await?;
+    let assigned: Vec<&[String]> = results.iter().map(|r| r.assigned.as_slice()).collect();
+    assert_eq!(assigned, [["animal"], ["vehicle"], ["appliance"]]);
+    let total: f64 = results[0].scores.iter().map(|s| s.score).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(llm.conversations().is_empty());
+
+    // Multi-label with a strict per-label threshold keeps animal but not the others.
+    let strict = labels
+        .iter()
+        .cloned()
+        .map(|label| label.with_threshold(0.99))
+        .collect::<Vec<_>>();
+    let options_multi = options(ClassificationMode::Embedding).multi_label();
+    let results = classify(&items[..1], &strict, &options_multi).await?;
+    assert_eq!(results[0].assigned, ["animal"]);
+
+    let results = classify(&items[2..], &labels, &options(ClassificationMode::Llm)).await?;
+    assert_eq!(results[0].assigned, ["appliance"]);
+    assert_eq!(results[0].scores[0].llm_rating, Some(0.9));
+    assert!(results[0].scores[0].similarity.is_none());
+
+    let hybrid = ClassifyOptions {
+        shortlist: 2,
+        ..options(ClassificationMode::Hybrid)
+    };
+    let results = classify(&items[2..], &labels, &hybrid).await?;
+    assert_eq!(results[0].assigned, ["appliance"]);
+    let conversations = llm.conversations();
+    let prompt = &conversations.last().unwrap().messages[1].content;
+    assert!(prompt.contains("- appliance") && !prompt.contains("- animal"));
+    Ok(())
+}
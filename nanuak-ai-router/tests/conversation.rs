@@ -0,0 +1,37 @@
+use nanuak_ai_router::conversation::Conversation;
+use nanuak_ai_router::conversation::Message;
+use nanuak_ai_router::conversation::Role;
+use nanuak_ai_router::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+
+#[tokio::test]
+async fn failed_turn_leaves_history_unchanged() -> eyre::Result<()> {
+    let mut conversation = Conversation::new().with_system_prompt("Be brief.".to_string());
+    let failing = MockGenerativeTextProvider::new();
+    let result = conversation
+        .continue_with(
+            &failing,
+            &Gemma2_2BGenerativeTextModel,
+            Message::user("Hi".to_string()),
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(conversation.messages.len(), 1);
+
+    let answering = MockGenerativeTextProvider::new().with_reply("Hello!");
+    let answer = conversation
+        .continue_with(
+            &answering,
+            &Gemma2_2BGenerativeTextModel,
+            Message::user("Hi".to_string()),
+        )
+        .await?;
+    assert_eq!(answer.body, "Hello!");
+    let roles = conversation
+        .messages
+        .iter()
+        .map(|m| m.role)
+        .collect::<Vec<_>>();
+    assert_eq!(roles, vec![Role::System, Role::User, Role::Assistant]);
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use nanuak_ai_router::embedding::Embedding;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn similarity_is_one_for_the_same_direction() -> eyre::Result<()> {
+    let a = Embedding(vec![1.0, 2.0, 3.0]);
+    let b = Embedding(vec![2.0, 4.0, 6.0]);
+    assert_close(a.cosine_similarity(&b)?, 1.0);
+    assert_close(a.cosine_distance(&b)?, 0.0);
+    Ok(())
+}
+
+#[test]
+fn similarity_is_zero_for_orthogonal_and_negative_for_opposite() -> eyre::Result<()> {
+    let x = Embedding(vec![1.0, 0.0]);
+    let y = Embedding(vec![0.0, 1.0]);
+    let minus_x = Embedding(vec![-1.0, 0.0]);
+    assert_close(x.cosine_similarity(&y)?, 0.0);
+    assert_close(x.cosine_similarity(&minus_x)?, -1.0);
+    assert_close(x.cosine_distance(&minus_x)?, 2.0);
+    Ok(())
+}
+
+#[test]
+fn closer_vectors_are_more_similar() -> eyre::Result<()> {
+    let query = Embedding(vec![1.0, 0.0, 0.0]);
+    let near = Embedding(vec![0.9, 0.1, 0.1]);
+    let far = Embedding(vec![0.1, 0.9, 0.3]);
+    assert!(query.cosine_similarity(&near)? > query.cosine_similarity(&far)?);
+    assert!(query.cosine_distance(&near)? < query.cosine_distance(&far)?);
+    Ok(())
+}
+
+#[test]
+fn mismatched_lengths_are_an_error() {
+    let a = Embedding(vec![1.0, 0.0]);
+    let b = Embedding(vec![1.0, 0.0, 0.0]);
+    assert!(a.cosine_similarity(&b).is_err());
+}
@@ -0,0 +1,118 @@
+use diesel::PgConnection;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use nanuak_ai_router::embedding::Embedding;
+use nanuak_ai_router::embedding_store::EmbeddingStore;
+use nanuak_ai_router::embedding_store::StoredEmbedding;
+use nanuak_ai_router::metadata_filter::MetadataFilter;
+use nanuak_ai_router::stores::memory_embedding_store::MemoryEmbeddingStore;
+use nanuak_ai_router::stores::pgvector_embedding_store::PgVectorCollection;
+use nanuak_ai_router::stores::pgvector_embedding_store::PgVectorEmbeddingStore;
+
+/// The behaviour every backend must share.
+async fn exercise(store: &dyn EmbeddingStore) -> eyre::Result<()> {
+    store
+        .upsert(vec![
+            StoredEmbedding::new("cat", Embedding(vec![1.0, 0.1, 0.0]))
+                .with_metadata("kind", "animal"),
+            StoredEmbedding::new("dog", Embedding(vec![0.9, 0.3, 0.0]))
+                .with_metadata("kind", "animal"),
+            StoredEmbedding::new("car", Embedding(vec![0.0, 0.2, 1.0]))
+                .with_metadata("kind", "vehicle")
+                .with_metadata("wheels", 4),
+        ])
+        .await?;
+    assert_eq!(store.count().await?, 3);
+
+    let query = Embedding(vec![1.0, 0.0, 0.0]);
+    let ids = |matches: Vec<nanuak_ai_router::embedding_store::EmbeddingMatch>| {
+        matches.into_iter().map(|m| m.id).collect::<Vec<_>>()
+    };
+    let all = store.top_k(&query, 2, &MetadataFilter::new()).await?;
+    assert!(all[0].score > all[1].score);
+    assert_eq!(ids(all), vec!["cat", "dog"]);
+
+    let vehicles = MetadataFilter::new().eq("kind", "vehicle");
+    let matches = store.top_k(&query, 5, &vehicles).await?;
+    assert_eq!(matches[0].metadata["wheels"], 4);
+    assert_eq!(ids(matches), vec!["car"]);
+
+    let either = MetadataFilter::new().one_of("kind", ["vehicle", "plant"]);
+    assert_eq!(ids(store.top_k(&query, 5, &either).await?), vec!["car"]);
+
+    // Upserting replaces the embedding and metadata.
+    store
+        .upsert(vec![
+            StoredEmbedding::new("car", Embedding(vec![1.0, 0.0, 0.0]))
+                .with_metadata("kind", "toy"),
+        ])
+        .await?;
+    assert_eq!(
+        ids(store.top_k(&query, 1, &MetadataFilter::new()).await?),
+        vec!["car"]
+    );
+    assert!(store.top_k(&query, 5, &vehicles).await?.is_empty());
+
+    // Dimension mismatches are errors, not empty results.
+    let flat = Embedding(vec![1.0, 0.0]);
+    assert!(
+        store
+            .upsert(vec![StoredEmbedding::new("flat", flat.clone())])
+            .await
+            .is_err()
+    );
+    assert!(store.top_k(&flat, 5, &MetadataFilter::new()).await.is_err());
+
+    let deleted = store
+        .delete(&["cat".to_string(), "dog".to_string(), "cow".to_string()])
+        .await?;
+    assert_eq!(deleted, 2);
+    assert_eq!(store.count().await?, 1);
+    store.delete(&["car".to_string()]).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_store() -> eyre::Result<()> {
+    let store = MemoryEmbeddingStore::new();
+    exercise(&store).await?;
+    assert!(
+        store
+            .upsert(vec![
+                StoredEmbedding::new("x", Embedding(vec![1.0])),
+                StoredEmbedding::new("y", Embedding(vec![1.0, 0.0])),
+            ])
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn table_collections_reject_metadata() -> eyre::Result<()> {
+    // Never connects; the checks run before any query.
+    let manager = ConnectionManager::<PgConnection>::new("postgres://localhost/unused");
+    let store = PgVectorEmbeddingStore::new(
+        Pool::builder().build_unchecked(manager),
+        PgVectorCollection::Files {
+            model: "clip-vit-base-patch32".to_string(),
+        },
+    );
+    let record = StoredEmbedding::new("a.png", Embedding(vec![1.0])).with_metadata("kind", "cat");
+    assert!(store.upsert(vec![record]).await.is_err());
+    let filter = MetadataFilter::new().eq("kind", "cat");
+    assert!(
+        store
+            .top_k(&Embedding(vec![1.0]), 1, &filter)
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Postgres with pgvector and the ai.embeddings migration"]
+async fn pgvector_store() -> eyre::Result<()> {
+    let store = PgVectorEmbeddingStore::connect("nanuak-ai-router-test").await?;
+    exercise(&store).await
+}
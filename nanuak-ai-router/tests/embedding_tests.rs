@@ -1,22 +1,48 @@
 use nanuak_ai_router::embedding::Embedding;
 use nanuak_ai_router::embedding_request::EmbeddingPayload;
 use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_ai_router::providers::mock_registry::MockInstallation;
 
 #[tokio::test]
 async fn it_works() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::default().install();
     let x = "howdy";
     let embeddings = Embedding::try_generate(
-        WellKnownEmbeddingStrategy::BestLocal,
+        WellKnownEmbeddingStrategy::Mock,
         vec![EmbeddingPayload::Text(x.to_string())],
     )
     .await?;
     assert_eq!(embeddings.len(), 1);
     assert_eq!(
         vec![embeddings.first().unwrap().0.len() as u16],
-        WellKnownEmbeddingStrategy::BestLocal
+        WellKnownEmbeddingStrategy::Mock
             .get_model()
             .get_embedding_space()
             .get_dimensions()
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn fails_without_installed_mock() {
+    let _nothing = MockInstallation::hold();
+    let result = Embedding::try_generate(
+        WellKnownEmbeddingStrategy::Mock,
+        vec![EmbeddingPayload::Text("howdy".to_string())],
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn mock_is_visible_from_worker_threads() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::new(8).install();
+    let embeddings = tokio::spawn(Embedding::try_generate(
+        WellKnownEmbeddingStrategy::Mock,
+        vec![EmbeddingPayload::Text("howdy".to_string())],
+    ))
+    .await??;
+    assert_eq!(embeddings[0].dimensions(), 8);
+    Ok(())
+}
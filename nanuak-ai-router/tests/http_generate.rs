@@ -0,0 +1,70 @@
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::State;
+use axum::routing::post;
+use nanuak_ai_router::generative_text_provider::GenerativeTextProvider;
+use nanuak_ai_router::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+use nanuak_ai_router::models::qwen2_5_vl_generative_text_model::Qwen2_5VLGenerativeTextModel;
+use nanuak_ai_router::providers::http_generate_generative_text_provider::HttpGenerateGenerativeTextProvider;
+use nanuak_ai_router::question::Question;
+use serde_json::Value;
+use serde_json::json;
+
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+/// Stands in for nanuak-qwen's `/generate`.
+async fn fake_generate(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+    requests.lock().unwrap().push(body);
+    Json(json!({ "response": "A cat sitting in a box." }))
+}
+
+#[tokio::test]
+async fn sends_images_in_chat_template_format() -> eyre::Result<()> {
+    let requests: Requests = Default::default();
+    let app = Router::new()
+        .route("/generate", post(fake_generate))
+        .with_state(requests.clone());
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(axum::Server::from_tcp(listener)?.serve(app.into_make_service()));
+
+    let image = std::env::temp_dir().join(format!("nanuak-http-generate-{}.png", addr.port()));
+    tokio::fs::write(&image, b"not really a png").await?;
+
+    let provider = HttpGenerateGenerativeTextProvider {
+        base_url: format!("http://{}", addr),
+        ..Default::default()
+    };
+    assert!(provider.is_supported(&Qwen2_5VLGenerativeTextModel).await?);
+    assert!(!provider.is_supported(&Gemma2_2BGenerativeTextModel).await?);
+
+    let question =
+        Question::new("Describe this image.".to_string()).with_images(vec![image.clone()]);
+    let answer = provider
+        .answer_question(&Qwen2_5VLGenerativeTextModel, question)
+        .await?;
+    tokio::fs::remove_file(&image).await?;
+    assert_eq!(answer.body, "A cat sitting in a box.");
+    assert_eq!(
+        answer.prompt.map(|prompt| prompt.to_string()).as_deref(),
+        Some("question@1")
+    );
+
+    let requests = requests.lock().unwrap();
+    let content = &requests[0]["messages"][0]["content"];
+    assert_eq!(requests[0]["messages"][0]["role"], "user");
+    assert_eq!(content[0]["type"], "image");
+    assert_eq!(
+        content[0]["image"],
+        "data:image/png;base64,bm90IHJlYWxseSBhIHBuZw=="
+    );
+    assert_eq!(
+        content[1],
+        json!({"type": "text", "text": "<question>\nDescribe this image.\n</question>"})
+    );
+    Ok(())
+}
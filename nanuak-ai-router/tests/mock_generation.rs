@@ -0,0 +1,83 @@
+use nanuak_ai_router::answer::Answer;
+use nanuak_ai_router::conversation::Conversation;
+use nanuak_ai_router::conversation::Message;
+use nanuak_ai_router::generative_text_provider::GenerativeTextProvider;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use nanuak_ai_router::question::Question;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[tokio::test]
+async fn mock_strategy_replays_script() -> eyre::Result<()> {
+    let mock = MockGenerativeTextProvider::new()
+        .with_reply("first")
+        .with_rule("sky", "Rayleigh scattering")
+        .with_fallback("I don't know");
+    let _mock = mock.clone().install();
+
+    let ask = |text: &str| Conversation::new().with_message(Message::user(text.to_string()));
+    let strategy = WellKnownGenerativeTextStrategy::Mock;
+    assert_eq!(
+        Answer::try_generate(strategy, &ask("hi")).await?.body,
+        "first"
+    );
+    assert_eq!(
+        Answer::try_generate(strategy, &ask("Why is the sky blue?"))
+            .await?
+            .body,
+        "Rayleigh scattering"
+    );
+    assert_eq!(
+        Answer::try_generate(strategy, &ask("?")).await?.body,
+        "I don't know"
+    );
+    assert_eq!(mock.conversations().len(), 3);
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct Tags {
+    tags: Vec<String>,
+}
+
+#[tokio::test]
+async fn strategy_answers_structured() -> eyre::Result<()> {
+    let mock = MockGenerativeTextProvider::new()
+        .with_reply("{\"tag\": \"cat\"}")
+        .with_reply("{\"tags\": [\"cat\"]}");
+    let _mock = mock.clone().install();
+
+    let conversation = Conversation::new().with_message(Message::user("Tag this".to_string()));
+    let tags: Tags =
+        Answer::try_generate_structured(WellKnownGenerativeTextStrategy::Mock, &conversation)
+            .await?;
+    assert_eq!(tags.tags, vec!["cat".to_string()]);
+    // The invalid first reply was retried with the validation error.
+    assert_eq!(mock.conversations().len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn questions_use_the_question_template() -> eyre::Result<()> {
+    let mock = MockGenerativeTextProvider::new().with_fallback("Blue");
+    let question =
+        Question::new("What colour?".to_string()).with_context(vec!["The sky".to_string()]);
+    let answer = mock
+        .answer_question(&Gemma2_2BGenerativeTextModel, question)
+        .await?;
+    assert_eq!(answer.body, "Blue");
+    assert_eq!(
+        answer.prompt.map(|prompt| prompt.name).as_deref(),
+        Some("question")
+    );
+    let asked = &mock.conversations()[0];
+    assert!(
+        asked
+            .messages
+            .iter()
+            .any(|message| message.content.contains("The sky"))
+    );
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use nanuak_ai_router::attributes::Residency;
+use nanuak_ai_router::models::bge_m3_embedding_model::BgeM3EmbeddingModel;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_ai_router::rerank_provider::RerankProvider;
+use nanuak_ai_router::retrieval_eval::EvalHistory;
+use nanuak_ai_router::retrieval_eval::EvalOptions;
+use nanuak_ai_router::retrieval_eval::EvalSet;
+use nanuak_ai_router::retrieval_eval::changes_since_previous;
+use nanuak_ai_router::retrieval_eval::evaluate_retrieval;
+
+/// Prefers shorter documents, to check the reranked order is what gets scored.
+struct ShortestFirst;
+#[async_trait]
+impl RerankProvider for ShortestFirst {
+    fn name(&self) -> &str {
+        "shortest-first"
+    }
+    async fn score(&self, _query: &str, documents: &[String]) -> eyre::Result<Vec<f32>> {
+        Ok(documents.iter().map(|d| -(d.len() as f32)).collect())
+    }
+    fn get_residency(&self) -> Residency {
+        Residency::Local
+    }
+}
+
+#[tokio::test]
+async fn evaluates_and_compares_runs() -> eyre::Result<()> {
+    let provider = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "tests/fixtures/animal_embeddings.json",
+    ))
+    .await?;
+    let set = EvalSet::load(Path::new("tests/fixtures/animals_eval.jsonl")).await?;
+    assert_eq!(
+        (set.name.as_str(), set.documents.len()),
+        ("animals_eval", 15)
+    );
+    let options = EvalOptions {
+        k_values: vec![1, 4, 8],
+        rerank_top_n: 8,
+        ..Default::default()
+    };
+
+    let report = evaluate_retrieval(&set, &provider, &BgeM3EmbeddingModel, None, &options).await?;
+    assert_eq!(report.recall[&8], 1.0);
+    assert_eq!(report.mrr, 1.0);
+    assert!(report.ndcg[&4] > 0.9);
+
+    let reranked = evaluate_retrieval(
+        &set,
+        &provider,
+        &BgeM3EmbeddingModel,
+        Some(&ShortestFirst),
+        &options,
+    )
+    .await?;
+    assert_eq!(reranked.reranker.as_deref(), Some("shortest-first"));
+    assert!(reranked.ndcg[&4] < report.ndcg[&4]);
+
+    let dir = std::env::temp_dir().join(format!("nanuak-evals-{}", std::process::id()));
+    let history = EvalHistory {
+        path: dir.join("evals.jsonl"),
+    };
+    history.append(&report).await?;
+    history.append(&reranked).await?;
+    history.append(&report).await?;
+    let reports = history.load(Some("animals_eval")).await?;
+    std::fs::remove_dir_all(&dir)?;
+    assert_eq!(reports.len(), 3);
+    let changes = changes_since_previous(&reports);
+    assert!(changes[0].is_none() && changes[1].is_none());
+    assert!(
+        changes[2]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|(_, delta)| *delta == 0.0)
+    );
+    Ok(())
+}
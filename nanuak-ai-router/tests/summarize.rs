@@ -0,0 +1,57 @@
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use nanuak_ai_router::summarize::SummarizeOptions;
+use nanuak_ai_router::summarize::summarize;
+
+const TRANSCRIPT: &str = "\
+Wolves hunt in packs across the tundra.
+Honeybees dance to share where flowers are.
+Owls fly silently thanks to soft feathers.
+Salmon swim upstream to spawn each year.
+Ants farm aphids for their sweet honeydew.
+";
+
+#[tokio::test]
+async fn summarizes_hierarchically_with_provenance() -> eyre::Result<()> {
+    let llm = MockGenerativeTextProvider::new()
+        .with_rule("<summaries>", "Animals cooperate and migrate [0] [3].")
+        .with_rule("Wolves", "Wolves hunt together in packs [0].")
+        .with_rule("Honeybees", "Bees dance to point at flowers [1].")
+        .with_rule("Owls", "Owls have feathers for silent flight [2].")
+        .with_rule("Salmon", "Salmon swim upstream to spawn [3].")
+        .with_rule("Ants", "Ants farm aphids for honeydew [4].");
+    let _mock = llm.clone().install();
+
+    let options = SummarizeOptions {
+        strategy: WellKnownGenerativeTextStrategy::Mock,
+        chunk_tokens: Some(12),
+        concurrency: 2,
+        ..SummarizeOptions::default()
+    }
+    .with_focus("how animals behave");
+    let summary = summarize(TRANSCRIPT, &options).await?;
+
+    assert_eq!(summary.chunks.len(), 5);
+    assert_eq!(
+        summary.chunk_summaries[1],
+        "Bees dance to point at flowers [1]."
+    );
+    assert_eq!(summary.text, "Animals cooperate and migrate [0] [3].");
+    assert_eq!(summary.sources, [0, 3]);
+    assert!(summary.levels > 2, "levels: {}", summary.levels);
+    let sources: Vec<&str> = summary
+        .source_chunks()
+        .map(|chunk| &TRANSCRIPT[chunk.start..chunk.end])
+        .collect();
+    assert!(sources[1].starts_with("Salmon"));
+
+    let conversations = llm.conversations();
+    assert!(conversations.len() > 6);
+    assert!(conversations.iter().all(|conversation| {
+        conversation
+            .messages
+            .last()
+            .is_some_and(|message| message.content.contains("how animals behave"))
+    }));
+    Ok(())
+}
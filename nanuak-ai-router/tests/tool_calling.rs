@@ -0,0 +1,96 @@
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::State;
+use axum::routing::post;
+use nanuak_ai_router::agent::Agent;
+use nanuak_ai_router::conversation::Conversation;
+use nanuak_ai_router::conversation::Message;
+use nanuak_ai_router::conversation::Role;
+use nanuak_ai_router::models::gemma2_2b_generative_text_model::Gemma2_2BGenerativeTextModel;
+use nanuak_ai_router::providers::ollama_generative_text_provider::OllamaGenerativeTextProvider;
+use nanuak_ai_router::providers::ollama_instance::OllamaInstance;
+use nanuak_ai_router::tool::Tool;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+/// Stands in for Ollama's `/api/chat`: asks for a tool call first, then answers with its result.
+async fn fake_chat(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+    requests.lock().unwrap().push(body.clone());
+    let messages = body["messages"].as_array().cloned().unwrap_or_default();
+    let tool_result = messages
+        .iter()
+        .find(|message| message["role"] == "tool")
+        .map(|message| message["content"].as_str().unwrap_or_default().to_string());
+    let message = match tool_result {
+        None => json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{"function": {"name": "count_videos", "arguments": {"channel": "Vsauce"}}}]
+        }),
+        Some(result) => json!({
+            "role": "assistant",
+            "content": format!("You watched {} videos.", result)
+        }),
+    };
+    Json(json!({ "model": body["model"], "message": message, "done": true }))
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CountVideosArgs {
+    channel: String,
+}
+
+#[tokio::test]
+async fn agent_executes_tool_calls_until_final_answer() -> eyre::Result<()> {
+    let requests: Requests = Default::default();
+    let app = Router::new()
+        .route("/api/chat", post(fake_chat))
+        .with_state(requests.clone());
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(axum::Server::from_tcp(listener)?.serve(app.into_make_service()));
+    let provider =
+        OllamaGenerativeTextProvider::new(OllamaInstance::new("fake", format!("http://{}", addr)));
+
+    let tool = Tool::new(
+        "count_videos",
+        "Count watched videos for a channel",
+        |args: CountVideosArgs| async move {
+            eyre::ensure!(args.channel == "Vsauce", "unexpected channel");
+            Ok(42)
+        },
+    )?;
+    let mut conversation = Conversation::new().with_message(Message::user(
+        "How many Vsauce videos have I watched?".to_string(),
+    ));
+    let answer = Agent::new(vec![tool])
+        .run(&provider, &Gemma2_2BGenerativeTextModel, &mut conversation)
+        .await?;
+
+    assert_eq!(answer.body, "You watched 42 videos.");
+    let roles = conversation
+        .messages
+        .iter()
+        .map(|m| m.role)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        vec![Role::User, Role::Assistant, Role::Tool, Role::Assistant]
+    );
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["tools"][0]["function"]["name"], "count_videos");
+    assert_eq!(
+        requests[0]["tools"][0]["function"]["parameters"]["properties"]["channel"]["type"],
+        "string"
+    );
+    Ok(())
+}
@@ -1,10 +1,12 @@
 use cloud_terrastodon_core_user_input::prelude::Choice;
 use cloud_terrastodon_core_user_input::prelude::FzfArgs;
-use cloud_terrastodon_core_user_input::prelude::pick_many;
 use eyre::Result;
 use futures::stream::StreamExt;
 use futures::stream::{self};
 use itertools::Itertools;
+use nanuak_ai_router::telemetry::install_postgres;
+use nanuak_picking::tui;
+use nanuak_picking::tui::TuiOptions;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::env;
@@ -44,10 +46,10 @@ async fn scan_subdirs(dir: PathBuf, tx: UnboundedSender<PathBuf>) -> Result<()>
         .for_each_concurrent(None, |entry| {
             let tx = tx.clone();
             async move {
-                if let Ok(ft) = entry.file_type().await {
-                    if ft.is_dir() {
-                        let _ = tx.send(entry.path());
-                    }
+                if let Ok(ft) = entry.file_type().await
+                    && ft.is_dir()
+                {
+                    let _ = tx.send(entry.path());
                 }
             }
         })
@@ -215,21 +217,35 @@ pub async fn get_crate_paths() -> eyre::Result<Vec<CrateEntry>> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let telemetry = install_postgres("nanuak-cargo-home");
+    let result = run().await;
+    if let Err(error) = telemetry.flush().await {
+        eprintln!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run() -> Result<()> {
     let crates = get_crate_paths().await?;
     println!("Total unique crate directories found: {}", crates.len());
 
-    // 7. Let the user select crates with fzf.
-    let chosen_crates = pick_many(FzfArgs {
-        header: Some(format!("Found {} unique crates", crates.len())),
-        prompt: Some("Crate to summarize: ".to_string()),
-        choices: crates
-            .into_iter()
-            .map(|ce| Choice {
-                key: ce.path.to_string_lossy().to_string(),
-                value: ce.path.clone(),
-            })
-            .collect_vec(),
-    })?;
+    // 7. Let the user select crates.
+    let chosen_crates = tui::pick_many_with_options(
+        FzfArgs {
+            header: Some(format!("Found {} unique crates", crates.len())),
+            prompt: Some("Crate to summarize: ".to_string()),
+            choices: crates
+                .into_iter()
+                .map(|ce| Choice {
+                    key: ce.path.to_string_lossy().to_string(),
+                    value: ce.path.clone(),
+                })
+                .collect_vec(),
+        },
+        &TuiOptions::default().learning()?,
+    )
+    .await?
+    .into_values();
 
     for crate_path in chosen_crates {
         println!("Selected crate: {:?}", crate_path);
@@ -65,6 +65,27 @@ impl<P: SecretProvider> NanuakConfig<P> {
         }
     }
 
+    /// Retrieves the configuration value for the given entry if one has been saved,
+    /// without consulting the secret provider.
+    pub fn get_optional<T: ConfigField>(&self) -> eyre::Result<Option<T::Value>>
+    where
+        T::Value: DeserializeOwned,
+    {
+        let Some(val) = self
+            .inner
+            .get(T::key())
+            .and_then(|entry| entry.as_table())
+            .and_then(|table| table.get("value"))
+        else {
+            return Ok(None);
+        };
+        let value = T::Value::deserialize(val.clone()).wrap_err(format!(
+            "Failed to deserialize configuration value for {}",
+            T::key()
+        ))?;
+        Ok(Some(value))
+    }
+
     /// Sets the configuration value for the given entry.
     pub async fn set<T: ConfigField>(&mut self, value: &T::Value) -> eyre::Result<()>
     where
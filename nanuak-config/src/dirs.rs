@@ -23,3 +23,8 @@ pub async fn get_config_path() -> Result<PathBuf> {
         })?;
     Ok(config_dir.join("config.toml"))
 }
+
+/// Where user prompt templates live, one `.toml` file per template.
+pub fn get_prompts_dir() -> Result<PathBuf> {
+    Ok(get_project_dirs()?.config_dir().join("prompts"))
+}
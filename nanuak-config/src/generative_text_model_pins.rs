@@ -0,0 +1,28 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config_entry::ConfigField;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedModel {
+    /// Model name as the provider knows it, e.g. `qwen2.5:14b`
+    pub model: String,
+    #[serde(default)]
+    pub context_size: Option<u32>,
+}
+
+/// Overrides the model chosen by a generative text strategy, keyed by strategy name.
+///
+/// ```toml
+/// [GENERATIVE_TEXT_MODEL_PINS.value]
+/// BestLocal = { model = "qwen2.5:14b", context_size = 32768 }
+/// ```
+pub struct GenerativeTextModelPins;
+impl ConfigField for GenerativeTextModelPins {
+    type Value = BTreeMap<String, PinnedModel>;
+    fn key() -> &'static str {
+        "GENERATIVE_TEXT_MODEL_PINS"
+    }
+}
@@ -4,6 +4,10 @@ pub mod db_url;
 pub mod default_secret_provider;
 pub mod dirs;
 pub mod env_secret_provider;
+pub mod generative_text_model_pins;
 pub mod my_1password_secret_provider;
+pub mod pick_thresholds;
+pub mod provider_instances;
+pub mod resilience_policies;
 pub mod secret_provider;
 pub mod youtube_api_key;
@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config_entry::ConfigField;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PickThresholdValues {
+    /// Keyed by embedding model name, e.g. `bge-m3:latest`
+    #[serde(default)]
+    pub models: BTreeMap<String, f64>,
+    /// For models without a calibrated threshold; the picker's built-in default when unset.
+    #[serde(default)]
+    pub fallback: Option<f64>,
+}
+
+/// Calibrated similarity thresholds for semantic picks, written by `nanuak-picking calibrate`.
+///
+/// ```toml
+/// [PICK_THRESHOLDS.value]
+/// fallback = 0.6
+///
+/// [PICK_THRESHOLDS.value.models]
+/// "bge-m3:latest" = 0.72
+/// ```
+pub struct PickThresholds;
+impl ConfigField for PickThresholds {
+    type Value = PickThresholdValues;
+    fn key() -> &'static str {
+        "PICK_THRESHOLDS"
+    }
+}
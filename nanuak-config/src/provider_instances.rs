@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config_entry::ConfigField;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    Ollama,
+    HttpGenerate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderInstance {
+    /// Shown in logs and telemetry, e.g. `gpu-box`
+    pub name: String,
+    pub kind: ProviderKind,
+    /// Host name or URL, e.g. `gpu-box.lan` or `https://ollama.example.com`
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Sent verbatim as the `Authorization` header, e.g. `Bearer abc123`
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// How long Ollama keeps the model loaded after a call, e.g. `30m` or `-1`
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// Context window Ollama allocates for each call
+    #[serde(default)]
+    pub num_ctx: Option<u32>,
+    /// The one model an `http-generate` server has loaded
+    #[serde(default)]
+    pub served_model: Option<String>,
+    /// Whether calls leave machines you control
+    #[serde(default)]
+    pub remote: bool,
+}
+impl ProviderInstance {
+    /// The base URL for requests, defaulting to `http` when `host` has no scheme.
+    pub fn base_url(&self) -> String {
+        let host = self.host.trim_end_matches('/');
+        let host = if host.contains("://") {
+            host.to_string()
+        } else {
+            format!("http://{}", host)
+        };
+        match self.port {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        }
+    }
+}
+
+/// Provider endpoints to route to, tried in the order listed.
+///
+/// When unset, the default local Ollama and `http-generate` endpoints are used.
+///
+/// ```toml
+/// [[PROVIDER_INSTANCES.value]]
+/// name = "gpu-box"
+/// kind = "ollama"
+/// host = "gpu-box.lan"
+/// port = 11434
+/// keep_alive = "30m"
+/// num_ctx = 16384
+///
+/// [[PROVIDER_INSTANCES.value]]
+/// name = "laptop"
+/// kind = "ollama"
+/// host = "127.0.0.1"
+/// port = 11434
+/// ```
+pub struct ProviderInstances;
+impl ConfigField for ProviderInstances {
+    type Value = Vec<ProviderInstance>;
+    fn key() -> &'static str {
+        "PROVIDER_INSTANCES"
+    }
+}
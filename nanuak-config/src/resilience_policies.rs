@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config_entry::ConfigField;
+
+/// Fields left unset keep the strategy's built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResiliencePolicyOverride {
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    #[serde(default)]
+    pub initial_backoff_ms: Option<u64>,
+    #[serde(default)]
+    pub max_backoff_ms: Option<u64>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    #[serde(default)]
+    pub min_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub failure_threshold: Option<u32>,
+    #[serde(default)]
+    pub cooldown_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResiliencePolicyOverrides {
+    /// Keyed by embedding strategy name, e.g. `BestLocal`
+    #[serde(default)]
+    pub embedding: BTreeMap<String, ResiliencePolicyOverride>,
+    /// Keyed by generative text strategy name, e.g. `Fast`
+    #[serde(default)]
+    pub generative_text: BTreeMap<String, ResiliencePolicyOverride>,
+}
+
+/// Retry, timeout, rate limit and circuit breaker settings per strategy.
+///
+/// ```toml
+/// [RESILIENCE_POLICIES.value.generative_text.BestLocal]
+/// timeout_ms = 600000
+/// max_attempts = 2
+/// ```
+pub struct ResiliencePolicies;
+impl ConfigField for ResiliencePolicies {
+    type Value = ResiliencePolicyOverrides;
+    fn key() -> &'static str {
+        "RESILIENCE_POLICIES"
+    }
+}
@@ -8,6 +8,7 @@ use clap::Parser;
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
+use nanuak_schema::migrations::ensure_schema_current;
 use process_file::process_file;
 use tracing::debug;
 use tracing::info;
@@ -40,6 +41,7 @@ fn main() -> eyre::Result<()> {
     let manager = ConnectionManager::<PgConnection>::new(&database_url);
     let pool = Pool::builder().build(manager)?;
     let mut conn = pool.get()?;
+    ensure_schema_current(&mut conn)?;
 
     // Gather file paths
     let file_paths = args.get_file_paths()?;
@@ -4,6 +4,7 @@ use diesel::r2d2::Pool;
 use eyre::Context;
 use nanuak_config::config::NanuakConfig;
 use nanuak_config::db_url::DatabasePassword;
+use nanuak_schema::migrations::ensure_schema_current;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -19,6 +20,7 @@ impl AppState {
         let pool = Pool::builder()
             .build(manager)
             .wrap_err("Failed building database pool")?;
+        ensure_schema_current(&mut *pool.get()?)?;
 
         Ok(Self { pool })
     }
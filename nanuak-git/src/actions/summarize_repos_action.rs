@@ -17,7 +17,7 @@ pub async fn summarize_repos_action(
 
     info!("Summarizing repo: {:?}", repo);
 
-    let remote = pick_remote(&repo.remotes)?;
+    let remote = pick_remote(&repo.remotes).await?;
     let github_details = fetch_github_repo_details(&remote.url).await?;
 
     let manifest = RepoManifest {
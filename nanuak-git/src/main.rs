@@ -18,8 +18,11 @@ use color_eyre::eyre::Result;
 use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
 use get_database_url::get_database_url;
+use nanuak_ai_router::telemetry::install_postgres;
+use nanuak_schema::migrations::ensure_schema_current;
 use r2d2::Pool;
 use std::path::PathBuf;
+use tracing::warn;
 use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
@@ -70,8 +73,18 @@ async fn main() -> Result<()> {
     let pool = Pool::builder()
         .build(manager)
         .expect("Failed to create r2d2 pool for PgConnection");
+    ensure_schema_current(&mut *pool.get()?)?;
 
-    match cli.command {
+    let telemetry = install_postgres("nanuak-git");
+    let result = run(cli.command, pool).await;
+    if let Err(error) = telemetry.flush().await {
+        warn!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run(command: Commands, pool: Pool<ConnectionManager<PgConnection>>) -> Result<()> {
+    match command {
         Commands::Crawl { dir } => {
             actions::crawl_repos_action::crawl_repos_action(dir, pool).await?;
         }
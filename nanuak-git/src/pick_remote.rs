@@ -1,14 +1,23 @@
 use crate::remotes::RepoRemote;
 use crate::remotes::RepoRemotes;
-use cloud_terrastodon_core_user_input::prelude::pick;
+use cloud_terrastodon_core_user_input::prelude::Choice;
 use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use itertools::Itertools;
+use nanuak_picking::tui;
 
-pub fn pick_remote(remotes: &str) -> eyre::Result<RepoRemote> {
+pub async fn pick_remote(remotes: &str) -> eyre::Result<RepoRemote> {
     let remotes = remotes.parse::<RepoRemotes>()?;
-    let chosen_remote = pick(FzfArgs {
-        choices: remotes.into(),
+    let chosen_remote = tui::pick(FzfArgs {
+        choices: Vec::from(remotes)
+            .into_iter()
+            .map(|remote| Choice {
+                key: remote.to_string(),
+                value: remote,
+            })
+            .collect_vec(),
         header: Some("Pick a remote to summarize".to_string()),
         ..Default::default()
-    })?;
-    Ok(chosen_remote)
+    })
+    .await?;
+    Ok(chosen_remote.choice.value)
 }
@@ -3,18 +3,18 @@
 #[macro_use]
 extern crate diesel;
 
-use base64::engine::general_purpose;
-use base64::Engine as _;
 use clap::Parser;
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use dotenvy::dotenv;
-use reqwest::Client;
-use serde::Deserialize;
-use serde::Serialize;
+use nanuak_ai_router::answer::Answer;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::prompt_registry::PromptRegistry;
+use nanuak_ai_router::prompt_template::PromptVariables;
+use nanuak_ai_router::telemetry::install_postgres;
+use nanuak_schema::migrations::ensure_schema_current;
 use std::env;
-use std::fs;
 use std::io::Write;
 use std::io::{self};
 use std::time::Duration;
@@ -30,6 +30,7 @@ mod schema {
             id -> Integer,
             file_path -> Text,
             description -> Text,
+            prompt -> Nullable<Text>,
         }
     }
 }
@@ -43,6 +44,7 @@ mod models {
         pub id: i32,
         pub file_path: String,
         pub description: String,
+        pub prompt: Option<String>,
     }
 
     #[derive(Insertable, Debug)]
@@ -50,6 +52,7 @@ mod models {
     pub struct NewMeme<'a> {
         pub file_path: &'a str,
         pub description: &'a str,
+        pub prompt: Option<&'a str>,
     }
 }
 
@@ -60,12 +63,14 @@ use schema::memes::dsl::*;
 /// A simple interactive CLI for indexing and querying memes.
 ///
 /// There are two modes:
-/// 1. **Index a folder:** Walk a folder and for each image file, call Ollama’s vision endpoint
-///    (for example using the model `x/llama3.2-vision:latest`) with the prompt:
+/// 1. **Index a folder:** Walk a folder and for each image file, ask the AI router's vision
+///    strategy (llama3.2-vision or qwen2.5-vl, whichever is available) with the
+///    `meme-description` prompt template, which by default is:
 ///
 ///    > "Describe this meme for a visually impaired person and explain why it is funny."
 ///
-///    The returned description is stored (with the file path) in a database.
+///    The returned description is stored (with the file path and the `name@version` of the
+///    prompt that produced it) in a database.
 ///
 /// 2. **Query memes:** Enter a search term, and the program lists the top 5 matches (by doing a
 ///    case‑insensitive search against the stored descriptions). Then you can choose one to open
@@ -92,7 +97,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let database_url = env::var("DATABASE_URL")?;
     let manager = ConnectionManager::<diesel::pg::PgConnection>::new(database_url);
     let pool = Pool::builder().build(manager)?;
+    ensure_schema_current(&mut *pool.get()?)?;
 
+    let telemetry = install_postgres("nanuak-memes");
+    let result = run(&pool).await;
+    if let Err(error) = telemetry.flush().await {
+        println!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run(
+    pool: &Pool<ConnectionManager<diesel::pg::PgConnection>>,
+) -> Result<(), Box<dyn Error>> {
     loop {
         println!();
         println!("Select mode: [1] Index folder, [2] Query memes, [q] Quit");
@@ -111,10 +128,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     println!("Directory path cannot be empty.");
                     continue;
                 }
-                index_folder(dir, &pool).await?;
+                index_folder(dir, pool).await?;
             }
             "2" => {
-                query_memes(&pool)?;
+                query_memes(pool)?;
             }
             "q" | "Q" => break,
             _ => println!("Invalid choice."),
@@ -123,16 +140,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-/// Walk the directory, process image files, call the Ollama API for a description,
+/// Walk the directory, process image files, ask a vision model for a description,
 /// and save/update the entry in the `memes` table.
 async fn index_folder(
     dir: &str,
     pool: &Pool<ConnectionManager<diesel::pg::PgConnection>>,
 ) -> Result<(), Box<dyn Error>> {
     println!("Indexing folder: {}", dir);
-    let client = Client::new();
-    let ollama_url = "http://localhost:11434/api/generate";
-
+    let template = PromptRegistry::global().await?.get("meme-description")?;
     // Walk the directory recursively
     for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
         let path = entry.path();
@@ -144,48 +159,26 @@ async fn index_folder(
                     let file_path_str = path.to_string_lossy();
                     println!("Processing file: {}", file_path_str);
 
-                    // Read file and encode in base64
-                    let bytes = fs::read(path)?;
-                    let b64 = general_purpose::STANDARD.encode(&bytes);
-                    // Determine MIME type based on extension
-                    let mime = match ext.as_str() {
-                        "jpg" | "jpeg" => "image/jpeg",
-                        "png" => "image/png",
-                        "gif" => "image/gif",
-                        _ => "application/octet-stream",
-                    };
-                    let data_uri = format!("data:{};base64,{}", mime, b64);
-
-                    // Prepare request payload.
-                    #[derive(Serialize)]
-                    struct GenerateRequest<'a> {
-                        model: &'a str,
-                        prompt: &'a str,
-                        images: Vec<&'a str>,
-                        stream: bool,
-                    }
-                    let prompt_text = "Describe this meme for a visually impaired person and explain why it is funny.";
-                    let payload = GenerateRequest {
-                        model: "x/llama3.2-vision:latest",
-                        prompt: prompt_text,
-                        images: vec![&data_uri],
-                        stream: false,
+                    let answer = match Answer::try_generate_from_template(
+                        WellKnownGenerativeTextStrategy::Vision,
+                        template,
+                        &PromptVariables::new(),
+                        vec![path.to_path_buf()],
+                    )
+                    .await
+                    {
+                        Ok(answer) => answer,
+                        Err(error) => {
+                            println!("Failed to process image: {}: {:?}", file_path_str, error);
+                            continue;
+                        }
                     };
-
-                    // Send POST request to Ollama API
-                    let resp = client.post(ollama_url).json(&payload).send().await?;
-                    if !resp.status().is_success() {
-                        println!("Failed to process image: {}", file_path_str);
-                        continue;
-                    }
-
-                    // Parse the response JSON.
-                    #[derive(Deserialize)]
-                    struct GenerateResponse {
-                        response: String,
-                    }
-                    let gen_resp: GenerateResponse = resp.json().await?;
-                    println!("Description: {}", gen_resp.response);
+                    let prompt_ref = answer.prompt.as_ref().map(|p| p.to_string());
+                    println!(
+                        "Description ({}): {}",
+                        prompt_ref.as_deref().unwrap_or_default(),
+                        answer.body
+                    );
 
                     // Insert (or update) into the database.
                     {
@@ -197,12 +190,13 @@ async fn index_folder(
                             .optional()?;
                         if existing.is_some() {
                             diesel::update(memes.filter(file_path.eq(&file_path_str)))
-                                .set(description.eq(&gen_resp.response))
+                                .set((description.eq(&answer.body), prompt.eq(&prompt_ref)))
                                 .execute(conn)?;
                         } else {
                             let new_meme = NewMeme {
                                 file_path: &file_path_str,
-                                description: &gen_resp.response,
+                                description: &answer.body,
+                                prompt: prompt_ref.as_deref(),
                             };
                             diesel::insert_into(memes).values(&new_meme).execute(conn)?;
                         }
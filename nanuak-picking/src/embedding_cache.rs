@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+use std::path::PathBuf;
+
+use eyre::Context;
+use eyre::bail;
+use itertools::Itertools;
+use nanuak_ai_router::embedding::Embedding;
+use nanuak_ai_router::embedding_request::EmbeddingPayload;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_config::dirs::get_project_dirs;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::debug;
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Embeddings of choice keys for one model, so a picker only embeds keys it has not seen before.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingCache {
+    embeddings: HashMap<String, Vec<f32>>,
+}
+impl EmbeddingCache {
+    pub fn default_path(model: &str) -> eyre::Result<PathBuf> {
+        let file_name = model.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+        Ok(get_project_dirs()?
+            .cache_dir()
+            .join("picking")
+            .join(format!("{}.bin", file_name)))
+    }
+    /// An empty cache when `path` does not exist yet.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        if !path.exists() {
+            return Ok(EmbeddingCache::default());
+        }
+        let file = std::fs::File::open(path)
+            .wrap_err_with(|| format!("Failed to open embedding cache: {}", path.display()))?;
+        let (version, cache): (u32, EmbeddingCache) =
+            bincode::deserialize_from(BufReader::new(file))
+                .wrap_err_with(|| format!("Failed to read embedding cache: {}", path.display()))?;
+        if version != FORMAT_VERSION {
+            bail!(
+                "Embedding cache {} has format {}, expected {}; delete it",
+                path.display(),
+                version,
+                FORMAT_VERSION
+            );
+        }
+        Ok(cache)
+    }
+    /// Writes to a temporary file first so a crash never leaves a truncated cache.
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("tmp");
+        let file = std::fs::File::create(&temp)
+            .wrap_err_with(|| format!("Failed to create embedding cache: {}", temp.display()))?;
+        bincode::serialize_into(BufWriter::new(file), &(FORMAT_VERSION, self))?;
+        std::fs::rename(&temp, path)
+            .wrap_err_with(|| format!("Failed to write embedding cache: {}", path.display()))?;
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.embeddings.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.embeddings.is_empty()
+    }
+    pub fn get(&self, key: &str) -> Option<Embedding> {
+        self.embeddings.get(key).cloned().map(Embedding)
+    }
+    /// The embedding of every key in order, generating the missing ones in a single batch.
+    pub async fn embed_all(
+        &mut self,
+        strategy: WellKnownEmbeddingStrategy,
+        keys: &[String],
+    ) -> eyre::Result<Vec<Embedding>> {
+        let missing = keys
+            .iter()
+            .filter(|key| !self.embeddings.contains_key(key.as_str()))
+            .unique()
+            .cloned()
+            .collect_vec();
+        if !missing.is_empty() {
+            debug!(
+                "Embedding {} of {} choices, the rest are cached",
+                missing.len(),
+                keys.len()
+            );
+            let payloads = missing
+                .iter()
+                .map(|key| EmbeddingPayload::Text(key.clone()))
+                .collect_vec();
+            let embeddings = Embedding::try_generate(strategy, payloads).await?;
+            if embeddings.len() != missing.len() {
+                bail!(
+                    "Asked for {} embeddings but got {}",
+                    missing.len(),
+                    embeddings.len()
+                );
+            }
+            for (key, embedding) in missing.into_iter().zip(embeddings) {
+                self.embeddings.insert(key, embedding.0);
+            }
+        }
+        Ok(keys
+            .iter()
+            .map(|key| Embedding(self.embeddings[key.as_str()].clone()))
+            .collect())
+    }
+}
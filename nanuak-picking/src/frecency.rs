@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use chrono::TimeDelta;
+
+use crate::pick_history::PickRecord;
+
+/// How much one pick counts at `age`, with zoxide's aging buckets.
+pub fn frecency_weight(age: TimeDelta) -> f64 {
+    if age < TimeDelta::hours(1) {
+        4.0
+    } else if age < TimeDelta::days(1) {
+        2.0
+    } else if age < TimeDelta::weeks(1) {
+        0.5
+    } else {
+        0.25
+    }
+}
+
+/// Summed pick weights per key.
+#[derive(Debug, Clone, Default)]
+pub struct Frecency {
+    scores: HashMap<String, f64>,
+}
+impl Frecency {
+    pub fn from_records(records: &[PickRecord], now: NaiveDateTime) -> Self {
+        let mut scores = HashMap::new();
+        for record in records {
+            *scores.entry(record.key.clone()).or_default() +=
+                frecency_weight(now - record.picked_at);
+        }
+        Frecency { scores }
+    }
+    pub fn score(&self, key: &str) -> f64 {
+        self.scores.get(key).copied().unwrap_or_default()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+    /// The `n` highest scoring keys, best first.
+    pub fn top(&self, n: usize) -> Vec<(&str, f64)> {
+        let mut top = self
+            .scores
+            .iter()
+            .map(|(key, score)| (key.as_str(), *score))
+            .collect::<Vec<_>>();
+        top.sort_by(|(a_key, a), (b_key, b)| b.total_cmp(a).then(a_key.cmp(b_key)));
+        top.truncate(n);
+        top
+    }
+}
@@ -0,0 +1,54 @@
+/// Scores `candidate` against an fzf-style query between 0 and 1, or `None` when it does not
+/// match. Each whitespace-separated term must appear in order as a case-insensitive
+/// subsequence; consecutive characters and characters at word starts score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<f64> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<_>>();
+    let terms = query.split_whitespace().collect::<Vec<_>>();
+    if terms.is_empty() {
+        return Some(1.0);
+    }
+    let mut total = 0.0;
+    for term in &terms {
+        total += term_score(&term.to_lowercase(), &candidate)?;
+    }
+    Some(total / terms.len() as f64)
+}
+
+fn term_score(term: &str, candidate: &[char]) -> Option<f64> {
+    let mut points = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    let mut length = 0;
+    for wanted in term.chars() {
+        length += 1;
+        let found = position + candidate[position..].iter().position(|c| *c == wanted)?;
+        points += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            points += 1;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            points += 1;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    // A word-start first character followed by consecutive ones is a perfect match.
+    Some((points as f64 / (length * 2) as f64).min(1.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::fuzzy_score;
+
+    #[test]
+    fn prefers_consecutive_and_word_start_matches() {
+        let prefix = fuzzy_score("nan", "nanuak-picking").unwrap();
+        let scattered = fuzzy_score("nan", "banana").unwrap();
+        assert_eq!(prefix, 1.0);
+        assert!(scattered < prefix);
+        assert_eq!(fuzzy_score("xyz", "nanuak"), None);
+        assert_eq!(fuzzy_score("pick nan", "nanuak-picking"), Some(1.0));
+        assert_eq!(fuzzy_score("pick zzz", "nanuak-picking"), None);
+        assert_eq!(fuzzy_score("  ", "anything"), Some(1.0));
+    }
+}
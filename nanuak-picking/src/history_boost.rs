@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use nanuak_ai_router::embedding::Embedding;
+
+use crate::frecency::Frecency;
+use crate::pick_history::PickHistory;
+
+/// Past picks whose embeddings are compared with new choices.
+const SIMILAR_HISTORY_KEYS: usize = 32;
+
+/// Score bonuses for choices picked before in the same context, or similar to ones that were.
+#[derive(Debug, Clone)]
+pub struct HistoryBoost {
+    frecency: Frecency,
+    embeddings: HashMap<String, Embedding>,
+    /// The largest bonus, approached as a choice's frecency grows.
+    pub weight: f64,
+    /// Share of a similar past pick's frecency passed on to a choice.
+    pub similarity_weight: f64,
+    /// Past picks less similar than this pass nothing on.
+    pub min_similarity: f64,
+}
+impl Default for HistoryBoost {
+    fn default() -> Self {
+        HistoryBoost::new(Frecency::default())
+    }
+}
+impl HistoryBoost {
+    pub fn new(frecency: Frecency) -> Self {
+        HistoryBoost {
+            frecency,
+            embeddings: HashMap::new(),
+            weight: 0.3,
+            similarity_weight: 0.5,
+            min_similarity: 0.75,
+        }
+    }
+    pub async fn load(history: &PickHistory, context: &str) -> eyre::Result<Self> {
+        let records = history.load(Some(context)).await?;
+        Ok(HistoryBoost::new(Frecency::from_records(
+            &records,
+            Utc::now().naive_utc(),
+        )))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.frecency.is_empty()
+    }
+    /// The past picks worth embedding for similarity boosts, most frecent first.
+    pub fn history_keys(&self) -> Vec<String> {
+        self.frecency
+            .top(SIMILAR_HISTORY_KEYS)
+            .into_iter()
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+    pub fn with_embeddings(
+        mut self,
+        keys: impl IntoIterator<Item = String>,
+        embeddings: impl IntoIterator<Item = Embedding>,
+    ) -> Self {
+        self.embeddings.extend(keys.into_iter().zip(embeddings));
+        self
+    }
+    /// Between 0 and `weight`: diminishing returns on the key's own frecency plus a share of
+    /// the frecency of similar past picks.
+    pub fn boost(&self, key: &str, embedding: Option<&Embedding>) -> f64 {
+        let mut total = self.frecency.score(key);
+        if let Some(embedding) = embedding {
+            let similar = self
+                .embeddings
+                .iter()
+                .filter(|(history_key, _)| history_key.as_str() != key)
+                .filter_map(|(history_key, history_embedding)| {
+                    let similarity = embedding.cosine_similarity(history_embedding).ok()?;
+                    (similarity >= self.min_similarity).then(|| {
+                        (similarity - self.min_similarity) / (1.0 - self.min_similarity)
+                            * self.frecency.score(history_key)
+                    })
+                })
+                .fold(0.0, f64::max);
+            total += self.similarity_weight * similar;
+        }
+        self.weight * total / (total + 4.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDateTime;
+    use chrono::TimeDelta;
+    use nanuak_ai_router::embedding::Embedding;
+
+    use super::HistoryBoost;
+    use crate::frecency::Frecency;
+    use crate::pick_history::PickRecord;
+
+    #[test]
+    fn boosts_frecent_and_similar_choices() {
+        let now = NaiveDateTime::default() + TimeDelta::days(30);
+        let record = |key: &str, age: TimeDelta| PickRecord {
+            context: "repo".to_string(),
+            key: key.to_string(),
+            picked_at: now - age,
+        };
+        let records = [
+            record("nanuak", TimeDelta::minutes(5)),
+            record("nanuak", TimeDelta::hours(3)),
+            record("terrastodon", TimeDelta::days(20)),
+        ];
+        let frecency = Frecency::from_records(&records, now);
+        assert_eq!(frecency.score("nanuak"), 6.0);
+        assert_eq!(frecency.top(1), [("nanuak", 6.0)]);
+
+        let boost = HistoryBoost::new(frecency)
+            .with_embeddings(["nanuak".to_string()], [Embedding(vec![1.0, 0.0])]);
+        let own = boost.boost("nanuak", None);
+        let old = boost.boost("terrastodon", None);
+        let similar = boost.boost("nanuak-fork", Some(&Embedding(vec![0.99, 0.1])));
+        let unrelated = boost.boost("dotfiles", Some(&Embedding(vec![0.0, 1.0])));
+        assert!(own > similar && similar > old && old > unrelated);
+        assert_eq!(unrelated, 0.0);
+        assert!(own < boost.weight);
+    }
+}
@@ -1 +1,15 @@
+pub mod embedding_cache;
+pub mod frecency;
+pub mod fuzzy;
+pub mod history_boost;
+pub mod pick_history;
+pub mod pick_many_options;
+pub mod pick_options;
+pub mod picked;
 pub mod picker;
+pub mod ranker;
+pub mod scored_choice;
+pub mod selection;
+pub mod selection_mode;
+pub mod threshold_calibration;
+pub mod tui;
@@ -1,3 +1,206 @@
-fn main() {
-    println!("Hello, world!");
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use clap::Parser;
+use clap::Subcommand;
+use cloud_terrastodon_core_user_input::prelude::Choice;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use eyre::Context;
+use eyre::bail;
+use nanuak_ai_router::telemetry::install_postgres;
+use nanuak_picking::frecency::Frecency;
+use nanuak_picking::pick_history::PickHistory;
+use nanuak_picking::threshold_calibration::ThresholdCalibration;
+use nanuak_picking::tui::TuiOptions;
+use nanuak_picking::tui::pick_many_with_options;
+use nanuak_picking::tui::pick_with_options;
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Nanuak semantic picker")]
+struct Cli {
+    /// If set, enable debug logging
+    #[arg(long)]
+    debug: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Pick from lines on stdin and print the choice
+    Pick {
+        /// Pick any number of lines, marking them with tab
+        #[arg(long)]
+        many: bool,
+        #[arg(long)]
+        header: Option<String>,
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Rank by fuzzy match alone, without embedding anything
+        #[arg(long)]
+        fuzzy_only: bool,
+        /// Neither boost by nor record pick history
+        #[arg(long)]
+        no_history: bool,
+    },
+    /// Show past picks by context, most frecent first
+    History {
+        /// Only this context, the picker's header and prompt joined by " | "
+        #[arg(long)]
+        context: Option<String>,
+    },
+    /// Forget past picks
+    ClearHistory {
+        /// Only this context, otherwise every pick
+        #[arg(long)]
+        context: Option<String>,
+    },
+    /// Fit a model's similarity threshold to labelled scores and save it to the Nanuak config
+    Calibrate {
+        /// Embedding model as its provider names it, e.g. bge-m3:latest
+        #[arg(long)]
+        model: String,
+        /// Lines of `<score> <true|false>`, whether a choice with that score should be picked
+        samples: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+
+    let log_level = if cli.debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let telemetry = install_postgres("nanuak-picking");
+    let result = run(cli.command).await;
+    if let Err(error) = telemetry.flush().await {
+        warn!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run(command: Commands) -> eyre::Result<()> {
+    match command {
+        Commands::Pick {
+            many,
+            header,
+            prompt,
+            fuzzy_only,
+            no_history,
+        } => {
+            let choices = std::io::read_to_string(std::io::stdin())?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Choice {
+                    key: line.to_string(),
+                    value: line.to_string(),
+                })
+                .collect();
+            let args = FzfArgs {
+                choices,
+                header,
+                prompt,
+            };
+            let mut options = TuiOptions {
+                fuzzy_only,
+                ..TuiOptions::default()
+            };
+            if !no_history {
+                options = options.learning()?;
+            }
+            if many {
+                for chosen in pick_many_with_options(args, &options).await?.into_values() {
+                    println!("{}", chosen);
+                }
+            } else {
+                println!("{}", pick_with_options(args, &options).await?.choice.value);
+            }
+        }
+        Commands::History { context } => {
+            let history = PickHistory::open()?;
+            let records = history.load(context.as_deref()).await?;
+            let mut by_context: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+            for record in &records {
+                by_context
+                    .entry(record.context.as_str())
+                    .or_default()
+                    .push(record.clone());
+            }
+            let now = Utc::now().naive_utc();
+            for (context, records) in by_context {
+                println!(
+                    "{}",
+                    if context.is_empty() {
+                        "<no context>"
+                    } else {
+                        context
+                    }
+                );
+                let frecency = Frecency::from_records(&records, now);
+                for (key, score) in frecency.top(usize::MAX) {
+                    let picks = records.iter().filter(|record| record.key == key);
+                    let count = picks.clone().count();
+                    let last = picks.map(|record| record.picked_at).max().unwrap_or(now);
+                    println!(
+                        "  {:6.2} {:3}x last {} {}",
+                        score,
+                        count,
+                        last.format("%Y-%m-%d %H:%M"),
+                        key
+                    );
+                }
+            }
+            info!("{} picks in {}", records.len(), history.path.display());
+        }
+        Commands::ClearHistory { context } => {
+            let history = PickHistory::open()?;
+            let removed = history.clear(context.as_deref()).await?;
+            info!("Forgot {} picks", removed);
+        }
+        Commands::Calibrate { model, samples } => {
+            let mut labelled = Vec::new();
+            for (i, line) in tokio::fs::read_to_string(&samples)
+                .await
+                .wrap_err_with(|| format!("Failed to read {}", samples.display()))?
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+            {
+                let Some((score, relevant)) = line.split_once(char::is_whitespace) else {
+                    bail!("Line {} is not `<score> <true|false>`: {}", i + 1, line);
+                };
+                labelled.push((
+                    score
+                        .parse::<f64>()
+                        .wrap_err_with(|| format!("Line {}", i + 1))?,
+                    relevant
+                        .trim()
+                        .parse::<bool>()
+                        .wrap_err_with(|| format!("Line {}", i + 1))?,
+                ));
+            }
+            let calibration = ThresholdCalibration::load().await?.fit(&model, &labelled);
+            calibration.save().await?;
+            println!("{:.3}", calibration.threshold_for(&model));
+        }
+    }
+    Ok(())
 }
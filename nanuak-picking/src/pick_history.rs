@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use eyre::Context;
+use nanuak_config::dirs::get_project_dirs;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+/// How many picks the history holds before old ones are aged out.
+const MAX_RECORDS: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PickRecord {
+    /// The picker's header and prompt, so picks only inform the same question.
+    pub context: String,
+    pub key: String,
+    pub picked_at: NaiveDateTime,
+}
+
+/// Every pick made with learning enabled, one JSON record per line, aged like zoxide's database
+/// once it grows past a couple of thousand picks.
+#[derive(Debug, Clone)]
+pub struct PickHistory {
+    pub path: PathBuf,
+}
+impl PickHistory {
+    pub fn default_path() -> eyre::Result<PathBuf> {
+        Ok(get_project_dirs()?.data_dir().join("pick_history.jsonl"))
+    }
+    pub fn open() -> eyre::Result<Self> {
+        Ok(PickHistory {
+            path: Self::default_path()?,
+        })
+    }
+    pub async fn record(&self, context: &str, keys: &[&str]) -> eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .wrap_err_with(|| format!("Failed to open pick history: {}", self.path.display()))?;
+        let picked_at = Utc::now().naive_utc();
+        let mut lines = String::new();
+        for key in keys {
+            lines.push_str(&serde_json::to_string(&PickRecord {
+                context: context.to_string(),
+                key: key.to_string(),
+                picked_at,
+            })?);
+            lines.push('\n');
+        }
+        file.write_all(lines.as_bytes()).await?;
+
+        let records = self.load(None).await?;
+        if records.len() > MAX_RECORDS {
+            self.write(&aged(records, MAX_RECORDS)).await?;
+        }
+        Ok(())
+    }
+    /// Records for `context`, or every context, in the order they were made. Unreadable lines,
+    /// such as one left half-written, are skipped with a warning.
+    pub async fn load(&self, context: Option<&str>) -> eyre::Result<Vec<PickRecord>> {
+        if !tokio::fs::try_exists(&self.path).await? {
+            return Ok(Vec::new());
+        }
+        let jsonl = tokio::fs::read_to_string(&self.path).await?;
+        let mut records = Vec::new();
+        for (number, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: PickRecord = match serde_json::from_str(line) {
+                Ok(record) => record,
+                Err(error) => {
+                    warn!(
+                        "Skipping unreadable line {} of pick history {}: {}",
+                        number + 1,
+                        self.path.display(),
+                        error
+                    );
+                    continue;
+                }
+            };
+            if context.is_none_or(|context| record.context == context) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+    /// Forgets the picks for `context`, or every pick, returning how many were removed.
+    pub async fn clear(&self, context: Option<&str>) -> eyre::Result<usize> {
+        let records = self.load(None).await?;
+        let (removed, kept): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .partition(|record| context.is_none_or(|context| record.context == context));
+        if removed.is_empty() {
+            return Ok(0);
+        }
+        self.write(&kept).await?;
+        Ok(removed.len())
+    }
+    async fn write(&self, records: &[PickRecord]) -> eyre::Result<()> {
+        let mut jsonl = String::new();
+        for record in records {
+            jsonl.push_str(&serde_json::to_string(record)?);
+            jsonl.push('\n');
+        }
+        tokio::fs::write(&self.path, jsonl)
+            .await
+            .wrap_err_with(|| format!("Failed to write pick history: {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Ages `records` the way zoxide ages its database once they number more than `max_records`:
+/// every key keeps the same share of its picks, the most recent ones, but never fewer than its
+/// newest pick. If that still leaves more than 90% of `max_records`, only the newest of them
+/// remain, so a history of one-off picks forgets the oldest instead of everything.
+pub fn aged(records: Vec<PickRecord>, max_records: usize) -> Vec<PickRecord> {
+    if records.len() <= max_records {
+        return records;
+    }
+    let target = (0.9 * max_records as f64) as usize;
+    let share = target as f64 / records.len() as f64;
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for record in &records {
+        *counts
+            .entry((record.context.clone(), record.key.clone()))
+            .or_default() += 1;
+    }
+    let mut keep = counts
+        .into_iter()
+        .map(|(key, count)| (key, ((count as f64 * share) as usize).max(1)))
+        .collect::<HashMap<_, _>>();
+    // Walk newest first so each key keeps its latest picks.
+    let mut kept = records
+        .into_iter()
+        .rev()
+        .filter(
+            |record| match keep.get_mut(&(record.context.clone(), record.key.clone())) {
+                Some(left) if *left > 0 => {
+                    *left -= 1;
+                    true
+                }
+                _ => false,
+            },
+        )
+        .take(target)
+        .collect::<Vec<_>>();
+    kept.reverse();
+    kept
+}
+
+/// The history context for a picker, from its header and prompt.
+pub fn context_of<T>(args: &FzfArgs<T>) -> String {
+    [args.header.as_deref(), args.prompt.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
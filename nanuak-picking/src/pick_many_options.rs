@@ -0,0 +1,50 @@
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+
+use crate::pick_history::PickHistory;
+use crate::selection_mode::SelectionMode;
+use crate::threshold_calibration::ThresholdCalibration;
+
+#[derive(Debug, Clone)]
+pub struct PickManyOptions {
+    pub embedding_strategy: WellKnownEmbeddingStrategy,
+    pub mode: SelectionMode,
+    /// Consulted by [`SelectionMode::Calibrated`].
+    pub calibration: ThresholdCalibration,
+    /// Where past picks are learned from; `None` leaves rankings unaffected by history.
+    pub history: Option<PickHistory>,
+}
+impl Default for PickManyOptions {
+    fn default() -> Self {
+        PickManyOptions {
+            embedding_strategy: WellKnownEmbeddingStrategy::BestLocal,
+            mode: SelectionMode::Calibrated,
+            calibration: ThresholdCalibration::default(),
+            history: None,
+        }
+    }
+}
+impl PickManyOptions {
+    pub fn new(mode: SelectionMode) -> Self {
+        PickManyOptions {
+            mode,
+            ..Default::default()
+        }
+    }
+    pub fn with_strategy(mut self, strategy: WellKnownEmbeddingStrategy) -> Self {
+        self.embedding_strategy = strategy;
+        self
+    }
+    /// Ranks choices picked before in the same context first among equally similar ones.
+    pub fn learning(mut self) -> eyre::Result<Self> {
+        self.history = Some(PickHistory::open()?);
+        Ok(self)
+    }
+    pub fn with_history(mut self, history: PickHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+    pub fn with_calibration(mut self, calibration: ThresholdCalibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+}
@@ -0,0 +1,66 @@
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+
+use crate::pick_history::PickHistory;
+
+/// What [`pick`](crate::picker::pick) does when several choices score about the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmbiguityPolicy {
+    /// Fail, naming the close choices.
+    Refuse,
+    /// Let the user choose between the close choices with fzf.
+    Ask,
+    /// Ask a language model which of the close choices the query means.
+    TieBreak(WellKnownGenerativeTextStrategy),
+}
+
+#[derive(Debug, Clone)]
+pub struct PickOptions {
+    pub embedding_strategy: WellKnownEmbeddingStrategy,
+    /// The cosine similarity the best choice needs to be picked without asking.
+    pub min_score: f64,
+    /// Choices scoring within this of the best are ambiguous with it.
+    pub min_margin: f64,
+    pub on_ambiguous: AmbiguityPolicy,
+    /// Offer every choice in fzf when the best is below `min_score`, instead of failing.
+    pub interactive_fallback: bool,
+    /// Where picks the user confirms are recorded and learned from; `None` leaves rankings
+    /// unaffected by history.
+    pub history: Option<PickHistory>,
+}
+impl Default for PickOptions {
+    fn default() -> Self {
+        PickOptions {
+            embedding_strategy: WellKnownEmbeddingStrategy::BestLocal,
+            min_score: 0.5,
+            min_margin: 0.03,
+            on_ambiguous: AmbiguityPolicy::Ask,
+            interactive_fallback: true,
+            history: None,
+        }
+    }
+}
+impl PickOptions {
+    pub fn with_strategy(mut self, strategy: WellKnownEmbeddingStrategy) -> Self {
+        self.embedding_strategy = strategy;
+        self
+    }
+    pub fn on_ambiguous(mut self, policy: AmbiguityPolicy) -> Self {
+        self.on_ambiguous = policy;
+        self
+    }
+    /// Ranks choices picked before in the same context first among equally similar ones and
+    /// records the picks the user confirms.
+    pub fn learning(mut self) -> eyre::Result<Self> {
+        self.history = Some(PickHistory::open()?);
+        Ok(self)
+    }
+    pub fn with_history(mut self, history: PickHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+    pub fn non_interactive(mut self) -> Self {
+        self.interactive_fallback = false;
+        self
+    }
+}
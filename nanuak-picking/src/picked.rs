@@ -0,0 +1,20 @@
+use cloud_terrastodon_core_user_input::prelude::Choice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickResolution {
+    /// The best choice stood out from the rest.
+    Confident,
+    /// A language model chose between close matches.
+    TieBreak,
+    /// The user chose in fzf or the picker TUI.
+    Interactive,
+}
+
+#[derive(Debug)]
+pub struct Picked<T> {
+    pub choice: Choice<T>,
+    /// Cosine similarity between the choice and the query; the blended fuzzy, semantic and
+    /// history score when picked in the TUI.
+    pub score: f64,
+    pub resolution: PickResolution,
+}
@@ -1,41 +1,330 @@
 use cloud_terrastodon_core_user_input::prelude::Choice;
 use cloud_terrastodon_core_user_input::prelude::FzfArgs;
 use eyre::bail;
+use itertools::Itertools;
+use nanuak_ai_router::answer::Answer;
 use nanuak_ai_router::embedding::Embedding;
 use nanuak_ai_router::embedding_request::EmbeddingPayload;
 use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::prompt_registry::PromptRegistry;
+use nanuak_ai_router::prompt_template::PromptVariables;
+use tracing::debug;
+use tracing::warn;
 
-pub async fn pick<T>(_args: FzfArgs<T>) -> eyre::Result<T> {
-    todo!()
+use crate::history_boost::HistoryBoost;
+use crate::pick_history::PickHistory;
+use crate::pick_history::context_of;
+use crate::pick_many_options::PickManyOptions;
+use crate::pick_options::AmbiguityPolicy;
+use crate::pick_options::PickOptions;
+use crate::picked::PickResolution;
+use crate::picked::Picked;
+use crate::scored_choice::ScoredChoice;
+use crate::selection::Selection;
+use crate::selection_mode::SelectionMode;
+use crate::threshold_calibration::ThresholdCalibration;
+
+/// The choice most similar to the header and prompt.
+pub async fn pick<T>(args: FzfArgs<Choice<T>>) -> eyre::Result<Picked<T>> {
+    pick_with_options(args, &PickOptions::default()).await
+}
+/// Past picks only reorder choices that are about as similar; only picks the user confirmed are
+/// recorded.
+pub async fn pick_with_options<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &PickOptions,
+) -> eyre::Result<Picked<T>> {
+    let context = context_of(&args);
+    let picked = resolve_pick(args, options).await?;
+    if let Some(history) = &options.history
+        && picked.resolution == PickResolution::Interactive
+    {
+        remember(history, &context, &[picked.choice.key.as_str()]).await;
+    }
+    Ok(picked)
 }
-pub async fn pick_many<T>(args: FzfArgs<Choice<T>>) -> eyre::Result<Vec<Choice<T>>> {
+async fn resolve_pick<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &PickOptions,
+) -> eyre::Result<Picked<T>> {
+    if args.choices.is_empty() {
+        bail!("No choices to pick from");
+    }
+    let header = args.header.clone();
+    let prompt = args.prompt.clone();
+    let query = query_text(&args);
+    let mut candidates =
+        score_choices(args, options.embedding_strategy, options.history.as_ref()).await?;
+    sort_by_similarity(&mut candidates);
+
+    let best = &candidates[0];
+    if best.similarity < options.min_score {
+        if !options.interactive_fallback {
+            bail!(
+                "No choice matches {:?} well enough: the best was {:?} at {:.3}, below {:.3}",
+                query.trim(),
+                best.choice.key,
+                best.similarity,
+                options.min_score
+            );
+        }
+        debug!(
+            "Best choice {:?} scored {:.3}, asking instead",
+            best.choice.key, best.similarity
+        );
+        sort_by_ranking(&mut candidates);
+        return pick_interactively(candidates, header, prompt);
+    }
+
+    let best_similarity = best.similarity;
+    let close = candidates
+        .iter()
+        .take_while(|candidate| best_similarity - candidate.similarity < options.min_margin)
+        .count();
+    if close == 1 {
+        let best = candidates.swap_remove(0);
+        return Ok(Picked {
+            choice: best.choice,
+            score: best.similarity,
+            resolution: PickResolution::Confident,
+        });
+    }
+    candidates.truncate(close);
+    sort_by_ranking(&mut candidates);
+    debug!(
+        "{} choices within {:.3} of the best",
+        close, options.min_margin
+    );
+    match options.on_ambiguous {
+        AmbiguityPolicy::Refuse => bail!(
+            "{:?} is ambiguous between {}",
+            query.trim(),
+            candidates
+                .iter()
+                .map(|candidate| format!(
+                    "{:?} ({:.3})",
+                    candidate.choice.key, candidate.similarity
+                ))
+                .join(", ")
+        ),
+        AmbiguityPolicy::Ask => pick_interactively(candidates, header, prompt),
+        AmbiguityPolicy::TieBreak(strategy) => {
+            let index = tie_break(&query, &candidates, strategy).await?;
+            let chosen = candidates.swap_remove(index);
+            Ok(Picked {
+                choice: chosen.choice,
+                score: chosen.similarity,
+                resolution: PickResolution::TieBreak,
+            })
+        }
+    }
+}
+/// The choices similar enough to the header and prompt, as decided by the threshold calibrated
+/// in the Nanuak config.
+pub async fn pick_many<T>(args: FzfArgs<Choice<T>>) -> eyre::Result<Selection<T>> {
+    let options = PickManyOptions::default().with_calibration(ThresholdCalibration::load().await?);
+    pick_many_with_options(args, &options).await
+}
+/// The mode decides how many choices to keep from their similarity alone, with past picks only
+/// breaking ties at the cut and ordering each side. Nothing is recorded, since the user confirmed
+/// none of it. Ties are kept in the order the choices were given.
+pub async fn pick_many_with_options<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &PickManyOptions,
+) -> eyre::Result<Selection<T>> {
+    let mut candidates =
+        score_choices(args, options.embedding_strategy, options.history.as_ref()).await?;
+    sort_by_similarity(&mut candidates);
+    let similarities = candidates
+        .iter()
+        .map(|candidate| candidate.similarity)
+        .collect_vec();
+    let threshold = match options.mode {
+        SelectionMode::Calibrated => options
+            .calibration
+            .threshold_for(options.embedding_strategy.get_model().name()),
+        _ => options.calibration.fallback,
+    };
+    let count = options.mode.count(&similarities, threshold);
+    debug!(
+        "Chose {} of {} choices by {:?}",
+        count,
+        similarities.len(),
+        options.mode
+    );
+    let mut rejected = candidates.split_off(count);
+    let mut chosen = candidates;
+    let cutoff = chosen.last().map(|candidate| candidate.similarity);
+    sort_by_ranking(&mut chosen);
+    sort_by_ranking(&mut rejected);
+    let scored = |candidate: Candidate<T>| ScoredChoice {
+        choice: candidate.choice,
+        score: candidate.similarity,
+    };
+    Ok(Selection {
+        chosen: chosen.into_iter().map(scored).collect(),
+        rejected: rejected.into_iter().map(scored).collect(),
+        cutoff,
+    })
+}
+
+fn query_text<T>(args: &FzfArgs<T>) -> String {
     let mut query = String::new();
-    if let Some(header) = args.header {
-        query.push_str(&header);
+    if let Some(header) = &args.header {
+        query.push_str(header);
         query.push('\n');
     }
-    if let Some(prompt) = args.prompt {
-        query.push_str(&prompt);
+    if let Some(prompt) = &args.prompt {
+        query.push_str(prompt);
         query.push('\n');
     }
+    query
+}
 
-    let mut to_embed = vec![EmbeddingPayload::Text(query.clone())];
+/// A choice with what decides whether it is picked and what only reorders it.
+struct Candidate<T> {
+    /// Position in the picker's input, so equal choices keep their order.
+    index: usize,
+    choice: Choice<T>,
+    /// Cosine similarity to the query, which thresholds apply to.
+    similarity: f64,
+    /// Bonus from past picks, which only reorders.
+    boost: f64,
+}
+impl<T> Candidate<T> {
+    fn ranking(&self) -> f64 {
+        self.similarity + self.boost
+    }
+}
+
+/// Most similar first, boosts breaking ties.
+fn sort_by_similarity<T>(candidates: &mut [Candidate<T>]) {
+    candidates.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then(b.boost.total_cmp(&a.boost))
+            .then(a.index.cmp(&b.index))
+    });
+}
+
+/// Highest similarity plus boost first.
+fn sort_by_ranking<T>(candidates: &mut [Candidate<T>]) {
+    candidates.sort_by(|a, b| {
+        b.ranking()
+            .total_cmp(&a.ranking())
+            .then(a.index.cmp(&b.index))
+    });
+}
+
+/// Every choice with its cosine similarity to the query and any boost from past picks, in the
+/// order given.
+async fn score_choices<T>(
+    args: FzfArgs<Choice<T>>,
+    strategy: WellKnownEmbeddingStrategy,
+    history: Option<&PickHistory>,
+) -> eyre::Result<Vec<Candidate<T>>> {
+    let boost = match history {
+        Some(history) => HistoryBoost::load(history, &context_of(&args)).await?,
+        None => HistoryBoost::default(),
+    };
+    let history_keys = boost
+        .history_keys()
+        .into_iter()
+        .filter(|key| !args.choices.iter().any(|choice| choice.key == *key))
+        .collect_vec();
+
+    let mut to_embed = vec![EmbeddingPayload::Text(query_text(&args))];
     for choice in &args.choices {
         to_embed.push(EmbeddingPayload::Text(choice.key.clone()));
     }
-    let embeddings =
-        Embedding::try_generate(WellKnownEmbeddingStrategy::BestLocal, to_embed).await?;
-    let ([query], choices_embeddings) = embeddings.split_at(1) else {
+    for key in &history_keys {
+        to_embed.push(EmbeddingPayload::Text(key.clone()));
+    }
+    let mut embeddings = Embedding::try_generate(strategy, to_embed).await?;
+    if embeddings.len() != 1 + args.choices.len() + history_keys.len() {
         bail!("Embedding shape was incorrect, got {}", embeddings.len());
-    };
-    let threshold = 0.65;
-    let mut chosen = Vec::new();
-    for (embedding, choice) in choices_embeddings.iter().zip(args.choices.into_iter()) {
-        let similarity = query.cosine_similarity(embedding)?;
-        println!("{}: {}", choice.key, similarity);
-        if similarity >= threshold {
-            chosen.push(choice);
-        }
     }
-    Ok(chosen)
+    let history_embeddings = embeddings.split_off(1 + args.choices.len());
+    let query = embeddings.remove(0);
+    let boost = boost
+        .with_embeddings(
+            args.choices.iter().map(|choice| choice.key.clone()),
+            embeddings.iter().cloned(),
+        )
+        .with_embeddings(history_keys, history_embeddings);
+
+    let mut candidates = Vec::new();
+    for (index, (embedding, choice)) in embeddings.iter().zip(args.choices).enumerate() {
+        candidates.push(Candidate {
+            index,
+            similarity: query.cosine_similarity(embedding)?,
+            boost: boost.boost(&choice.key, Some(embedding)),
+            choice,
+        });
+    }
+    Ok(candidates)
+}
+
+/// Records picks for learning, warning rather than failing a pick that already succeeded.
+async fn remember(history: &PickHistory, context: &str, keys: &[&str]) {
+    if let Err(error) = history.record(context, keys).await {
+        warn!("Failed to record pick history: {:?}", error);
+    }
+}
+
+fn pick_interactively<T>(
+    candidates: Vec<Candidate<T>>,
+    header: Option<String>,
+    prompt: Option<String>,
+) -> eyre::Result<Picked<T>> {
+    let chosen = cloud_terrastodon_core_user_input::prelude::pick(FzfArgs {
+        choices: candidates
+            .into_iter()
+            .map(|candidate| Choice {
+                key: candidate.choice.key.clone(),
+                value: candidate,
+            })
+            .collect_vec(),
+        header,
+        prompt,
+    })?;
+    let chosen = chosen.value;
+    Ok(Picked {
+        choice: chosen.choice,
+        score: chosen.similarity,
+        resolution: PickResolution::Interactive,
+    })
+}
+
+/// The index of the close choice the model prefers.
+async fn tie_break<T>(
+    query: &str,
+    close: &[Candidate<T>],
+    strategy: WellKnownGenerativeTextStrategy,
+) -> eyre::Result<usize> {
+    let template = PromptRegistry::global().await?.get("pick-tie-break")?;
+    let variables = PromptVariables::new().with("query", query.trim()).with(
+        "options",
+        close
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| format!("{}. {}", i + 1, candidate.choice.key))
+            .collect_vec(),
+    );
+    let answer =
+        Answer::try_generate_from_template(strategy, template, &variables, Vec::new()).await?;
+    let number = answer
+        .body
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())
+        .and_then(|part| part.parse::<usize>().ok());
+    match number {
+        Some(number) if (1..=close.len()).contains(&number) => Ok(number - 1),
+        _ => bail!(
+            "Tie-breaker reply {:?} names none of the {} options",
+            answer.body,
+            close.len()
+        ),
+    }
 }
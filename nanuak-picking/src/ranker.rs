@@ -0,0 +1,141 @@
+use nanuak_ai_router::embedding::Embedding;
+
+use crate::fuzzy::fuzzy_score;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedChoice {
+    /// Position of the choice in the picker's input.
+    pub index: usize,
+    pub score: f64,
+    pub fuzzy: Option<f64>,
+    pub semantic: Option<f64>,
+}
+
+/// Ranks choices by a blend of fuzzy matching and embedding similarity to the query.
+#[derive(Debug, Clone)]
+pub struct BlendedRanker {
+    keys: Vec<String>,
+    embeddings: Option<Vec<Embedding>>,
+    /// Added to each choice's score, from past picks.
+    boosts: Vec<f64>,
+    /// Share of the score from fuzzy matching, the rest from embedding similarity.
+    pub fuzzy_weight: f64,
+    /// Choices that do not fuzzy match are shown when at least this similar.
+    pub min_semantic: f64,
+}
+impl BlendedRanker {
+    /// Without `embeddings` the ranking is purely fuzzy.
+    pub fn new(keys: Vec<String>, embeddings: Option<Vec<Embedding>>) -> Self {
+        BlendedRanker {
+            keys,
+            embeddings,
+            boosts: Vec::new(),
+            fuzzy_weight: 0.5,
+            min_semantic: 0.5,
+        }
+    }
+    /// One bonus per key, ranking choices picked before first.
+    pub fn with_boosts(mut self, boosts: Vec<f64>) -> Self {
+        self.boosts = boosts;
+        self
+    }
+    fn boost(&self, index: usize) -> f64 {
+        self.boosts.get(index).copied().unwrap_or_default()
+    }
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+    pub fn is_semantic(&self) -> bool {
+        self.embeddings.is_some()
+    }
+    /// Matching choices, best first; every choice by boost then input order when the query is
+    /// empty.
+    pub fn rank(&self, query: &str, query_embedding: Option<&Embedding>) -> Vec<RankedChoice> {
+        if query.trim().is_empty() {
+            let mut ranked = (0..self.keys.len())
+                .map(|index| RankedChoice {
+                    index,
+                    score: self.boost(index),
+                    fuzzy: None,
+                    semantic: None,
+                })
+                .collect::<Vec<_>>();
+            ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+            return ranked;
+        }
+        let mut ranked = Vec::new();
+        for (index, key) in self.keys.iter().enumerate() {
+            let fuzzy = fuzzy_score(query, key);
+            let semantic = match (&self.embeddings, query_embedding) {
+                (Some(embeddings), Some(query_embedding)) => {
+                    query_embedding.cosine_similarity(&embeddings[index]).ok()
+                }
+                _ => None,
+            };
+            let score = match semantic {
+                Some(semantic) => {
+                    if fuzzy.is_none() && semantic < self.min_semantic {
+                        continue;
+                    }
+                    self.fuzzy_weight * fuzzy.unwrap_or(0.0) + (1.0 - self.fuzzy_weight) * semantic
+                }
+                None => match fuzzy {
+                    Some(fuzzy) => fuzzy,
+                    None => continue,
+                },
+            };
+            ranked.push(RankedChoice {
+                index,
+                score: score + self.boost(index),
+                fuzzy,
+                semantic,
+            });
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nanuak_ai_router::embedding::Embedding;
+
+    use super::BlendedRanker;
+
+    fn ranker() -> BlendedRanker {
+        let keys = ["Dog", "Toaster", "Wolf"].map(String::from).to_vec();
+        let embeddings = [[0.9, 0.1, 0.4], [0.1, 0.7, 0.7], [0.9, 0.1, 0.1]]
+            .map(|vector| Embedding(vector.to_vec()))
+            .to_vec();
+        BlendedRanker::new(keys, Some(embeddings))
+    }
+
+    #[test]
+    fn blends_fuzzy_and_semantic_scores() {
+        let ranker = ranker();
+        let order = |query: &str, embedding: Option<&Embedding>| {
+            ranker
+                .rank(query, embedding)
+                .iter()
+                .map(|ranked| ranker.keys()[ranked.index].as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order("", None), ["Dog", "Toaster", "Wolf"]);
+        assert_eq!(order("to", None), ["Toaster"]);
+        assert_eq!(order("canine", None), Vec::<&str>::new());
+
+        let canine = Embedding(vec![1.0, 0.0, 0.4]);
+        assert_eq!(order("canine", Some(&canine)), ["Dog", "Wolf"]);
+        // "wo" only fuzzy matches Wolf, which also stays ahead on similarity.
+        assert_eq!(order("wo", Some(&canine))[0], "Wolf");
+    }
+
+    #[test]
+    fn ranks_boosted_choices_first() {
+        let ranker = ranker().with_boosts(vec![0.0, 0.0, 0.2]);
+        let first = |query: &str| ranker.keys()[ranker.rank(query, None)[0].index].as_str();
+        assert_eq!(first(""), "Wolf");
+        assert_eq!(first("o"), "Wolf");
+        assert_eq!(first("to"), "Toaster");
+    }
+}
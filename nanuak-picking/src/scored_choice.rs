@@ -0,0 +1,9 @@
+use cloud_terrastodon_core_user_input::prelude::Choice;
+
+#[derive(Debug)]
+pub struct ScoredChoice<T> {
+    pub choice: Choice<T>,
+    /// Cosine similarity between the choice and the query; the blended fuzzy, semantic and
+    /// history score when picked in the TUI.
+    pub score: f64,
+}
@@ -0,0 +1,19 @@
+use crate::scored_choice::ScoredChoice;
+
+/// The outcome of [`pick_many`](crate::picker::pick_many) or its TUI counterpart, each side best
+/// first with choices picked before moved up.
+#[derive(Debug)]
+pub struct Selection<T> {
+    pub chosen: Vec<ScoredChoice<T>>,
+    pub rejected: Vec<ScoredChoice<T>>,
+    /// The lowest score that was chosen, if any were.
+    pub cutoff: Option<f64>,
+}
+impl<T> Selection<T> {
+    pub fn into_values(self) -> Vec<T> {
+        self.chosen
+            .into_iter()
+            .map(|scored| scored.choice.value)
+            .collect()
+    }
+}
@@ -0,0 +1,58 @@
+/// How [`pick_many`](crate::picker::pick_many) decides which scored choices to keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionMode {
+    /// Choices scoring at least this.
+    Threshold(f64),
+    /// The `k` best choices.
+    TopK(usize),
+    /// Choices scoring within this of the best.
+    Margin(f64),
+    /// Choices above the largest drop between consecutive scores.
+    Elbow,
+    /// Choices scoring at least the threshold calibrated for the embedding model.
+    Calibrated,
+}
+impl SelectionMode {
+    /// How many of `scores`, sorted descending, to keep.
+    pub fn count(&self, scores: &[f64], calibrated_threshold: f64) -> usize {
+        match *self {
+            SelectionMode::Threshold(threshold) => scores
+                .iter()
+                .take_while(|score| **score >= threshold)
+                .count(),
+            SelectionMode::TopK(k) => k.min(scores.len()),
+            SelectionMode::Margin(margin) => match scores.first() {
+                Some(best) => scores
+                    .iter()
+                    .take_while(|score| best - *score <= margin)
+                    .count(),
+                None => 0,
+            },
+            SelectionMode::Elbow => scores
+                .windows(2)
+                .enumerate()
+                .max_by(|(_, a), (_, b)| (a[0] - a[1]).total_cmp(&(b[0] - b[1])))
+                .map(|(i, _)| i + 1)
+                .unwrap_or(scores.len()),
+            SelectionMode::Calibrated => {
+                SelectionMode::Threshold(calibrated_threshold).count(scores, calibrated_threshold)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SelectionMode;
+
+    #[test]
+    fn counts_by_mode() {
+        let scores = [0.91, 0.9, 0.87, 0.5, 0.45];
+        assert_eq!(SelectionMode::Threshold(0.88).count(&scores, 0.0), 2);
+        assert_eq!(SelectionMode::TopK(10).count(&scores, 0.0), 5);
+        assert_eq!(SelectionMode::Margin(0.05).count(&scores, 0.0), 3);
+        assert_eq!(SelectionMode::Elbow.count(&scores, 0.0), 3);
+        assert_eq!(SelectionMode::Elbow.count(&[0.3], 0.0), 1);
+        assert_eq!(SelectionMode::Calibrated.count(&scores, 0.48), 4);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::pick_thresholds::PickThresholdValues;
+use nanuak_config::pick_thresholds::PickThresholds;
+
+/// A middling cosine similarity, for models nobody has calibrated yet.
+pub const DEFAULT_FALLBACK_THRESHOLD: f64 = 0.65;
+
+/// Similarity thresholds per embedding model, since each model spreads cosine scores differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdCalibration {
+    pub thresholds: HashMap<String, f64>,
+    /// Used for models without a calibrated threshold.
+    pub fallback: f64,
+}
+impl Default for ThresholdCalibration {
+    fn default() -> Self {
+        ThresholdCalibration {
+            thresholds: HashMap::new(),
+            fallback: DEFAULT_FALLBACK_THRESHOLD,
+        }
+    }
+}
+impl From<PickThresholdValues> for ThresholdCalibration {
+    fn from(values: PickThresholdValues) -> Self {
+        ThresholdCalibration {
+            thresholds: values.models.into_iter().collect(),
+            fallback: values.fallback.unwrap_or(DEFAULT_FALLBACK_THRESHOLD),
+        }
+    }
+}
+impl From<&ThresholdCalibration> for PickThresholdValues {
+    fn from(calibration: &ThresholdCalibration) -> Self {
+        PickThresholdValues {
+            models: calibration
+                .thresholds
+                .iter()
+                .map(|(model, threshold)| (model.clone(), *threshold))
+                .collect(),
+            fallback: Some(calibration.fallback),
+        }
+    }
+}
+impl ThresholdCalibration {
+    /// The calibrations saved in the Nanuak config, or none when nothing has been saved.
+    pub async fn load() -> eyre::Result<Self> {
+        Ok(NanuakConfig::acquire()
+            .await?
+            .get_optional::<PickThresholds>()?
+            .map(Self::from)
+            .unwrap_or_default())
+    }
+    pub async fn save(&self) -> eyre::Result<()> {
+        let mut config = NanuakConfig::acquire().await?;
+        config.set::<PickThresholds>(&self.into()).await?;
+        config.save().await
+    }
+    pub fn threshold_for(&self, model: &str) -> f64 {
+        self.thresholds.get(model).copied().unwrap_or(self.fallback)
+    }
+    pub fn with_threshold(mut self, model: impl Into<String>, threshold: f64) -> Self {
+        self.thresholds.insert(model.into(), threshold);
+        self
+    }
+    /// Calibrates `model` from scores labelled by whether the choice should have been picked,
+    /// keeping the threshold that maximizes F1.
+    pub fn fit(mut self, model: impl Into<String>, labelled: &[(f64, bool)]) -> Self {
+        let mut scores = labelled.iter().map(|(score, _)| *score).collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        scores.dedup();
+        let positives = labelled.iter().filter(|(_, relevant)| *relevant).count();
+        let mut best: Option<(f64, f64)> = None;
+        for (i, score) in scores.iter().enumerate() {
+            // Halfway to the next lower score, so the threshold generalizes past the samples.
+            let threshold = match scores.get(i + 1) {
+                Some(next) => (score + next) / 2.0,
+                None => *score,
+            };
+            let true_positives = labelled
+                .iter()
+                .filter(|(score, relevant)| *relevant && *score >= threshold)
+                .count();
+            let selected = labelled
+                .iter()
+                .filter(|(score, _)| *score >= threshold)
+                .count();
+            let f1 = 2.0 * true_positives as f64 / (selected + positives).max(1) as f64;
+            if best.is_none_or(|(best_f1, _)| f1 > best_f1) {
+                best = Some((f1, threshold));
+            }
+        }
+        if let Some((_, threshold)) = best {
+            self.thresholds.insert(model.into(), threshold);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nanuak_config::pick_thresholds::PickThresholdValues;
+
+    use super::DEFAULT_FALLBACK_THRESHOLD;
+    use super::ThresholdCalibration;
+
+    #[test]
+    fn fits_threshold_between_classes() {
+        let labelled = [
+            (0.82, true),
+            (0.78, true),
+            (0.74, true),
+            (0.7, false),
+            (0.61, false),
+            (0.4, false),
+        ];
+        let calibration = ThresholdCalibration::default().fit("tiny", &labelled);
+        assert!((calibration.threshold_for("tiny") - 0.72).abs() < 1e-9);
+        assert_eq!(
+            calibration.threshold_for("unknown"),
+            DEFAULT_FALLBACK_THRESHOLD
+        );
+    }
+
+    #[test]
+    fn round_trips_through_config_values() {
+        let calibration = ThresholdCalibration::default().with_threshold("bge-m3:latest", 0.7);
+        let values = PickThresholdValues::from(&calibration);
+        assert_eq!(values.models["bge-m3:latest"], 0.7);
+        assert_eq!(ThresholdCalibration::from(values), calibration);
+
+        let partial = PickThresholdValues {
+            fallback: None,
+            ..Default::default()
+        };
+        assert_eq!(
+            ThresholdCalibration::from(partial).fallback,
+            DEFAULT_FALLBACK_THRESHOLD
+        );
+    }
+}
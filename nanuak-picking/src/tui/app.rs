@@ -0,0 +1,385 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+use std::time::Instant;
+
+use eyre::OptionExt;
+use eyre::Result;
+use nanuak_ai_router::embedding::Embedding;
+use nanuak_ai_router::embedding_request::EmbeddingPayload;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::crossterm::event::{self};
+use ratatui::widgets::ListState;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::ranker::BlendedRanker;
+use crate::ranker::RankedChoice;
+use crate::tui::terminal::PickerTerminal;
+use crate::tui::ui;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyOutcome {
+    Continue,
+    /// Indexes of the accepted choices in the picker's input.
+    Accept(Vec<usize>),
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticStatus {
+    Off,
+    Waiting,
+    Ready,
+    Failed,
+}
+
+pub struct App<'a> {
+    pub ranker: &'a BlendedRanker,
+    pub header: Option<String>,
+    pub prompt: Option<String>,
+    pub multi: bool,
+    pub query: String,
+    pub ranked: Vec<RankedChoice>,
+    pub list_state: ListState,
+    /// Indexes of the choices marked with tab, when picking many.
+    pub marked: BTreeSet<usize>,
+    pub semantic: SemanticStatus,
+
+    strategy: WellKnownEmbeddingStrategy,
+    debounce: Duration,
+    query_embedding: Option<(String, Embedding)>,
+    embed_at: Option<Instant>,
+    pending: Option<JoinHandle<Result<(String, Embedding)>>>,
+}
+
+impl<'a> App<'a> {
+    pub fn new(
+        ranker: &'a BlendedRanker,
+        strategy: WellKnownEmbeddingStrategy,
+        debounce: Duration,
+        multi: bool,
+    ) -> Self {
+        let mut app = Self {
+            ranker,
+            header: None,
+            prompt: None,
+            multi,
+            query: String::new(),
+            ranked: Vec::new(),
+            list_state: ListState::default(),
+            marked: BTreeSet::new(),
+            semantic: if ranker.is_semantic() {
+                SemanticStatus::Ready
+            } else {
+                SemanticStatus::Off
+            },
+            strategy,
+            debounce,
+            query_embedding: None,
+            embed_at: None,
+            pending: None,
+        };
+        app.rerank();
+        app
+    }
+
+    /// Ranks with the query embedding only once it matches the query, fuzzy-only until then.
+    pub fn rerank(&mut self) {
+        let embedding = self
+            .query_embedding
+            .as_ref()
+            .filter(|(query, _)| *query == self.query)
+            .map(|(_, embedding)| embedding);
+        self.ranked = self.ranker.rank(&self.query, embedding);
+        if self.ranked.is_empty() {
+            self.list_state.select(None);
+        } else {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    fn query_changed(&mut self) {
+        self.rerank();
+        if self.ranker.is_semantic() && !self.query.trim().is_empty() {
+            self.embed_at = Some(Instant::now() + self.debounce);
+            self.semantic = SemanticStatus::Waiting;
+        }
+    }
+
+    async fn update_query_embedding(&mut self) {
+        if let Some(pending) = self.pending.take_if(|pending| pending.is_finished()) {
+            match pending.await {
+                Ok(Ok((query, embedding))) => {
+                    let current = query == self.query;
+                    self.query_embedding = Some((query, embedding));
+                    if current {
+                        self.semantic = SemanticStatus::Ready;
+                        self.rerank();
+                    }
+                }
+                Ok(Err(error)) => {
+                    debug!("Failed to embed query: {:?}", error);
+                    self.semantic = SemanticStatus::Failed;
+                }
+                Err(error) => {
+                    debug!("Query embedding task failed: {:?}", error);
+                    self.semantic = SemanticStatus::Failed;
+                }
+            }
+        }
+        if self.pending.is_none() && self.embed_at.is_some_and(|when| Instant::now() >= when) {
+            self.embed_at = None;
+            let strategy = self.strategy;
+            let query = self.query.clone();
+            self.pending = Some(tokio::spawn(async move {
+                let payload = EmbeddingPayload::Text(query.clone());
+                let mut embeddings = Embedding::try_generate(strategy, vec![payload]).await?;
+                Ok((query, embeddings.remove(0)))
+            }));
+        }
+    }
+
+    pub fn highlighted(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.ranked.get(i))
+            .map(|ranked| ranked.index)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> KeyOutcome {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return KeyOutcome::Cancel,
+            KeyCode::Char('c') if control => return KeyOutcome::Cancel,
+
+            KeyCode::Enter => {
+                if self.multi && !self.marked.is_empty() {
+                    return KeyOutcome::Accept(self.marked.iter().copied().collect());
+                }
+                if let Some(index) = self.highlighted() {
+                    return KeyOutcome::Accept(vec![index]);
+                }
+            }
+
+            KeyCode::Tab if self.multi => {
+                if let Some(index) = self.highlighted() {
+                    if !self.marked.remove(&index) {
+                        self.marked.insert(index);
+                    }
+                    ui::select_next(&mut self.list_state, self.ranked.len());
+                }
+            }
+            KeyCode::Char('a') if control && self.multi => {
+                self.marked
+                    .extend(self.ranked.iter().map(|ranked| ranked.index));
+            }
+            KeyCode::Char('d') if control && self.multi => self.marked.clear(),
+
+            KeyCode::Down => ui::select_next(&mut self.list_state, self.ranked.len()),
+            KeyCode::Char('j' | 'n') if control => {
+                ui::select_next(&mut self.list_state, self.ranked.len())
+            }
+            KeyCode::Up => ui::select_previous(&mut self.list_state, self.ranked.len()),
+            KeyCode::Char('k' | 'p') if control => {
+                ui::select_previous(&mut self.list_state, self.ranked.len())
+            }
+
+            x if matches!(x, KeyCode::Char('w') | KeyCode::Backspace)
+                && control
+                && !self.query.is_empty() =>
+            {
+                self.query.clear();
+                self.query_changed();
+            }
+            KeyCode::Backspace if !self.query.is_empty() => {
+                self.query.pop();
+                self.query_changed();
+            }
+            KeyCode::Char(c) if !control => {
+                self.query.push(c);
+                self.query_changed();
+            }
+
+            _ => {}
+        }
+        KeyOutcome::Continue
+    }
+
+    /// The accepted choice indexes, or `None` when the user cancelled.
+    pub async fn run(&mut self, terminal: &mut PickerTerminal) -> Result<Option<Vec<usize>>> {
+        let (events, reader) = spawn_input_reader();
+        let result = self.run_with_events(terminal, events).await;
+        // Dropping the receiver stops the reader, which must finish before the next picker starts
+        // reading the terminal.
+        reader.await?;
+        result
+    }
+
+    async fn run_with_events(
+        &mut self,
+        terminal: &mut PickerTerminal,
+        mut events: UnboundedReceiver<std::io::Result<Event>>,
+    ) -> Result<Option<Vec<usize>>> {
+        loop {
+            self.update_query_embedding().await;
+
+            terminal.draw(|f| ui::draw(f, self))?;
+
+            // Wake up regularly to pick up the query embedding
+            let event = tokio::select! {
+                event = events.recv() => event.ok_or_eyre("Terminal input reader stopped")??,
+                _ = tokio::time::sleep(Duration::from_millis(16)) => continue,
+            };
+
+            // Only care about presses
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            };
+            match self.handle_key(key) {
+                KeyOutcome::Continue => {}
+                KeyOutcome::Accept(chosen) => return Ok(Some(chosen)),
+                KeyOutcome::Cancel => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Reads terminal events on a blocking thread so waiting for input never stalls the runtime that
+/// embeds the query. The thread stops once the receiver is dropped.
+fn spawn_input_reader() -> (UnboundedReceiver<std::io::Result<Event>>, JoinHandle<()>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let reader = tokio::task::spawn_blocking(move || {
+        while !sender.is_closed() {
+            match event::poll(Duration::from_millis(50)) {
+                Ok(false) => {}
+                Ok(true) => {
+                    if sender.send(event::read()).is_err() {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    let _ = sender.send(Err(error));
+                    break;
+                }
+            }
+        }
+    });
+    (receiver, reader)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+    use ratatui::crossterm::event::KeyCode;
+    use ratatui::crossterm::event::KeyEvent;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use super::App;
+    use super::KeyOutcome;
+    use crate::ranker::BlendedRanker;
+
+    fn ranker() -> BlendedRanker {
+        BlendedRanker::new(["Dog", "Toaster", "Wolf"].map(String::from).to_vec(), None)
+    }
+
+    fn app(ranker: &BlendedRanker, multi: bool) -> App<'_> {
+        App::new(
+            ranker,
+            WellKnownEmbeddingStrategy::BestLocal,
+            Duration::ZERO,
+            multi,
+        )
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> KeyOutcome {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn control(app: &mut App, c: char) -> KeyOutcome {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    #[test]
+    fn typing_filters_and_enter_accepts_the_highlighted_choice() {
+        let ranker = ranker();
+        let mut app = app(&ranker, false);
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(app.query, "to");
+        assert_eq!(app.highlighted(), Some(1));
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.query, "t");
+        control(&mut app, 'w');
+        assert_eq!(app.query, "");
+        assert_eq!(app.ranked.len(), 3);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        control(&mut app, 'p');
+        assert_eq!(press(&mut app, KeyCode::Enter), KeyOutcome::Accept(vec![1]));
+    }
+
+    #[test]
+    fn enter_without_matches_keeps_going() {
+        let ranker = ranker();
+        let mut app = app(&ranker, false);
+        press(&mut app, KeyCode::Char('z'));
+        assert_eq!(app.highlighted(), None);
+        assert_eq!(press(&mut app, KeyCode::Enter), KeyOutcome::Continue);
+    }
+
+    #[test]
+    fn escape_and_control_c_cancel() {
+        let ranker = ranker();
+        let mut app = app(&ranker, false);
+        assert_eq!(press(&mut app, KeyCode::Esc), KeyOutcome::Cancel);
+        assert_eq!(control(&mut app, 'c'), KeyOutcome::Cancel);
+        assert_eq!(app.query, "");
+    }
+
+    #[test]
+    fn tab_marks_only_when_picking_many() {
+        let ranker = ranker();
+        let mut single = app(&ranker, false);
+        press(&mut single, KeyCode::Tab);
+        assert!(single.marked.is_empty());
+
+        let mut app = app(&ranker, true);
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.marked.iter().copied().collect::<Vec<_>>(), [0, 2]);
+        // Tab on a marked choice unmarks it.
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.marked.iter().copied().collect::<Vec<_>>(), [0]);
+        assert_eq!(press(&mut app, KeyCode::Enter), KeyOutcome::Accept(vec![0]));
+    }
+
+    #[test]
+    fn control_a_marks_the_shown_choices_and_control_d_clears() {
+        let ranker = ranker();
+        let mut app = app(&ranker, true);
+        press(&mut app, KeyCode::Char('o'));
+        control(&mut app, 'a');
+        assert_eq!(app.marked.len(), app.ranked.len());
+        control(&mut app, 'd');
+        assert!(app.marked.is_empty());
+        assert_eq!(
+            press(&mut app, KeyCode::Enter),
+            KeyOutcome::Accept(vec![app.highlighted().unwrap()])
+        );
+    }
+}
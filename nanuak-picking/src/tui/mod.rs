@@ -0,0 +1,221 @@
+//! An interactive picker that ranks choices by fuzzy match and embedding similarity as the user
+//! types, taking and returning the same types as [`picker`](crate::picker)'s `pick` and
+//! `pick_many`.
+//!
+//! Choice embeddings are computed once per key and cached on disk; the query is embedded in the
+//! background after typing pauses. When embeddings are unavailable the picker stays usable as a
+//! plain fuzzy finder. With learning enabled, choices picked before in the same context, or
+//! similar to ones that were, rank higher.
+
+mod app;
+mod terminal;
+mod ui;
+
+use std::time::Duration;
+
+use cloud_terrastodon_core_user_input::prelude::Choice;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use eyre::bail;
+use itertools::Itertools;
+use nanuak_ai_router::embedding::Embedding;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use tracing::warn;
+
+use crate::embedding_cache::EmbeddingCache;
+use crate::history_boost::HistoryBoost;
+use crate::pick_history::PickHistory;
+use crate::pick_history::context_of;
+use crate::picked::PickResolution;
+use crate::picked::Picked;
+use crate::ranker::BlendedRanker;
+use crate::scored_choice::ScoredChoice;
+use crate::selection::Selection;
+use crate::tui::app::App;
+
+#[derive(Debug, Clone)]
+pub struct TuiOptions {
+    pub embedding_strategy: WellKnownEmbeddingStrategy,
+    /// Share of the score from fuzzy matching, the rest from embedding similarity.
+    pub fuzzy_weight: f64,
+    /// Choices that do not fuzzy match are shown when at least this similar.
+    pub min_semantic: f64,
+    /// How long typing must pause before the query is embedded.
+    pub debounce: Duration,
+    /// Rank by fuzzy match alone, without embedding anything.
+    pub fuzzy_only: bool,
+    /// Where picks are recorded and learned from; `None` leaves rankings unaffected by history.
+    pub history: Option<PickHistory>,
+}
+impl Default for TuiOptions {
+    fn default() -> Self {
+        TuiOptions {
+            embedding_strategy: WellKnownEmbeddingStrategy::BestLocal,
+            fuzzy_weight: 0.5,
+            min_semantic: 0.5,
+            debounce: Duration::from_millis(150),
+            fuzzy_only: false,
+            history: None,
+        }
+    }
+}
+impl TuiOptions {
+    /// Ranks choices picked before in the same context first and records new picks.
+    pub fn learning(mut self) -> eyre::Result<Self> {
+        self.history = Some(PickHistory::open()?);
+        Ok(self)
+    }
+}
+
+/// Prompt the user to pick one of the choices.
+pub async fn pick<T>(args: FzfArgs<Choice<T>>) -> eyre::Result<Picked<T>> {
+    pick_with_options(args, &TuiOptions::default()).await
+}
+pub async fn pick_with_options<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &TuiOptions,
+) -> eyre::Result<Picked<T>> {
+    let mut selection = run(args, options, false).await?;
+    let chosen = selection.chosen.remove(0);
+    Ok(Picked {
+        choice: chosen.choice,
+        score: chosen.score,
+        resolution: PickResolution::Interactive,
+    })
+}
+
+/// Prompt the user to pick any number of the choices, marking them with tab.
+pub async fn pick_many<T>(args: FzfArgs<Choice<T>>) -> eyre::Result<Selection<T>> {
+    pick_many_with_options(args, &TuiOptions::default()).await
+}
+pub async fn pick_many_with_options<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &TuiOptions,
+) -> eyre::Result<Selection<T>> {
+    run(args, options, true).await
+}
+
+/// Embeddings for every key, from the cache where possible, or `None` if they are unavailable.
+pub async fn choice_embeddings(
+    keys: &[String],
+    strategy: WellKnownEmbeddingStrategy,
+) -> Option<Vec<Embedding>> {
+    let path = match EmbeddingCache::default_path(strategy.get_model().name()) {
+        Ok(path) => path,
+        Err(error) => {
+            warn!(
+                "No embedding cache, ranking by fuzzy match only: {:?}",
+                error
+            );
+            return None;
+        }
+    };
+    let mut cache = EmbeddingCache::load(&path).unwrap_or_else(|error| {
+        warn!("Ignoring unreadable embedding cache: {:?}", error);
+        EmbeddingCache::default()
+    });
+    match cache.embed_all(strategy, keys).await {
+        Ok(embeddings) => {
+            if let Err(error) = cache.save(&path) {
+                warn!("Failed to save embedding cache: {:?}", error);
+            }
+            Some(embeddings)
+        }
+        Err(error) => {
+            warn!(
+                "Failed to embed choices, ranking by fuzzy match only: {:?}",
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Scores are from the final ranking, with choices the final query filtered out scoring zero.
+async fn run<T>(
+    args: FzfArgs<Choice<T>>,
+    options: &TuiOptions,
+    multi: bool,
+) -> eyre::Result<Selection<T>> {
+    if args.choices.is_empty() {
+        bail!("No choices to pick from");
+    }
+    let keys = args
+        .choices
+        .iter()
+        .map(|choice| choice.key.clone())
+        .collect_vec();
+    let context = context_of(&args);
+    let mut boost = match &options.history {
+        Some(history) => HistoryBoost::load(history, &context).await?,
+        None => HistoryBoost::default(),
+    };
+    let history_keys = boost
+        .history_keys()
+        .into_iter()
+        .filter(|key| !keys.contains(key))
+        .collect_vec();
+
+    // Past picks are embedded alongside the choices so similar new choices share their boost.
+    let embeddings = if options.fuzzy_only {
+        None
+    } else {
+        let to_embed = keys.iter().chain(&history_keys).cloned().collect_vec();
+        choice_embeddings(&to_embed, options.embedding_strategy).await
+    };
+    let embeddings = embeddings.map(|mut embeddings| {
+        let history_embeddings = embeddings.split_off(keys.len());
+        boost = std::mem::take(&mut boost)
+            .with_embeddings(keys.iter().cloned(), embeddings.iter().cloned())
+            .with_embeddings(history_keys, history_embeddings);
+        embeddings
+    });
+    let boosts = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| boost.boost(key, embeddings.as_ref().map(|embeddings| &embeddings[i])))
+        .collect_vec();
+    let mut ranker = BlendedRanker::new(keys, embeddings).with_boosts(boosts);
+    ranker.fuzzy_weight = options.fuzzy_weight;
+    ranker.min_semantic = options.min_semantic;
+
+    let mut app = App::new(&ranker, options.embedding_strategy, options.debounce, multi);
+    app.header = args.header;
+    app.prompt = args.prompt;
+
+    let mut terminal = terminal::init()?;
+    let result = app.run(&mut terminal).await;
+    terminal::restore();
+
+    let Some(accepted) = result? else {
+        bail!("Picker was cancelled");
+    };
+    if let Some(history) = &options.history {
+        let chosen_keys = accepted
+            .iter()
+            .map(|index| ranker.keys()[*index].as_str())
+            .collect_vec();
+        if let Err(error) = history.record(&context, &chosen_keys).await {
+            warn!("Failed to record pick history: {:?}", error);
+        }
+    }
+    let mut scores = vec![0.0; args.choices.len()];
+    for ranked in &app.ranked {
+        scores[ranked.index] = ranked.score;
+    }
+    let (mut chosen, mut rejected): (Vec<_>, Vec<_>) = args
+        .choices
+        .into_iter()
+        .zip(scores)
+        .enumerate()
+        .partition(|(index, _)| accepted.contains(index));
+    // Stable sorts keep equally scored choices in the order they were given.
+    chosen.sort_by(|(_, (_, a)), (_, (_, b))| b.total_cmp(a));
+    rejected.sort_by(|(_, (_, a)), (_, (_, b))| b.total_cmp(a));
+    let scored = |(_, (choice, score))| ScoredChoice { choice, score };
+    let chosen = chosen.into_iter().map(scored).collect_vec();
+    Ok(Selection {
+        cutoff: chosen.last().map(|scored| scored.score),
+        chosen,
+        rejected: rejected.into_iter().map(scored).collect(),
+    })
+}
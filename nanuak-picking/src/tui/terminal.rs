@@ -0,0 +1,38 @@
+//! The picker draws on stderr rather than stdout, like fzf draws on the terminal, so a picked
+//! choice printed to stdout can be captured with `$(…)` or `> file` without the UI.
+
+use std::io::Stderr;
+use std::io::stderr;
+use std::sync::Once;
+
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::EnterAlternateScreen;
+use ratatui::crossterm::terminal::LeaveAlternateScreen;
+use ratatui::crossterm::terminal::disable_raw_mode;
+use ratatui::crossterm::terminal::enable_raw_mode;
+
+pub type PickerTerminal = Terminal<CrosstermBackend<Stderr>>;
+
+/// Like `ratatui::init`, but on stderr, restoring the terminal before a panic is reported.
+pub fn init() -> eyre::Result<PickerTerminal> {
+    static PANIC_HOOK: Once = Once::new();
+    PANIC_HOOK.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+    });
+    enable_raw_mode()?;
+    execute!(stderr(), EnterAlternateScreen)?;
+    Ok(Terminal::new(CrosstermBackend::new(stderr()))?)
+}
+
+/// Like `ratatui::restore`, but on stderr. Failures are ignored since there is nothing left to do.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stderr(), LeaveAlternateScreen, Show);
+}
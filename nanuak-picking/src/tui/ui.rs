@@ -0,0 +1,117 @@
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::layout::Direction;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::List;
+use ratatui::widgets::ListItem;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+
+use crate::tui::app::App;
+use crate::tui::app::SemanticStatus;
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(0)].as_ref())
+        .split(f.area());
+
+    let prompt = app.prompt.clone().unwrap_or_else(|| "> ".to_string());
+    let query_line = Line::from(vec![
+        Span::styled(prompt.clone(), Style::default().fg(Color::Blue)),
+        Span::styled(
+            app.query.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    let (semantic, semantic_color) = match app.semantic {
+        SemanticStatus::Off => ("fuzzy only", Color::Gray),
+        SemanticStatus::Waiting => ("semantic: updating", Color::Yellow),
+        SemanticStatus::Ready => ("semantic: on", Color::Green),
+        SemanticStatus::Failed => ("semantic: query failed", Color::Red),
+    };
+    let mut status = vec![
+        Span::raw(format!("{}/{} ", app.ranked.len(), app.ranker.keys().len())),
+        Span::styled(semantic, Style::default().fg(semantic_color)),
+    ];
+    if app.multi {
+        status.push(Span::raw(format!(" | {} marked", app.marked.len())));
+    }
+    let input = Paragraph::new(vec![query_line, Line::from(status)]).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(app.header.clone().unwrap_or_default()),
+    );
+    f.render_widget(input, chunks[0]);
+    f.set_cursor_position((
+        chunks[0].x + 1 + (prompt.chars().count() + app.query.chars().count()) as u16,
+        chunks[0].y + 1,
+    ));
+
+    let items: Vec<ListItem> = app
+        .ranked
+        .iter()
+        .map(|ranked| {
+            let marker = if app.marked.contains(&ranked.index) {
+                Span::styled("● ", Style::default().fg(Color::Magenta))
+            } else {
+                Span::raw("  ")
+            };
+            let mut spans = vec![marker, Span::raw(app.ranker.keys()[ranked.index].clone())];
+            if let Some(semantic) = ranked.semantic {
+                spans.push(Span::styled(
+                    format!("  {:.2}", semantic),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(list, chunks[1], &mut app.list_state);
+}
+
+pub fn select_next(state: &mut ListState, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let i = match state.selected() {
+        Some(i) => {
+            if i >= len - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    state.select(Some(i));
+}
+
+pub fn select_previous(state: &mut ListState, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let i = match state.selected() {
+        Some(i) => {
+            if i == 0 {
+                len - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    state.select(Some(i));
+}
@@ -1,9 +1,14 @@
 use std::collections::HashSet;
+use std::path::Path;
 
 use cloud_terrastodon_core_user_input::prelude::Choice;
 use cloud_terrastodon_core_user_input::prelude::FzfArgs;
 use itertools::Itertools;
-use nanuak_picking::picker::pick_many;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_picking::pick_many_options::PickManyOptions;
+use nanuak_picking::picker::pick_many_with_options;
+use nanuak_picking::selection_mode::SelectionMode;
 use strum::VariantArray;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
@@ -38,6 +43,11 @@ async fn main() -> eyre::Result<()> {
         )
         .without_time()
         .init();
+    let _mock = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "../nanuak-ai-router/tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .install();
 
     let choices = Word::VARIANTS
         .iter()
@@ -47,15 +57,26 @@ async fn main() -> eyre::Result<()> {
         })
         .collect_vec();
 
-    let chosen = pick_many(FzfArgs {
-        choices,
+    let args = || FzfArgs {
+        choices: choices
+            .iter()
+            .map(|choice| Choice {
+                key: choice.key.clone(),
+                value: choice.value,
+            })
+            .collect_vec(),
         header: Some("animal".to_string()),
         ..Default::default()
-    })
-    .await?
-    .into_iter()
-    .map(|x| x.value)
-    .collect::<HashSet<_>>();
+    };
+    let options = |mode| PickManyOptions::new(mode).with_strategy(WellKnownEmbeddingStrategy::Mock);
+
+    let selection = pick_many_with_options(args(), &options(SelectionMode::Calibrated)).await?;
+    assert_eq!(
+        selection.chosen.len() + selection.rejected.len(),
+        choices.len()
+    );
+    assert!(selection.cutoff.is_some_and(|cutoff| cutoff >= 0.65));
+    let chosen = selection.into_values().into_iter().collect::<HashSet<_>>();
 
     let expected = [
         Word::Dog,
@@ -71,5 +92,33 @@ async fn main() -> eyre::Result<()> {
     .collect::<HashSet<_>>();
 
     assert_eq!(chosen, expected);
+
+    let elbow = pick_many_with_options(args(), &options(SelectionMode::Elbow))
+        .await?
+        .into_values()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(elbow, expected);
+
+    // Wolf, Elephant and Whale share a fixture vector, so they tie and keep their enum order.
+    let top = pick_many_with_options(args(), &options(SelectionMode::TopK(3))).await?;
+    assert!(
+        top.chosen
+            .iter()
+            .all(|scored| scored.score == top.chosen[0].score)
+    );
+    assert_eq!(
+        top.into_values(),
+        [&Word::Wolf, &Word::Elephant, &Word::Whale]
+    );
+
+    let margin = pick_many_with_options(args(), &options(SelectionMode::Margin(0.01))).await?;
+    let scores = margin
+        .chosen
+        .iter()
+        .map(|scored| scored.score)
+        .collect_vec();
+    assert_eq!(scores.len(), 5);
+    assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
     Ok(())
 }
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_picking::embedding_cache::EmbeddingCache;
+
+#[tokio::test]
+async fn caches_choice_embeddings_across_runs() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "../nanuak-ai-router/tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .install();
+    let path = std::env::temp_dir()
+        .join(format!("nanuak-picking-{}", std::process::id()))
+        .join("cache.bin");
+
+    let keys = ["Dog", "Cat", "Dog"].map(String::from);
+    let mut cache = EmbeddingCache::load(&path)?;
+    assert!(cache.is_empty());
+    let embeddings = cache
+        .embed_all(WellKnownEmbeddingStrategy::Mock, &keys)
+        .await?;
+    assert_eq!(embeddings.len(), 3);
+    assert_eq!(embeddings[0], embeddings[2]);
+    assert_eq!(cache.len(), 2);
+    cache.save(&path)?;
+
+    let reloaded = EmbeddingCache::load(&path)?;
+    assert_eq!(reloaded.len(), 2);
+    assert_eq!(reloaded.get("Cat"), Some(embeddings[1].clone()));
+    std::fs::remove_dir_all(path.parent().unwrap())?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use cloud_terrastodon_core_user_input::prelude::Choice;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::generative_text_strategy::WellKnownGenerativeTextStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_ai_router::providers::mock_generative_text_provider::MockGenerativeTextProvider;
+use nanuak_picking::pick_options::AmbiguityPolicy;
+use nanuak_picking::pick_options::PickOptions;
+use nanuak_picking::picked::PickResolution;
+use nanuak_picking::picker::pick_with_options;
+
+fn args(header: &str) -> FzfArgs<Choice<&'static str>> {
+    FzfArgs {
+        choices: ["House", "Refrigerator", "Toaster", "Wolf"]
+            .map(|key| Choice {
+                key: key.to_string(),
+                value: key,
+            })
+            .to_vec(),
+        header: Some(header.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn picks_confidently_or_resolves_ambiguity() -> eyre::Result<()> {
+    let _embeddings = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "../nanuak-ai-router/tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .with_fixture("home", vec![0.1, 0.3, 0.9])
+    .with_fixture("kitchen appliance", vec![0.1, 0.7, 0.7])
+    .with_fixture("nothing", vec![-1.0, 0.0, 0.0])
+    .install();
+    let llm = MockGenerativeTextProvider::new().with_rule("kitchen appliance", "2. Toaster");
+    let _generative_text = llm.clone().install();
+    let options = PickOptions::default()
+        .with_strategy(WellKnownEmbeddingStrategy::Mock)
+        .non_interactive();
+
+    let picked = pick_with_options(args("home"), &options).await?;
+    assert_eq!(picked.choice.value, "House");
+    assert_eq!(picked.resolution, PickResolution::Confident);
+    assert!(picked.score > 0.99);
+
+    let refuse = options.clone().on_ambiguous(AmbiguityPolicy::Refuse);
+    let error = pick_with_options(args("kitchen appliance"), &refuse)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("Refrigerator") && error.contains("Toaster"),
+        "{error}"
+    );
+    assert!(!error.contains("House"), "{error}");
+
+    let tie_break = options.clone().on_ambiguous(AmbiguityPolicy::TieBreak(
+        WellKnownGenerativeTextStrategy::Mock,
+    ));
+    let picked = pick_with_options(args("kitchen appliance"), &tie_break).await?;
+    assert_eq!(picked.choice.value, "Toaster");
+    assert_eq!(picked.resolution, PickResolution::TieBreak);
+    let conversations = llm.conversations();
+    let asked = &conversations[0].messages.last().unwrap().content;
+    assert!(asked.contains("1. Refrigerator\n2. Toaster\n"), "{asked}");
+
+    let error = pick_with_options(args("nothing"), &options)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("well enough"), "{error}");
+    Ok(())
+}
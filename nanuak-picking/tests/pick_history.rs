@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use chrono::TimeDelta;
+use cloud_terrastodon_core_user_input::prelude::Choice;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
+use nanuak_ai_router::embedding_strategy::WellKnownEmbeddingStrategy;
+use nanuak_ai_router::providers::mock_embedding_provider::MockEmbeddingProvider;
+use nanuak_picking::pick_history::PickHistory;
+use nanuak_picking::pick_history::PickRecord;
+use nanuak_picking::pick_history::aged;
+use nanuak_picking::pick_many_options::PickManyOptions;
+use nanuak_picking::picker::pick_many_with_options;
+use nanuak_picking::scored_choice::ScoredChoice;
+use nanuak_picking::selection_mode::SelectionMode;
+
+fn args(header: &str) -> FzfArgs<Choice<&'static str>> {
+    FzfArgs {
+        choices: ["Dog", "Cat", "Wolf", "Whale"]
+            .map(|key| Choice {
+                key: key.to_string(),
+                value: key,
+            })
+            .to_vec(),
+        header: Some(header.to_string()),
+        ..Default::default()
+    }
+}
+
+fn temp_history(name: &str) -> PickHistory {
+    PickHistory {
+        path: std::env::temp_dir()
+            .join(format!(
+                "nanuak-pick-history-{}-{}",
+                name,
+                std::process::id()
+            ))
+            .join("history.jsonl"),
+    }
+}
+
+#[tokio::test]
+async fn learns_from_picks_per_context() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "../nanuak-ai-router/tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .with_fixture("pet", vec![0.9, 0.1, 0.4])
+    .install();
+    let history = temp_history("learns");
+    let options = PickManyOptions::new(SelectionMode::TopK(1))
+        .with_strategy(WellKnownEmbeddingStrategy::Mock)
+        .with_history(history.clone());
+
+    // Dog and Cat share a vector, so without history Dog wins by input order.
+    let first = pick_many_with_options(args("pet"), &options).await?;
+    assert_eq!(first.into_values(), ["Dog"]);
+    history.record("pet", &["Cat", "Cat"]).await?;
+    let learned = pick_many_with_options(args("pet"), &options).await?;
+    assert_eq!(learned.into_values(), ["Cat"]);
+
+    // Another context is unaffected by picks made for "pet".
+    history.record("pet", &["Whale"]).await?;
+    let other = pick_many_with_options(args("animal"), &options).await?;
+    assert_eq!(other.into_values(), ["Wolf"]);
+
+    // Choices picked automatically are not recorded, only the picks the user confirmed.
+    let records = history.load(Some("pet")).await?;
+    assert_eq!(
+        records
+            .iter()
+            .map(|record| record.key.as_str())
+            .collect::<Vec<_>>(),
+        ["Cat", "Cat", "Whale"]
+    );
+    assert_eq!(history.load(None).await?.len(), 3);
+    assert_eq!(history.clear(Some("pet")).await?, 3);
+    assert_eq!(history.load(None).await?.len(), 0);
+    std::fs::remove_dir_all(history.path.parent().unwrap())?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn thresholds_ignore_the_history_boost() -> eyre::Result<()> {
+    let _mock = MockEmbeddingProvider::from_fixture_file(Path::new(
+        "../nanuak-ai-router/tests/fixtures/animal_embeddings.json",
+    ))
+    .await?
+    .with_fixture("pet", vec![0.9, 0.1, 0.4])
+    .install();
+    let history = temp_history("thresholds");
+    history.record("pet", &["Whale"; 20]).await?;
+    history.record("pet", &["Cat"]).await?;
+    let options = PickManyOptions::new(SelectionMode::Threshold(0.99))
+        .with_strategy(WellKnownEmbeddingStrategy::Mock)
+        .with_history(history.clone());
+
+    // Whale is boosted past Dog and Cat but is still less similar than the threshold.
+    let selection = pick_many_with_options(args("pet"), &options).await?;
+    let keys = |side: &[ScoredChoice<&'static str>]| {
+        side.iter()
+            .map(|scored| scored.choice.value)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&selection.chosen), ["Cat", "Dog"]);
+    assert_eq!(keys(&selection.rejected), ["Whale", "Wolf"]);
+    assert_eq!(selection.cutoff, Some(selection.chosen[0].score));
+    std::fs::remove_dir_all(history.path.parent().unwrap())?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn skips_unreadable_lines() -> eyre::Result<()> {
+    let history = temp_history("unreadable");
+    history.record("pet", &["Dog"]).await?;
+    let mut jsonl = std::fs::read_to_string(&history.path)?;
+    jsonl.push_str("{\"context\": \"pet\", \"key\": \"Ca");
+    std::fs::write(&history.path, jsonl)?;
+
+    let records = history.load(Some("pet")).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key, "Dog");
+    std::fs::remove_dir_all(history.path.parent().unwrap())?;
+    Ok(())
+}
+
+#[test]
+fn ages_out_old_and_rare_picks() {
+    let now = NaiveDateTime::default() + TimeDelta::days(30);
+    let records = (0..10)
+        .map(|i| PickRecord {
+            context: "repo".to_string(),
+            key: if i < 8 { "nanuak" } else { "dotfiles" }.to_string(),
+            picked_at: now - TimeDelta::days(10 - i),
+        })
+        .chain([PickRecord {
+            context: "repo".to_string(),
+            key: "terrastodon".to_string(),
+            picked_at: now - TimeDelta::days(20),
+        }])
+        .collect::<Vec<_>>();
+
+    assert_eq!(aged(records.clone(), 20), records);
+    let aged = aged(records.clone(), 10);
+    // Each key keeps about 80% of its picks, the most recent ones, but never less than one.
+    assert_eq!(
+        aged.iter()
+            .map(|record| record.key.as_str())
+            .collect::<Vec<_>>(),
+        ["nanuak"; 6]
+            .into_iter()
+            .chain(["dotfiles", "terrastodon"])
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(aged[0].picked_at, records[2].picked_at);
+    assert_eq!(aged[6].picked_at, records[9].picked_at);
+}
+
+#[test]
+fn ages_one_off_picks_by_keeping_the_newest() {
+    let now = NaiveDateTime::default() + TimeDelta::days(30);
+    let records = (0..2001)
+        .map(|i| PickRecord {
+            context: "file".to_string(),
+            key: format!("file-{i}"),
+            picked_at: now - TimeDelta::minutes(2001 - i),
+        })
+        .collect::<Vec<_>>();
+
+    let aged = aged(records.clone(), 2000);
+    assert_eq!(aged.len(), 1800);
+    assert_eq!(aged, records[201..]);
+}
@@ -1,7 +1,11 @@
 use clap::Parser;
+use cloud_terrastodon_core_user_input::prelude::Choice;
+use cloud_terrastodon_core_user_input::prelude::FzfArgs;
 use color_eyre::eyre::Result;
 use color_eyre::eyre::WrapErr;
 use ignore::WalkBuilder;
+use nanuak_ai_router::telemetry::install_postgres;
+use nanuak_picking::tui;
 use std::collections::HashSet;
 use std::fs::OpenOptions;
 use std::fs::{self};
@@ -14,10 +18,9 @@ use std::process::Command;
 use std::process::Stdio;
 use tracing::debug;
 use tracing::level_filters::LevelFilter;
+use tracing::warn;
 use tracing_subscriber::EnvFilter;
 
-mod fzf;
-
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
@@ -42,7 +45,8 @@ struct Cli {
     editor: String,
 }
 
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Setup logging
@@ -57,6 +61,15 @@ fn main() -> Result<()> {
     tracing_subscriber::fmt().with_env_filter(env_filter).init();
     color_eyre::install()?;
 
+    let telemetry = install_postgres("nanuak-prompting");
+    let result = run(cli).await;
+    if let Err(error) = telemetry.flush().await {
+        warn!("Failed to write telemetry: {:?}", error);
+    }
+    result
+}
+
+async fn run(cli: Cli) -> Result<()> {
     // Read existing files from files.txt into a Set<PathBuf>
     let mut tracked_files = read_tracked_files(&cli.files_txt)?;
 
@@ -66,20 +79,29 @@ fn main() -> Result<()> {
     let show = "Show";
     let write_prompt = "WritePrompt";
     let quit = "Quit";
-    let actions = vec![add, remove, show, write_prompt, quit];
+    let actions = [add, remove, show, write_prompt, quit];
 
     loop {
-        let action = fzf::pick(fzf::FzfArgs {
-            choices: actions.clone(),
+        let action = tui::pick(FzfArgs {
+            choices: actions
+                .iter()
+                .map(|action| Choice {
+                    key: action.to_string(),
+                    value: *action,
+                })
+                .collect(),
             prompt: Some("Choose an action".to_string()),
             header: None,
-        })?;
+        })
+        .await?
+        .choice
+        .value;
         debug!("Chose action: {}", action);
 
         match action {
             "Add" => {
                 // Let user pick multiple unignored files not already in our set
-                let new_files = pick_files_to_add(&cli.path, &tracked_files)?;
+                let new_files = pick_files_to_add(&cli.path, &tracked_files).await?;
                 for f in new_files {
                     tracked_files.insert(f);
                 }
@@ -87,14 +109,14 @@ fn main() -> Result<()> {
             }
             "Remove" => {
                 // Let user pick from the currently tracked set
-                let removed_files = pick_files_to_remove(&tracked_files)?;
+                let removed_files = pick_files_to_remove(&tracked_files).await?;
                 for f in removed_files {
                     tracked_files.remove(&f);
                 }
                 write_tracked_files(&cli.files_txt, &tracked_files)?;
             }
             "Show" => {
-                show_tracked_files(&tracked_files)?;
+                show_tracked_files(&tracked_files).await?;
             }
             "WritePrompt" => {
                 write_prompt_file(&cli.output_file, &tracked_files)?;
@@ -148,8 +170,11 @@ fn write_tracked_files(path: &Path, tracked_files: &HashSet<PathBuf>) -> Result<
     Ok(())
 }
 
-/// Let user pick multiple new files to add using FZF, skipping those already in the set.
-fn pick_files_to_add(base_path: &Path, tracked_files: &HashSet<PathBuf>) -> Result<Vec<PathBuf>> {
+/// Let user pick multiple new files to add in the picker, skipping those already in the set.
+async fn pick_files_to_add(
+    base_path: &Path,
+    tracked_files: &HashSet<PathBuf>,
+) -> Result<Vec<PathBuf>> {
     debug!("Picking files to add from {:?}", base_path);
     let all_unignored = get_unignored_files(base_path)?;
     let filtered = all_unignored
@@ -163,51 +188,49 @@ fn pick_files_to_add(base_path: &Path, tracked_files: &HashSet<PathBuf>) -> Resu
         return Ok(vec![]);
     }
 
-    let chosen = fzf::pick_many(fzf::FzfArgs {
+    let chosen = tui::pick_many(FzfArgs {
         choices: filtered
             .into_iter()
-            .map(|path| fzf::Choice {
+            .map(|path| Choice {
                 key: path.display().to_string(),
                 value: path,
             })
             .collect(),
         prompt: Some("Select files to add".to_string()),
         header: None,
-    })?
-    .into_iter()
-    .map(|x| x.value)
-    .collect();
+    })
+    .await?
+    .into_values();
     Ok(chosen)
 }
 
 /// Let user pick multiple from the currently tracked set to remove.
-fn pick_files_to_remove(tracked_files: &HashSet<PathBuf>) -> Result<Vec<PathBuf>> {
+async fn pick_files_to_remove(tracked_files: &HashSet<PathBuf>) -> Result<Vec<PathBuf>> {
     if tracked_files.is_empty() {
         println!("No files to remove.");
         return Ok(vec![]);
     }
 
-    // Convert to a Vec to hand over to FZF
+    // Convert to a Vec to hand over to the picker
     let choices = tracked_files.iter().cloned().collect::<Vec<_>>();
-    let chosen = fzf::pick_many(fzf::FzfArgs {
+    let chosen = tui::pick_many(FzfArgs {
         choices: choices
             .into_iter()
-            .map(|path| fzf::Choice {
+            .map(|path| Choice {
                 key: path.display().to_string(),
                 value: path,
             })
             .collect(),
         prompt: Some("Select files to remove".to_string()),
         header: None,
-    })?
-    .into_iter()
-    .map(|x| x.value)
-    .collect();
+    })
+    .await?
+    .into_values();
     Ok(chosen)
 }
 
 /// Show tracked files in the terminal
-fn show_tracked_files(tracked_files: &HashSet<PathBuf>) -> eyre::Result<()> {
+async fn show_tracked_files(tracked_files: &HashSet<PathBuf>) -> eyre::Result<()> {
     if tracked_files.is_empty() {
         println!("No tracked files.");
         return Ok(());
@@ -215,17 +238,18 @@ fn show_tracked_files(tracked_files: &HashSet<PathBuf>) -> eyre::Result<()> {
     let mut entries: Vec<_> = tracked_files.iter().collect();
     entries.sort_by_key(|p| p.display().to_string());
 
-    let _ = fzf::pick(fzf::FzfArgs {
+    let _ = tui::pick(FzfArgs {
         choices: entries
             .into_iter()
-            .map(|p| fzf::Choice {
+            .map(|p| Choice {
                 key: p.display().to_string(),
                 value: p,
             })
             .collect(),
         prompt: Some("Press Enter to continue".to_string()),
         header: Some("Tracked files".to_string()),
-    })?;
+    })
+    .await?;
     Ok(())
 }
 
@@ -1,2 +1,2 @@
-. .\Get-DatabaseUrl.ps1
-diesel migration run
\ No newline at end of file
+cargo run --bin nanuak-schema --features cli -- migrate
+cargo run --bin nanuak-schema --features cli -- status
\ No newline at end of file
@@ -1,6 +1,2 @@
-diesel migration list
-# ask are you sure
-$resp = Read-Host "Are you sure you want to undo the last migration? (y/n)"
-if ($resp -eq "y") {
-    diesel migration revert
-}
\ No newline at end of file
+cargo run --bin nanuak-schema --features cli -- status
+cargo run --bin nanuak-schema --features cli -- rollback
\ No newline at end of file
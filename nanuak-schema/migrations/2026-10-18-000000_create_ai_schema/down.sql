@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS ai.calls;
+DROP SCHEMA IF EXISTS ai CASCADE;
@@ -0,0 +1,17 @@
+CREATE SCHEMA IF NOT EXISTS ai;
+
+CREATE TABLE ai.calls (
+    id BIGSERIAL PRIMARY KEY,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    modality TEXT NOT NULL,       -- embedding, generative_text, ...
+    input_tokens INTEGER,         -- NULL when the provider doesn't report it
+    output_tokens INTEGER,
+    latency_ms BIGINT NOT NULL,
+    success BOOLEAN NOT NULL,
+    error TEXT,
+    tool TEXT NOT NULL,           -- the binary that made the call
+    called_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX calls_model_called_at_idx ON ai.calls (model, called_at);
@@ -0,0 +1 @@
+ALTER TABLE ai.calls DROP COLUMN prompt;
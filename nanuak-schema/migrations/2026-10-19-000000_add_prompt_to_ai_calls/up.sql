@@ -0,0 +1 @@
+ALTER TABLE ai.calls ADD COLUMN prompt TEXT; -- template name@version, for generative calls
@@ -0,0 +1,12 @@
+-- Embeddings written through nanuak-ai-router's EmbeddingStore, grouped into named collections.
+-- The vector has no fixed dimension so collections can use different models.
+CREATE TABLE ai.embeddings (
+    collection TEXT NOT NULL,
+    id TEXT NOT NULL,
+    embedding vector NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (collection, id)
+);
+
+CREATE INDEX embeddings_metadata_idx ON ai.embeddings USING GIN (metadata);
@@ -0,0 +1 @@
+ALTER TABLE memes DROP COLUMN prompt;
@@ -0,0 +1,7 @@
+-- nanuak-memes has so far created this table by hand, so adopt it if it already exists.
+CREATE TABLE IF NOT EXISTS memes (
+    id SERIAL PRIMARY KEY,
+    file_path TEXT NOT NULL,
+    description TEXT NOT NULL
+);
+ALTER TABLE memes ADD COLUMN prompt TEXT; -- template name@version that produced the description
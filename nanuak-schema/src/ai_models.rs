@@ -0,0 +1,36 @@
+use crate::ai::calls;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = calls)]
+pub struct Call {
+    pub id: i64,
+    pub provider: String,
+    pub model: String,
+    pub modality: String,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub latency_ms: i64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub tool: String,
+    pub called_at: NaiveDateTime,
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = calls)]
+pub struct NewCall {
+    pub provider: String,
+    pub model: String,
+    pub modality: String,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub latency_ms: i64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub tool: String,
+    pub called_at: NaiveDateTime,
+    pub prompt: Option<String>,
+}
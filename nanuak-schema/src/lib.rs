@@ -1,10 +1,14 @@
+mod ai_schema;
 mod files_schema;
 mod git_schema;
 mod youtube_schema;
 
+pub mod ai_models;
 pub mod files_models;
 pub mod git_models;
+pub mod migrations;
 
+pub use ai_schema::ai;
 pub use files_schema::files;
 pub use git_schema::git;
 pub use youtube_schema::youtube;
@@ -0,0 +1,105 @@
+use clap::Parser;
+use clap::Subcommand;
+use diesel::Connection;
+use diesel::PgConnection;
+use nanuak_config::config::NanuakConfig;
+use nanuak_config::db_url::DatabasePassword;
+use nanuak_schema::migrations::migration_statuses;
+use nanuak_schema::migrations::revert_migrations;
+use nanuak_schema::migrations::run_pending_migrations;
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Nanuak database migrations")]
+struct Cli {
+    /// If set, enable debug logging
+    #[arg(long)]
+    debug: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Apply every pending migration
+    Migrate,
+    /// List migrations and whether each is applied
+    Status,
+    /// Revert the most recently applied migrations
+    Rollback {
+        /// How many migrations to revert
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+
+    let log_level = if cli.debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
+    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+
+    let database_url = DatabasePassword::format_url(
+        &NanuakConfig::acquire()
+            .await?
+            .get::<DatabasePassword>()
+            .await?,
+    );
+    let mut conn = PgConnection::establish(&database_url)?;
+
+    match cli.command {
+        Commands::Migrate => {
+            let applied = run_pending_migrations(&mut conn)?;
+            if applied.is_empty() {
+                info!("Schema is up to date");
+            }
+            for version in applied {
+                info!("Applied {}", version);
+            }
+        }
+        Commands::Status => {
+            for migration in migration_statuses(&mut conn)? {
+                println!(
+                    "[{}] {}{}",
+                    if migration.applied { "x" } else { " " },
+                    migration.name,
+                    if migration.embedded {
+                        ""
+                    } else {
+                        " (applied by a newer nanuak-schema)"
+                    }
+                );
+            }
+        }
+        Commands::Rollback { count, yes } => {
+            if !yes {
+                println!("Revert the last {} migration(s)? (y/n)", count);
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if answer.trim() != "y" {
+                    info!("Nothing reverted");
+                    return Ok(());
+                }
+            }
+            for version in revert_migrations(&mut conn, count)? {
+                info!("Reverted {}", version);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! The migrations in `nanuak-schema/migrations`, compiled into every binary that depends on
+//! this crate so they can be applied and checked without the diesel CLI.
+
+use std::collections::HashSet;
+
+use diesel::migration::MigrationSource;
+use diesel::pg::Pg;
+use diesel::PgConnection;
+use diesel_migrations::embed_migrations;
+use diesel_migrations::EmbeddedMigrations;
+use diesel_migrations::MigrationHarness;
+use eyre::bail;
+use eyre::eyre;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// The migration's directory name, e.g. `2024-12-08-162117_create_youtube_schema`, or just
+    /// its version when this binary doesn't embed it.
+    pub name: String,
+    pub version: String,
+    pub applied: bool,
+    /// False for migrations the database applied from a newer schema than this binary's.
+    pub embedded: bool,
+}
+
+/// Every embedded migration and every applied one this binary doesn't know about, in version
+/// order, with whether the database has applied it.
+pub fn migration_statuses(conn: &mut PgConnection) -> eyre::Result<Vec<MigrationStatus>> {
+    let applied = conn
+        .applied_migrations()
+        .map_err(|error| eyre!(error))?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect::<HashSet<_>>();
+    let embedded = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|error| eyre!(error))?
+        .iter()
+        .map(|migration| {
+            (
+                migration.name().to_string(),
+                migration.name().version().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(merge_statuses(embedded, applied))
+}
+
+/// Pairs the embedded `(name, version)`s with the applied versions.
+fn merge_statuses(
+    embedded: Vec<(String, String)>,
+    mut applied: HashSet<String>,
+) -> Vec<MigrationStatus> {
+    let mut migrations = embedded
+        .into_iter()
+        .map(|(name, version)| MigrationStatus {
+            name,
+            applied: applied.remove(&version),
+            version,
+            embedded: true,
+        })
+        .collect::<Vec<_>>();
+    migrations.extend(applied.into_iter().map(|version| MigrationStatus {
+        name: version.clone(),
+        version,
+        applied: true,
+        embedded: false,
+    }));
+    migrations.sort_by(|a, b| a.version.cmp(&b.version));
+    migrations
+}
+
+/// Names of the embedded migrations the database has not applied, in order.
+pub fn pending_migrations(conn: &mut PgConnection) -> eyre::Result<Vec<String>> {
+    Ok(conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|error| eyre!(error))?
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
+
+/// Applies every pending migration, returning the versions applied.
+pub fn run_pending_migrations(conn: &mut PgConnection) -> eyre::Result<Vec<String>> {
+    Ok(conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|error| eyre!(error))?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect())
+}
+
+/// Reverts the `count` most recently applied migrations, returning their versions.
+pub fn revert_migrations(conn: &mut PgConnection, count: usize) -> eyre::Result<Vec<String>> {
+    let mut reverted = Vec::new();
+    for _ in 0..count {
+        let version = conn
+            .revert_last_migration(MIGRATIONS)
+            .map_err(|error| eyre!(error))?;
+        reverted.push(version.to_string());
+    }
+    Ok(reverted)
+}
+
+/// Refuses to continue against a database that is missing migrations this binary was built with.
+pub fn ensure_schema_current(conn: &mut PgConnection) -> eyre::Result<()> {
+    let pending = pending_migrations(conn)?;
+    if !pending.is_empty() {
+        bail!(
+            "The database schema is out of date, {} migration(s) pending:\n  {}\nRun `cargo run --bin nanuak-schema --features cli -- migrate` to apply them.",
+            pending.len(),
+            pending.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use diesel::migration::MigrationSource;
+    use diesel::pg::Pg;
+
+    use super::merge_statuses;
+    use super::MIGRATIONS;
+
+    #[test]
+    fn lists_applied_migrations_this_binary_does_not_know() {
+        let embedded = [
+            ("2024-12-08-162117_create_youtube_schema", "20241208162117"),
+            ("2025-01-02-000000_add_files", "20250102000000"),
+        ]
+        .map(|(name, version)| (name.to_string(), version.to_string()))
+        .to_vec();
+        let applied = ["20241208162117", "20991231000000"]
+            .map(String::from)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let statuses = merge_statuses(embedded, applied);
+        let summary = statuses
+            .iter()
+            .map(|status| (status.name.as_str(), status.applied, status.embedded))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("2024-12-08-162117_create_youtube_schema", true, true),
+                ("2025-01-02-000000_add_files", false, true),
+                ("20991231000000", true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn embeds_every_migration_directory() {
+        let directories = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().into_string().unwrap())
+            .collect::<HashSet<_>>();
+        let embedded = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+            .unwrap()
+            .iter()
+            .map(|migration| migration.name().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(embedded, directories);
+    }
+}
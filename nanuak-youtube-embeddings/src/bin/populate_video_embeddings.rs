@@ -5,6 +5,7 @@ use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use itertools::Itertools;
+use nanuak_schema::migrations::ensure_schema_current;
 use nanuak_schema::youtube::video_embeddings_bge_m3;
 use nanuak_youtube_embeddings::count_videos_needing_embeddings;
 use nanuak_youtube_embeddings::load_videos_needing_embeddings;
@@ -126,6 +127,7 @@ async fn main() -> Result<()> {
 
     let ollama = Ollama::default();
     let mut conn = pool.get()?;
+    ensure_schema_current(&mut conn)?;
 
     // Count how many videos are remaining at the start
     let mut remaining = count_videos_needing_embeddings(&mut conn)?;
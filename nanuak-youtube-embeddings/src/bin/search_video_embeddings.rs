@@ -5,6 +5,7 @@ use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::sql_types::Float8;
 use diesel::sql_types::Text;
+use nanuak_schema::migrations::ensure_schema_current;
 use ollama_rs::generation::embeddings::request::GenerateEmbeddingsRequest;
 use ollama_rs::Ollama;
 use pgvector::Vector;
@@ -58,6 +59,7 @@ async fn main() -> Result<()> {
     info!("Connecting to database...");
     let manager = ConnectionManager::<PgConnection>::new(std::env::var("DATABASE_URL")?);
     let pool = Pool::builder().build(manager)?;
+    ensure_schema_current(&mut *pool.get()?)?;
 
     let ollama = Ollama::default();
 
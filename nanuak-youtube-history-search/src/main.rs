@@ -4,6 +4,7 @@ use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use nanuak_config::config::NanuakConfig;
 use nanuak_config::db_url::DatabasePassword;
+use nanuak_schema::migrations::ensure_schema_current;
 use tracing::info;
 
 // Bring in our modules:
@@ -30,6 +31,7 @@ async fn main() -> Result<()> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = Pool::builder().build(manager)?;
     let mut conn = pool.get()?;
+    ensure_schema_current(&mut conn)?;
     info!("Established database connection");
 
     // Initialize the terminal
@@ -14,6 +14,7 @@ use itertools::Itertools;
 use nanuak_config::config::NanuakConfig;
 use nanuak_config::db_url::DatabasePassword;
 use nanuak_config::youtube_api_key::YouTubeApiKey;
+use nanuak_schema::migrations::ensure_schema_current;
 use nanuak_schema::youtube;
 use reqwest::Client;
 use serde::Deserialize;
@@ -409,6 +410,7 @@ async fn main() -> Result<()> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = Pool::builder().build(manager)?;
     let mut conn = pool.get()?;
+    ensure_schema_current(&mut conn)?;
     info!("Established database connection");
 
     let api_key = config.get::<YouTubeApiKey>().await?;
@@ -12,6 +12,7 @@ use entry::load_entries;
 use entry::Entry;
 use nanuak_config::config::NanuakConfig;
 use nanuak_config::db_url::DatabasePassword;
+use nanuak_schema::migrations::ensure_schema_current;
 use nanuak_schema::youtube::posts::dsl as posts_dsl;
 use nanuak_schema::youtube::search_history::dsl as search_dsl;
 use nanuak_schema::youtube::watch_history::dsl as watch_dsl;
@@ -46,6 +47,7 @@ async fn main() -> Result<()> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = Pool::builder().build(manager)?;
     let mut conn = pool.get()?;
+    ensure_schema_current(&mut conn)?;
     info!("Established database connection");
 
     // Read the directory and process JSON files